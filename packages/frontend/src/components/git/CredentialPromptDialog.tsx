@@ -0,0 +1,97 @@
+import { useEffect, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { KeyRound } from 'lucide-react';
+import * as api from '@/lib/api';
+import type { GitCredentialPrompt } from '@/lib/api';
+
+const KIND_TITLES: Record<GitCredentialPrompt['kind'], string> = {
+  username: 'Git 用户名',
+  password: 'Git 密码',
+  passphrase: 'SSH 密钥口令',
+  hostKey: '确认 SSH 主机',
+  other: 'Git 凭据',
+};
+
+/**
+ * git / ssh 通过 askpass 请求凭据时弹出，按到达顺序逐个回答
+ */
+export default function CredentialPromptDialog() {
+  const [queue, setQueue] = useState<GitCredentialPrompt[]>([]);
+  const [value, setValue] = useState('');
+  const [remember, setRemember] = useState(false);
+  const current = queue[0];
+
+  useEffect(() => {
+    const unlisten = listen<GitCredentialPrompt>('git-credential-prompt', (event) => {
+      setQueue((q) => [...q, event.payload]);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const answer = async (text: string | null) => {
+    if (!current) return;
+    setQueue((q) => q.slice(1));
+    setValue('');
+    setRemember(false);
+    try {
+      await api.gitCredentialRespond(current.id, text, remember);
+    } catch (e) {
+      console.warn('[Git] 回答凭据提示失败:', e);
+    }
+  };
+
+  if (!current) return null;
+
+  return (
+    <div className="dialog-overlay fixed inset-0 bg-black/40 flex justify-center pt-[16vh] z-50">
+      <div className="dialog-content bg-base/95 backdrop-blur-xl border border-surface0/60 rounded-xl shadow-2xl w-[420px] flex flex-col">
+        <div className="flex items-center gap-2 px-4 py-3 border-b border-surface0">
+          <KeyRound size={14} className="text-accent" />
+          <span className="text-sm font-semibold text-text">{KIND_TITLES[current.kind]}</span>
+        </div>
+
+        <form
+          className="px-4 py-3 space-y-3"
+          onSubmit={(e) => {
+            e.preventDefault();
+            answer(current.kind === 'hostKey' ? 'yes' : value);
+          }}
+        >
+          <div className="text-xs text-subtext1 break-all whitespace-pre-wrap">{current.prompt}</div>
+          {current.kind !== 'hostKey' && (
+            <input
+              autoFocus
+              type={current.secret ? 'password' : 'text'}
+              value={value}
+              onChange={(e) => setValue(e.target.value)}
+              className="w-full px-2 py-1.5 text-xs bg-crust border border-surface0 rounded text-text placeholder:text-overlay0 focus:outline-none focus:border-accent"
+            />
+          )}
+          {current.canRemember && (
+            <label className="flex items-center gap-2 text-xs text-subtext0">
+              <input type="checkbox" checked={remember} onChange={(e) => setRemember(e.target.checked)} />
+              保存到系统凭据管理器
+            </label>
+          )}
+          <div className="flex gap-2 pt-1">
+            <button
+              type="button"
+              onClick={() => answer(null)}
+              className="flex-1 py-1.5 text-xs rounded-lg bg-surface1 text-subtext1 hover:bg-surface2 transition-all duration-200 active:scale-[0.97]"
+            >
+              取消
+            </button>
+            <button
+              type="submit"
+              className="flex-1 py-1.5 text-xs font-medium rounded-lg bg-accent text-crust hover:bg-accent/80 transition-all duration-200 active:scale-[0.97]"
+            >
+              {current.kind === 'hostKey' ? '信任并继续' : '确定'}
+            </button>
+          </div>
+        </form>
+      </div>
+    </div>
+  );
+}
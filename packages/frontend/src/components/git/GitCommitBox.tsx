@@ -17,23 +17,28 @@ export default function GitCommitBox() {
   const syncing = useGitStore((s) => s.syncing);
   const sync = useGitStore((s) => s.sync);
   const [generating, setGenerating] = useState(false);
+  const [amend, setAmend] = useState(false);
+  const [signoff, setSignoff] = useState(false);
   const shortcuts = useConfigStore((s) => s.config.shortcuts);
 
   const hasStagedFiles = files.some((f) => f.staged);
   const hasUnstagedFiles = files.some((f) => !f.staged);
 
   const handleCommit = useCallback(async () => {
-    if (!commitMessage.trim()) return;
+    // amend 不填信息时沿用原提交信息
+    if (!commitMessage.trim() && !amend) return;
     // 获取最新的 files 状态
     const currentFiles = useGitStore.getState().files;
     const currentHasStaged = currentFiles.some((f) => f.staged);
     const currentHasUnstaged = currentFiles.some((f) => !f.staged);
 
-    if (!currentHasStaged && currentHasUnstaged) {
+    if (!currentHasStaged && currentHasUnstaged && !amend) {
       await useGitStore.getState().stageAll();
     }
-    await useGitStore.getState().commit();
-  }, [commitMessage]);
+    if (await useGitStore.getState().commit({ amend, signoff })) {
+      setAmend(false);
+    }
+  }, [commitMessage, amend, signoff]);
 
   const generateCommitMessage = useCallback(async () => {
     setGenerating(true);
@@ -100,12 +105,22 @@ export default function GitCommitBox() {
           }
         }}
       />
+      <div className="flex items-center gap-3 mt-1 text-xs text-subtext0">
+        <label className="flex items-center gap-1 cursor-pointer" title="修改上一次提交（git commit --amend）">
+          <input type="checkbox" checked={amend} onChange={(e) => setAmend(e.target.checked)} />
+          <span>修改上次提交</span>
+        </label>
+        <label className="flex items-center gap-1 cursor-pointer" title="追加 Signed-off-by 尾注">
+          <input type="checkbox" checked={signoff} onChange={(e) => setSignoff(e.target.checked)} />
+          <span>Sign-off</span>
+        </label>
+      </div>
       <button
         onClick={handleCommit}
-        disabled={!commitMessage.trim()}
+        disabled={!commitMessage.trim() && !amend}
         className="w-full mt-1.5 py-1 text-xs font-medium rounded-lg bg-accent text-crust hover:bg-accent/80 disabled:opacity-40 disabled:cursor-not-allowed transition-all duration-200 active:scale-[0.97] shadow-[0_0_12px_var(--color-accent)/20]"
       >
-        提交 (Ctrl+Enter)
+        {amend ? '修改提交' : '提交'} (Ctrl+Enter)
       </button>
       {hasRemote && (
         <button
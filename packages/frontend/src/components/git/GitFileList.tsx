@@ -2,7 +2,6 @@ import { useState, useEffect, useRef, useCallback } from 'react';
 import { Plus, Minus, ChevronRight, ChevronDown, EyeOff, RotateCcw } from 'lucide-react';
 import { useGitStore } from '@/stores/gitStore';
 import { useEditorStore } from '@/stores/editorStore';
-import { useWorkspaceStore } from '@/stores/workspaceStore';
 import * as api from '@/lib/api';
 import { showError } from '@/stores/errorStore';
 
@@ -22,6 +21,13 @@ const STATUS_LABELS: Record<string, string> = {
   renamed: 'R',
 };
 
+/** 文件扩展名；没有扩展名或是 .env 这类点开头的文件时返回 null */
+function extensionOf(filePath: string): string | null {
+  const name = filePath.split(/[\\/]/).pop() ?? '';
+  const dot = name.lastIndexOf('.');
+  return dot > 0 && dot < name.length - 1 ? name.slice(dot + 1) : null;
+}
+
 export default function GitFileList() {
   const { files, stageFiles, unstageFiles, discardFiles, fetchStatus } = useGitStore();
   const [ctxMenu, setCtxMenu] = useState<{ x: number; y: number; filePath: string } | null>(null);
@@ -55,22 +61,17 @@ export default function GitFileList() {
     }
   }, [discardFiles]);
 
-  const addToGitignore = useCallback(async (filePath: string) => {
-    const root = useWorkspaceStore.getState().currentPath;
-    if (!root) return;
-    const sep = root.includes('/') ? '/' : '\\';
-    const ignorePath = root + sep + '.gitignore';
-    let content = '';
-    try {
-      const res = await api.readFile(ignorePath);
-      content = res.content;
-    } catch { /* .gitignore 不存在，新建 */ }
-    const entry = filePath.replace(/\\/g, '/');
-    if (content.split('\n').some((l) => l.trim() === entry)) return;
-    const newContent = content.endsWith('\n') || content === '' ? content + entry + '\n' : content + '\n' + entry + '\n';
-    await api.writeFile(ignorePath, newContent);
-    await fetchStatus();
+  const addToGitignore = useCallback(async (filePath: string, kind: api.GitIgnoreKind = 'file') => {
     setCtxMenu(null);
+    try {
+      const res = await api.gitIgnoreAdd({ path: filePath, kind });
+      if (res.tracked.length > 0 && confirm(`${res.tracked.length} 个已跟踪的文件不受 ${res.pattern} 影响，是否停止跟踪（保留本地文件）？`)) {
+        await api.gitIgnoreAdd({ path: filePath, kind, untrack: true });
+      }
+      await fetchStatus();
+    } catch (error) {
+      showError('添加忽略规则失败', error, '添加忽略规则失败');
+    }
   }, [fetchStatus]);
 
   return (
@@ -121,6 +122,15 @@ export default function GitFileList() {
             <EyeOff size={14} />
             添加到 .gitignore
           </button>
+          {extensionOf(ctxMenu.filePath) && (
+            <button
+              className="flex items-center gap-2 w-full px-3 py-1.5 text-xs text-left text-text hover:bg-surface0 transition-colors"
+              onClick={() => addToGitignore(ctxMenu.filePath, 'extension')}
+            >
+              <EyeOff size={14} />
+              忽略所有 *.{extensionOf(ctxMenu.filePath)} 文件
+            </button>
+          )}
           <button
             className="flex items-center gap-2 w-full px-3 py-1.5 text-xs text-left text-red hover:bg-red/10 transition-colors"
             onClick={() => handleDiscard(ctxMenu.filePath)}
@@ -1,17 +1,35 @@
 import { useEffect, useState, useCallback, useRef } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import { GitBranch, RefreshCw, X, GitPullRequest, FolderGit2 } from 'lucide-react';
 import { useGitStore } from '@/stores/gitStore';
 import GitFileList from './GitFileList';
 import GitCommitBox from './GitCommitBox';
 import GitActions from './GitActions';
 import GitGraph from './GitGraph';
+import { gitRemoveStaleLock, type GitQueueState, type GitSyncStatus } from '@/lib/api';
+
+const OPERATION_LABELS: Record<string, string> = {
+  stage: '暂存',
+  unstage: '取消暂存',
+  discard: '放弃更改',
+  commit: '提交',
+  checkout: '切换分支',
+  pull: '拉取',
+  'tag-delete': '删除标签',
+  stash: '储藏',
+  reset: '重置',
+  rebase: '变基',
+  'cherry-pick': '拣选',
+  revert: '还原提交',
+  ignore: '添加忽略规则',
+};
 
 // 模块级变量，切换面板时不会丢失
 let _graphOpen = true;
 let _graphRatio = 0.4;
 
 export default function GitPanel() {
-  const { branch, error, clearError, fetchStatus, fetchBranch, fetchLog, fetchSyncStatus, init } = useGitStore();
+  const { branch, error, queue, clearError, fetchStatus, fetchBranch, fetchLog, fetchSyncStatus, init } = useGitStore();
   const [refreshing, setRefreshing] = useState(false);
   const [graphOpen, _setGraphOpen] = useState(_graphOpen);
   const [graphRatio, _setGraphRatio] = useState(_graphRatio);
@@ -57,6 +75,27 @@ export default function GitPanel() {
     }
   }, [hasGitRepo, fetchStatus, fetchBranch, fetchLog, fetchSyncStatus]);
 
+  // 后台 fetch 或本地 git 操作改变了引用时，由后端推送最新的 ahead/behind
+  useEffect(() => {
+    const unlisten = listen<GitSyncStatus>('git-sync-changed', (event) => {
+      const { ahead, behind, hasRemote } = event.payload;
+      useGitStore.setState({ ahead, behind, hasRemote });
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // 后端串行执行写操作，显示正在执行和排队的操作
+  useEffect(() => {
+    const unlisten = listen<GitQueueState>('git-queue-changed', (event) => {
+      useGitStore.setState({ queue: event.payload });
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   // 组件卸载时清理拖拽事件监听器
   useEffect(() => {
     return () => {
@@ -75,6 +114,20 @@ export default function GitPanel() {
     setRefreshing(false);
   }, [fetchStatus, fetchBranch, fetchLog, fetchSyncStatus]);
 
+  // 报错提示 index.lock 是残留锁文件时，确认后删除并重新读取状态
+  const handleRemoveStaleLock = useCallback(async () => {
+    if (!window.confirm('确认没有其他 git 进程在运行，删除残留的 index.lock？')) return;
+    try {
+      const { removed } = await gitRemoveStaleLock();
+      if (removed) {
+        clearError();
+        await fetchStatus();
+      }
+    } catch (e) {
+      useGitStore.setState({ error: String(e) });
+    }
+  }, [clearError, fetchStatus]);
+
   const handleInitGit = useCallback(async () => {
     setInitializing(true);
     const success = await init();
@@ -125,6 +178,13 @@ export default function GitPanel() {
         <div className="flex items-center gap-2 text-xs font-medium text-subtext0 uppercase tracking-wider">
           <GitBranch size={14} />
           <span>源代码管理</span>
+          {queue?.running && (
+            <span className="normal-case tracking-normal text-[11px] text-overlay0 flex items-center gap-1">
+              <RefreshCw size={10} className="animate-spin" />
+              {OPERATION_LABELS[queue.running.name] ?? queue.running.name}
+              {queue.waiting.length > 0 && ` (+${queue.waiting.length})`}
+            </span>
+          )}
         </div>
         <button
           onClick={handleRefresh}
@@ -188,6 +248,11 @@ export default function GitPanel() {
             {error && (
               <div className="flex items-start gap-1.5 px-3 py-1.5 bg-red/10 border-b border-red/20 text-xs text-red">
                 <span className="flex-1 break-all">{error}</span>
+                {error.includes('index.lock') && error.includes('残留') && (
+                  <button onClick={handleRemoveStaleLock} className="shrink-0 px-1.5 py-0.5 hover:bg-red/20 rounded">
+                    删除锁文件
+                  </button>
+                )}
                 <button onClick={clearError} className="shrink-0 p-0.5 hover:bg-red/20 rounded">
                   <X size={12} />
                 </button>
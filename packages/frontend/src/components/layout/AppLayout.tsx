@@ -16,6 +16,7 @@ import TerminalPanel from '../terminal/TerminalPanel';
 import FolderBrowserDialog from '../workspace/FolderBrowserDialog';
 import QuickOpenDialog from '../quickopen/QuickOpenDialog';
 import SettingsDialog from '../settings/SettingsDialog';
+import CredentialPromptDialog from '../git/CredentialPromptDialog';
 
 export default function AppLayout() {
   const sidebarVisible = useLayoutStore((s) => s.sidebarVisible);
@@ -98,6 +99,7 @@ export default function AppLayout() {
       <FolderBrowserDialog />
       <QuickOpenDialog />
       <SettingsDialog />
+      <CredentialPromptDialog />
     </div>
   );
 }
@@ -203,6 +203,51 @@ function GeneralTab({
           </button>
         </div>
       </Field>
+      <Field label="后台自动 Fetch 间隔（秒，0 为关闭）">
+        <input
+          type="number"
+          min={0}
+          step={60}
+          value={form.git.autoFetchInterval}
+          onChange={(e) =>
+            setForm({ ...form, git: { ...form.git, autoFetchInterval: Math.max(0, Number(e.target.value) || 0) } })
+          }
+          className="w-24 px-2 py-1.5 text-xs bg-crust border border-surface0 rounded text-text placeholder:text-overlay0 focus:outline-none focus:border-accent"
+        />
+      </Field>
+      <Field label="大文件暂存提示阈值（MB，0 为关闭）">
+        <input
+          type="number"
+          min={0}
+          step={10}
+          value={form.git.lfsThresholdMb}
+          onChange={(e) =>
+            setForm({ ...form, git: { ...form.git, lfsThresholdMb: Math.max(0, Number(e.target.value) || 0) } })
+          }
+          className="w-24 px-2 py-1.5 text-xs bg-crust border border-surface0 rounded text-text placeholder:text-overlay0 focus:outline-none focus:border-accent"
+        />
+      </Field>
+      <Field label="Git 读取方式">
+        <div className="flex items-center gap-2">
+          {([
+            ['cli', 'git 命令行'],
+            ['libgit2', '进程内（libgit2）'],
+          ] as const).map(([value, label]) => (
+            <button
+              key={value}
+              type="button"
+              onClick={() => setForm({ ...form, git: { ...form.git, backend: value } })}
+              className={`px-2 py-1 text-[11px] rounded-md border transition-colors ${
+                form.git.backend === value
+                  ? 'border-accent text-text'
+                  : 'border-surface0 text-subtext0 hover:text-text hover:border-accent'
+              }`}
+            >
+              {label}
+            </button>
+          ))}
+        </div>
+      </Field>
     </>
   );
 }
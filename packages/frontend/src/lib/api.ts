@@ -61,11 +61,20 @@ export async function listFiles(): Promise<string[]> {
 // ===== Git =====
 
 export async function gitStatus(): Promise<GitFileStatus[]> {
-  return invoke('git_status');
+  const status = await invoke<{ files: GitFileStatus[] }>('git_status');
+  return status.files;
 }
 
-export async function gitStage(paths: string[]): Promise<void> {
-  return invoke('git_stage', { paths });
+export interface GitStageResult {
+  ok: boolean;
+  /** 超过阈值且未走 LFS 的文件；存在时未执行暂存 */
+  largeFiles?: { path: string; size: number }[];
+  thresholdMb?: number;
+  lfsInstalled?: boolean;
+}
+
+export async function gitStage(paths: string[], force?: boolean): Promise<GitStageResult> {
+  return invoke('git_stage', { paths, force: force || null });
 }
 
 export async function gitUnstage(paths: string[]): Promise<void> {
@@ -76,10 +85,38 @@ export interface GitCommitResult {
   ok: boolean;
   hash?: string;
   message?: string;
-}
-
-export async function gitCommit(message: string): Promise<GitCommitResult> {
-  return invoke('git_commit', { message });
+  /** 钩子和 git 的输出行 */
+  output?: string[];
+  /** 被钩子改动的文件 */
+  modifiedFiles?: string[];
+}
+
+export interface GitCommitOptions {
+  amend?: boolean;
+  /** 追加 Signed-off-by 尾注 */
+  signoff?: boolean;
+  /** 覆盖作者，格式 "Name <email>" */
+  author?: string;
+  allowEmpty?: boolean;
+  /** 只提交这些路径的工作区内容，其余已暂存的改动保持不动 */
+  paths?: string[];
+  /** 跳过 pre-commit / commit-msg 钩子 */
+  noVerify?: boolean;
+  timeoutSecs?: number;
+  taskId?: string;
+}
+
+/** git_commit 失败时 reject 的结构，按 kind 区分 */
+export type GitCommitError =
+  | { kind: 'signing'; message: string }
+  | { kind: 'hook'; message: string; output: string[]; modifiedFiles: string[] }
+  | { kind: 'timedOut' }
+  | { kind: 'cancelled' }
+  | { kind: 'git'; message: string };
+
+/** amend 时 message 可以为空，沿用原提交信息 */
+export async function gitCommit(message: string, options?: GitCommitOptions): Promise<GitCommitResult> {
+  return invoke('git_commit', { message: message || null, options: options || null });
 }
 
 export async function gitBranches(): Promise<GitBranchInfo> {
@@ -87,7 +124,8 @@ export async function gitBranches(): Promise<GitBranchInfo> {
 }
 
 export async function gitLog(): Promise<GitLogEntry[]> {
-  return invoke('git_log');
+  const page = await invoke<{ entries: GitLogEntry[]; nextCursor: string | null }>('git_log');
+  return page.entries;
 }
 
 export interface GitShowResult {
@@ -98,31 +136,353 @@ export interface GitShowResult {
   email: string;
   date: string;
   body: string;
-  files: { file: string; stats: string }[];
+  parents: string[];
+  /** 实际比较的父提交，根提交为 null */
+  parent: string | null;
+  files: {
+    file: string;
+    oldFile: string;
+    status: string;
+    additions: number | null;
+    deletions: number | null;
+    binary: boolean;
+    stats: string;
+  }[];
+}
+
+/** parent 从 1 开始，选择合并提交比较的父提交 */
+export async function gitShow(hash: string, parent?: number): Promise<GitShowResult> {
+  return invoke('git_show', { hash, parent: parent || null });
+}
+
+export async function gitFileDiff(hash: string, file: string, parent?: number): Promise<string> {
+  return invoke('git_file_diff', { hash, file, parent: parent || null });
+}
+
+export interface GitFileHistoryQuery {
+  path: string;
+  cursor?: string;
+  limit?: number;
+  /** 行范围历史（git log -L），与 function 二选一 */
+  startLine?: number;
+  endLine?: number;
+  function?: string;
+}
+
+export interface GitFileHistoryEntry {
+  hash: string;
+  shortHash: string;
+  parents: string[];
+  author: string;
+  email: string;
+  date: string;
+  message: string;
+  /** 该版本中的文件路径（跟随重命名） */
+  path?: string;
+  oldPath?: string | null;
+  status?: string | null;
+  /** 行范围历史时该提交对范围内代码的补丁 */
+  patch?: string;
+}
+
+export async function gitFileHistory(
+  query: GitFileHistoryQuery,
+): Promise<{ entries: GitFileHistoryEntry[]; nextCursor: string | null }> {
+  return invoke('git_file_history', { query });
+}
+
+export async function gitFileRevisionDiff(
+  from: { hash: string; path: string },
+  to: { hash: string; path: string },
+): Promise<{ oldContent: string; newContent: string; file: string; oldFile: string }> {
+  return invoke('git_file_revision_diff', { from, to });
+}
+
+export type GitCompareMode = 'two-dot' | 'three-dot';
+
+export interface GitCompareFile {
+  status: string;
+  path: string;
+  oldPath: string;
+  /** 二进制文件为 null */
+  additions: number | null;
+  deletions: number | null;
+  binary: boolean;
+  oldContent?: string;
+  newContent?: string;
+  /** 超出内联体积上限，需要用 gitCompareFile 单独读取 */
+  contentOmitted?: boolean;
+}
+
+export interface GitCompareResult {
+  base: string;
+  head: string;
+  files: GitCompareFile[];
+  commits: { hash: string; shortHash: string; message: string }[];
+}
+
+export async function gitCompare(base: string, head: string, mode?: GitCompareMode): Promise<GitCompareResult> {
+  return invoke('git_compare', { base, head, mode: mode || null });
+}
+
+export async function gitCompareFile(
+  base: string,
+  head: string,
+  file: string,
+  options?: { mode?: GitCompareMode; oldFile?: string },
+): Promise<{ oldContent: string; newContent: string; file: string }> {
+  return invoke('git_compare_file', {
+    base,
+    head,
+    file,
+    mode: options?.mode || null,
+    oldFile: options?.oldFile || null,
+  });
 }
 
-export async function gitShow(hash: string): Promise<GitShowResult> {
-  return invoke('git_show', { hash });
+export type GitDiffSource =
+  | { kind: 'commit'; hash: string; file: string; parent?: number }
+  | { kind: 'working'; file: string; staged?: boolean }
+  | { kind: 'revisions'; from: { hash: string; path: string }; to: { hash: string; path: string } }
+  | { kind: 'text'; oldText: string; newText: string };
+
+export interface GitDiffOptions {
+  ignoreWhitespace?: boolean;
+  contextLines?: number;
+  algorithm?: 'myers' | 'patience' | 'histogram';
+  /** 行内高亮粒度，默认 word */
+  inline?: 'word' | 'char' | 'none';
 }
 
-export async function gitFileDiff(hash: string, file: string): Promise<string> {
-  return invoke('git_file_diff', { hash, file });
+export interface GitDiffLine {
+  kind: 'context' | 'add' | 'delete';
+  oldLine: number | null;
+  newLine: number | null;
+  /** 不含行尾 */
+  content: string;
+  /** 行尾；只改换行符或文件末尾缺换行时，内容相同而 eol 不同 */
+  eol: 'lf' | 'crlf' | 'none';
+  /** 行内改动范围 [start, end)，按 JS 字符串下标 */
+  highlights: [number, number][];
+}
+
+export interface GitDiffHunk {
+  oldStart: number;
+  oldLines: number;
+  newStart: number;
+  newLines: number;
+  header: string;
+  lines: GitDiffLine[];
+}
+
+export interface GitDiffHunksResult {
+  file?: string;
+  oldFile?: string;
+  hunks: GitDiffHunk[];
+  additions: number;
+  deletions: number;
+  /** 二进制、子模块、LFS 文件不返回 hunk，只带各自的元数据 */
+  binary?: boolean;
+  submodule?: unknown;
+  lfs?: unknown;
+}
+
+export async function gitDiffHunks(source: GitDiffSource, options?: GitDiffOptions): Promise<GitDiffHunksResult> {
+  return invoke('git_diff_hunks', { source, options: options || null });
 }
 
 export async function gitStagedDiff(): Promise<{ stat: string; diff: string; files: { status: string; path: string }[] }> {
   return invoke('git_staged_diff');
 }
 
-export async function gitSyncStatus(): Promise<{ ahead: number; behind: number; hasRemote: boolean }> {
+export interface GitSyncStatus {
+  ahead: number;
+  behind: number;
+  hasRemote: boolean;
+}
+
+export async function gitSyncStatus(): Promise<GitSyncStatus> {
   return invoke('git_sync_status');
 }
 
-export async function gitPush(): Promise<{ ok: boolean }> {
-  return invoke('git_push');
+export interface GitQueuedOperation {
+  id: number;
+  /** 操作名，如 stage、commit、checkout */
+  name: string;
+  queuedAt: number;
+  startedAt: number | null;
+}
+
+/** 串行执行的写操作队列；`git-queue-changed` 事件推送同样的结构（不含 staleLock） */
+export interface GitQueueState {
+  cwd: string;
+  running: GitQueuedOperation | null;
+  waiting: GitQueuedOperation[];
+  /** 长时间未更新、可能是残留的 index.lock */
+  staleLock?: { path: string; ageSecs: number } | null;
+}
+
+export async function gitQueueState(): Promise<GitQueueState> {
+  return invoke('git_queue_state');
+}
+
+export async function gitRemoveStaleLock(): Promise<{ ok: boolean; removed: boolean }> {
+  return invoke('git_remove_stale_lock');
+}
+
+export type GitRemoteStatus = 'ok' | 'rejected' | 'diverged' | 'staleLease' | 'conflicts' | 'cancelled' | 'timedOut';
+
+export interface GitRemoteResult {
+  ok: boolean;
+  status: GitRemoteStatus;
+  taskId: string;
+  message?: string;
+  conflicts?: { path: string; conflict: string }[];
+}
+
+export interface GitPushOptions {
+  remote?: string;
+  branch?: string;
+  setUpstream?: boolean;
+  forceWithLease?: boolean;
+  taskId?: string;
+  timeoutSecs?: number;
 }
 
-export async function gitPull(): Promise<{ ok: boolean }> {
-  return invoke('git_pull');
+export interface GitPullOptions {
+  remote?: string;
+  branch?: string;
+  strategy?: 'merge' | 'rebase' | 'ff-only';
+  taskId?: string;
+  timeoutSecs?: number;
+}
+
+export interface GitFetchOptions {
+  remote?: string;
+  prune?: boolean;
+  taskId?: string;
+  timeoutSecs?: number;
+}
+
+export async function gitPush(options?: GitPushOptions): Promise<GitRemoteResult> {
+  return invoke('git_push', { options: options || null });
+}
+
+export async function gitPull(options?: GitPullOptions): Promise<GitRemoteResult> {
+  return invoke('git_pull', { options: options || null });
+}
+
+export async function gitFetch(options?: GitFetchOptions): Promise<GitRemoteResult> {
+  return invoke('git_fetch', { options: options || null });
+}
+
+export interface GitCredentialPrompt {
+  id: number;
+  prompt: string;
+  kind: 'username' | 'password' | 'passphrase' | 'hostKey' | 'other';
+  target: string | null;
+  secret: boolean;
+  canRemember: boolean;
+}
+
+/** 回答 askpass 的凭据提示；answer 为 null 表示取消 */
+export async function gitCredentialRespond(id: number, answer: string | null, remember?: boolean): Promise<{ ok: boolean }> {
+  return invoke('git_credential_respond', { id, answer, remember: remember || null });
+}
+
+export interface GitSubmodule {
+  name: string;
+  path: string;
+  /** 用 workspaceStore.openFolder 把子模块作为独立仓库打开 */
+  absolutePath: string;
+  url: string;
+  branch: string | null;
+  recorded: string | null;
+  checkedOut: string | null;
+  initialized: boolean;
+  populated: boolean;
+  outOfDate: boolean;
+  dirty: boolean;
+}
+
+export async function gitSubmodules(): Promise<GitSubmodule[]> {
+  return invoke('git_submodules');
+}
+
+export async function gitSubmoduleInit(paths?: string[]): Promise<{ ok: boolean }> {
+  return invoke('git_submodule_init', { paths: paths || null });
+}
+
+export async function gitSubmoduleSync(paths?: string[], recursive?: boolean): Promise<{ ok: boolean }> {
+  return invoke('git_submodule_sync', { paths: paths || null, recursive: recursive ?? null });
+}
+
+export async function gitSubmoduleUpdate(
+  options?: { paths?: string[]; init?: boolean; recursive?: boolean; remote?: boolean },
+): Promise<GitRemoteResult> {
+  return invoke('git_submodule_update', {
+    paths: options?.paths || null,
+    init: options?.init ?? null,
+    recursive: options?.recursive ?? null,
+    remote: options?.remote ?? null,
+  });
+}
+
+export interface GitLfsObject {
+  path: string;
+  oid: string | null;
+  size: number;
+  /** 本地对象库中是否已有该对象 */
+  present: boolean;
+}
+
+export interface GitLfsStatus {
+  installed: boolean;
+  patterns: string[];
+  files: GitLfsObject[];
+  missing: number;
+}
+
+export async function gitLfsStatus(): Promise<GitLfsStatus> {
+  return invoke('git_lfs_status');
+}
+
+export async function gitLfsTrack(patterns: string[]): Promise<{ ok: boolean; added: string[] }> {
+  return invoke('git_lfs_track', { patterns });
+}
+
+export type GitIgnoreKind = 'file' | 'folder' | 'extension';
+export type GitIgnoreTarget = 'root' | 'nested' | 'exclude';
+
+export interface GitIgnoreOptions {
+  path: string;
+  kind?: GitIgnoreKind;
+  target?: GitIgnoreTarget;
+  untrack?: boolean;
+}
+
+export interface GitIgnoreResult {
+  ok: boolean;
+  file: string;
+  pattern: string;
+  added: boolean;
+  tracked: string[];
+  untracked: string[];
+}
+
+export async function gitIgnoreAdd(options: GitIgnoreOptions): Promise<GitIgnoreResult> {
+  return invoke('git_ignore_add', { options });
+}
+
+export interface GitIgnoreRule {
+  source: string;
+  line: number | null;
+  pattern: string;
+  negated: boolean;
+}
+
+export async function gitCheckIgnore(path: string): Promise<{ path: string; ignored: boolean; tracked: boolean; rule: GitIgnoreRule | null }> {
+  return invoke('git_check_ignore', { path });
 }
 
 export async function gitWorkingDiff(file: string, staged?: boolean): Promise<string> {
@@ -158,6 +518,10 @@ export interface OpenWorkspaceResult {
   projectName: string;
 }
 
+export async function gitWorktreeOpen(path: string, shellType?: 'powershell' | 'cmd'): Promise<OpenWorkspaceResult & { ptyId: number }> {
+  return invoke('git_worktree_open', { path, shellType: shellType || null });
+}
+
 export async function openWorkspace(path: string): Promise<OpenWorkspaceResult> {
   return invoke('open_workspace', { path });
 }
@@ -217,11 +581,21 @@ export interface AiConfig {
   customPrompt: string;
 }
 
+export interface GitConfig {
+  /** 后台自动 fetch 间隔（秒），0 表示关闭 */
+  autoFetchInterval: number;
+  /** 暂存超过该大小（MB）且未走 LFS 的文件时提示，0 表示不检查 */
+  lfsThresholdMb: number;
+  /** status、log、show、分支和文件内容等读操作的实现，写操作始终走 git 命令行 */
+  backend: 'cli' | 'libgit2';
+}
+
 export interface AppConfig {
   terminalFontSize: number;
   editorFontSize: number;
   shortcuts: Shortcuts;
   ai: AiConfig;
+  git: GitConfig;
 }
 
 export async function getConfig(): Promise<AppConfig> {
@@ -1,9 +1,9 @@
 import { create } from 'zustand';
 import * as api from '@/lib/api';
-import type { Shortcuts, AiConfig } from '@/lib/api';
+import type { Shortcuts, AiConfig, GitConfig } from '@/lib/api';
 import { showError } from './errorStore';
 
-export type { Shortcuts, AiConfig };
+export type { Shortcuts, AiConfig, GitConfig };
 
 export const TERMINAL_FONT_SIZE_MIN = 10;
 export const TERMINAL_FONT_SIZE_MAX = 24;
@@ -29,6 +29,7 @@ export interface AppConfig {
   editorFontSize: number;
   shortcuts: Shortcuts;
   ai: AiConfig;
+  git: GitConfig;
 }
 
 interface ConfigState {
@@ -54,11 +55,18 @@ const DEFAULT_AI: AiConfig = {
   customPrompt: '',
 };
 
+const DEFAULT_GIT: GitConfig = {
+  autoFetchInterval: 0,
+  lfsThresholdMb: 50,
+  backend: 'cli',
+};
+
 const DEFAULT_CONFIG: AppConfig = {
   terminalFontSize: DEFAULT_TERMINAL_FONT_SIZE,
   editorFontSize: DEFAULT_EDITOR_FONT_SIZE,
   shortcuts: DEFAULT_SHORTCUTS,
   ai: DEFAULT_AI,
+  git: DEFAULT_GIT,
 };
 
 export const useConfigStore = create<ConfigState>((set, get) => ({
@@ -90,6 +98,7 @@ export const useConfigStore = create<ConfigState>((set, get) => ({
           ...config,
           shortcuts: { ...DEFAULT_SHORTCUTS, ...config.shortcuts },
           ai: { ...DEFAULT_AI, ...config.ai },
+          git: { ...DEFAULT_GIT, ...config.git },
         },
         loading: false,
       });
@@ -113,6 +122,7 @@ export const useConfigStore = create<ConfigState>((set, get) => ({
         : clampEditorFontSize(updates.editorFontSize),
       shortcuts: updates.shortcuts ? { ...current.shortcuts, ...updates.shortcuts } : current.shortcuts,
       ai: updates.ai ? { ...current.ai, ...updates.ai } : current.ai,
+      git: updates.git ? { ...current.git, ...updates.git } : current.git,
     };
     try {
       await api.saveConfig(updated);
@@ -79,6 +79,44 @@ function parseSshGitBranches(output: string): GitBranchInfo {
   return { current, branches };
 }
 
+// git_commit 失败时 reject 的是按 kind 区分的对象，转成可读的提示
+function commitErrorMessage(e: unknown): string {
+  if (typeof e === 'string') return e || '提交失败';
+  const error = (e && typeof e === 'object' ? e : null) as api.GitCommitError | null;
+  switch (error?.kind) {
+    case 'signing':
+      return `提交签名失败：${error.message}`;
+    case 'hook': {
+      const modified = error.modifiedFiles.length ? `\n钩子修改了文件：${error.modifiedFiles.join(', ')}` : '';
+      return `提交钩子未通过：${error.message}${modified}`;
+    }
+    case 'timedOut':
+      return '提交超时，已终止';
+    case 'cancelled':
+      return '提交已取消';
+    case 'git':
+      return error.message || '提交失败';
+    default:
+      return String(e) || '提交失败';
+  }
+}
+
+// 本地暂存：大文件先询问是否改用 LFS 跟踪
+async function stageWithLfsCheck(paths: string[]): Promise<void> {
+  const result = await api.gitStage(paths);
+  const large = result.largeFiles;
+  if (!large?.length) return;
+
+  const list = large.map((f) => `${f.path} (${(f.size / 1024 / 1024).toFixed(1)} MB)`).join('\n');
+  const header = `以下文件超过 ${result.thresholdMb} MB：\n${list}\n\n`;
+  if (result.lfsInstalled && confirm(`${header}是否改用 Git LFS 跟踪这些文件？`)) {
+    await api.gitLfsTrack(large.map((f) => f.path));
+    await api.gitStage([...paths, '.gitattributes'], true);
+  } else if (confirm(`${header}仍然直接暂存？`)) {
+    await api.gitStage(paths, true);
+  }
+}
+
 interface GitState {
   files: GitFileStatus[];
   branch: GitBranchInfo | null;
@@ -90,6 +128,8 @@ interface GitState {
   behind: number;
   hasRemote: boolean;
   syncing: boolean;
+  /** 后端写操作队列，由 `git-queue-changed` 事件更新 */
+  queue: api.GitQueueState | null;
   setCommitMessage: (msg: string) => void;
   clearError: () => void;
   fetchStatus: () => Promise<void>;
@@ -101,7 +141,7 @@ interface GitState {
   unstageFiles: (paths: string[]) => Promise<void>;
   discardFiles: (paths: string[]) => Promise<boolean>;
   discardAll: () => Promise<boolean>;
-  commit: () => Promise<boolean>;
+  commit: (options?: api.GitCommitOptions) => Promise<boolean>;
   push: () => Promise<boolean>;
   pull: () => Promise<boolean>;
   sync: () => Promise<boolean>;
@@ -119,6 +159,7 @@ export const useGitStore = create<GitState>((set, get) => ({
   behind: 0,
   hasRemote: false,
   syncing: false,
+  queue: null,
 
   setCommitMessage: (commitMessage) => set({ commitMessage }),
   clearError: () => set({ error: null }),
@@ -222,7 +263,7 @@ export const useGitStore = create<GitState>((set, get) => ({
     if (isRemote) {
       await api.sshGitStage(paths);
     } else {
-      await api.gitStage(paths);
+      await stageWithLfsCheck(paths);
     }
     await get().fetchStatus();
   },
@@ -237,7 +278,7 @@ export const useGitStore = create<GitState>((set, get) => ({
     if (isRemote) {
       await api.sshGitStage(unstaged);
     } else {
-      await api.gitStage(unstaged);
+      await stageWithLfsCheck(unstaged);
     }
     await get().fetchStatus();
   },
@@ -305,9 +346,10 @@ export const useGitStore = create<GitState>((set, get) => ({
     }
   },
 
-  commit: async () => {
+  commit: async (options) => {
     const msg = get().commitMessage.trim();
-    if (!msg) return false;
+    // amend 可以不填信息，沿用原提交信息
+    if (!msg && !options?.amend) return false;
     set({ error: null });
 
     const sshSession = useSSHStore.getState().session;
@@ -321,7 +363,7 @@ export const useGitStore = create<GitState>((set, get) => ({
         await get().fetchLog();
         return true;
       } else {
-        const data = await api.gitCommit(msg) as any;
+        const data = await api.gitCommit(msg, options);
         if (data.ok) {
           set({ commitMessage: '' });
           await get().fetchStatus();
@@ -332,9 +374,10 @@ export const useGitStore = create<GitState>((set, get) => ({
         set({ error: '提交失败' });
         return false;
       }
-    } catch (e: any) {
-      set({ error: e.toString() || '提交失败' });
-      showError('Git 提交失败', e, '提交失败');
+    } catch (e: unknown) {
+      const message = commitErrorMessage(e);
+      set({ error: message });
+      showError('Git 提交失败', message, '提交失败');
       return false;
     }
   },
@@ -353,8 +396,11 @@ export const useGitStore = create<GitState>((set, get) => ({
     }
 
     try {
-      const data = await api.gitPush() as any;
-      return !!data.ok;
+      const data = await api.gitPush();
+      if (!data.ok) {
+        set({ error: data.message || 'Push 失败' });
+      }
+      return data.ok;
     } catch (e: any) {
       set({ error: e.toString() || 'Push 失败' });
       showError('Git Push 失败', e, 'Push 失败');
@@ -382,7 +428,7 @@ export const useGitStore = create<GitState>((set, get) => ({
         await get().fetchSyncStatus();
         return true;
       }
-      set({ error: 'Pull 失败' });
+      set({ error: data.message || 'Pull 失败' });
       return false;
     } catch (e: any) {
       set({ error: e.toString() || 'Pull 失败' });
@@ -417,7 +463,7 @@ export const useGitStore = create<GitState>((set, get) => ({
         await Promise.all([get().fetchStatus(), get().fetchLog(), get().fetchSyncStatus()]);
         return true;
       }
-      set({ error: '同步失败' });
+      set({ error: data.message || '同步失败' });
       return false;
     } catch (e: any) {
       set({ error: e.toString() || '同步失败' });
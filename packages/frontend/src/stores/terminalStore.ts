@@ -17,6 +17,7 @@ interface TerminalState {
   defaultShellType: ShellType;
   setDefaultShellType: (shellType: ShellType) => void;
   createTerminal: (shellType?: ShellType) => Promise<number>;
+  attachTerminal: (id: number, shellType?: ShellType) => void;
   closeTerminal: (id: number) => Promise<void>;
   setActiveTab: (id: number) => void;
   setConnected: (id: number, v: boolean) => void;
@@ -46,6 +47,18 @@ export const useTerminalStore = create<TerminalState>((set, get) => ({
     return id;
   },
 
+  /** 为后端已经启动的 pty 添加标签页 */
+  attachTerminal: (id: number, shellType?: ShellType) => {
+    const index = get().nextIndex;
+    const type = shellType ?? get().defaultShellType;
+    const tab: TerminalTab = { id, name: `终端 ${index} (${type === 'powershell' ? 'PS' : 'CMD'})`, connected: true, shellType: type };
+    set((s) => ({
+      tabs: [...s.tabs, tab],
+      activeTabId: id,
+      nextIndex: s.nextIndex + 1,
+    }));
+  },
+
   closeTerminal: async (id: number) => {
     await api.ptyKill(id).catch(() => {});
     const { tabs, activeTabId } = get();
@@ -9,7 +9,8 @@ interface WorkspaceState {
   browserOpen: boolean;
   error: string | null;
   fetchWorkspace: () => Promise<void>;
-  openFolder: (path: string) => Promise<boolean>;
+  openFolder: (path: string, open?: (path: string) => Promise<api.OpenWorkspaceResult>) => Promise<boolean>;
+  openWorktree: (path: string) => Promise<boolean>;
   fetchRecent: () => Promise<void>;
   setBrowserOpen: (open: boolean) => void;
   clearError: () => void;
@@ -32,7 +33,7 @@ export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
     }
   },
 
-  openFolder: async (path: string) => {
+  openFolder: async (path: string, open = api.openWorkspace) => {
     try {
       set({ error: null });
 
@@ -68,7 +69,7 @@ export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
       });
 
       // 打开新工作区
-      const data = await api.openWorkspace(path) as { ok: boolean; path: string; projectName: string };
+      const data = await open(path);
       if (data.ok) {
         set({
           currentPath: data.path,
@@ -98,6 +99,22 @@ export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
     }
   },
 
+  /** 切换到某个 git 工作树，并在其中打开一个新终端 */
+  openWorktree: async (path: string) => {
+    const { useTerminalStore } = await import('./terminalStore');
+    const shellType = useTerminalStore.getState().defaultShellType;
+    let ptyId: number | null = null;
+    const ok = await get().openFolder(path, async (p) => {
+      const data = await api.gitWorktreeOpen(p, shellType);
+      ptyId = data.ptyId;
+      return data;
+    });
+    if (ok && ptyId !== null) {
+      useTerminalStore.getState().attachTerminal(ptyId, shellType);
+    }
+    return ok;
+  },
+
   fetchRecent: async () => {
     try {
       const list = await api.getRecent() as string[];
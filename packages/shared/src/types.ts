@@ -23,8 +23,10 @@ export interface DiffReviewItem {
 // Git 相关类型
 export interface GitFileStatus {
   path: string;
-  status: 'modified' | 'added' | 'deleted' | 'untracked' | 'renamed';
+  status: 'modified' | 'added' | 'deleted' | 'untracked' | 'renamed' | 'copied' | 'conflicted';
   staged: boolean;
+  origPath?: string | null;
+  conflict?: string | null;
 }
 
 export interface GitLogEntry {
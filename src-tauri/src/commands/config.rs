@@ -75,6 +75,41 @@ impl Default for AiConfig {
     }
 }
 
+/// status、log、show、分支和文件内容等读操作使用的 git 实现，写操作始终走 git 命令行
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum GitBackendKind {
+    #[default]
+    Cli,
+    Libgit2,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GitConfig {
+    /// 后台自动 fetch 的间隔（秒），0 表示关闭
+    #[serde(rename = "autoFetchInterval", default)]
+    pub auto_fetch_interval: u64,
+    /// 暂存超过该大小（MB）且未走 LFS 的文件时提示，0 表示不检查
+    #[serde(rename = "lfsThresholdMb", default = "default_lfs_threshold_mb")]
+    pub lfs_threshold_mb: u64,
+    #[serde(default)]
+    pub backend: GitBackendKind,
+}
+
+fn default_lfs_threshold_mb() -> u64 {
+    50
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            auto_fetch_interval: 0,
+            lfs_threshold_mb: default_lfs_threshold_mb(),
+            backend: GitBackendKind::default(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct AppConfig {
     #[serde(rename = "terminalFontSize", default = "default_font_size")]
@@ -85,6 +120,8 @@ pub struct AppConfig {
     pub shortcuts: Shortcuts,
     #[serde(default)]
     pub ai: AiConfig,
+    #[serde(default)]
+    pub git: GitConfig,
 }
 
 fn default_font_size() -> u16 {
@@ -101,6 +138,7 @@ impl Default for AppConfig {
             editor_font_size: default_editor_font_size(),
             shortcuts: Shortcuts::default(),
             ai: AiConfig::default(),
+            git: GitConfig::default(),
         }
     }
 }
@@ -156,6 +194,11 @@ pub async fn get_config(state: State<'_, AppState>) -> Result<serde_json::Value,
             "apiKey": mask_api_key(&config.ai.api_key),
             "model": config.ai.model,
             "customPrompt": config.ai.custom_prompt,
+        },
+        "git": {
+            "autoFetchInterval": config.git.auto_fetch_interval,
+            "lfsThresholdMb": config.git.lfs_threshold_mb,
+            "backend": config.git.backend,
         }
     }))
 }
@@ -211,6 +254,7 @@ mod tests {
             editor_font_size: 19,
             shortcuts: Shortcuts::default(),
             ai: AiConfig::default(),
+            git: GitConfig::default(),
         };
 
         write_config(dir.path(), &config).unwrap();
@@ -0,0 +1,425 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use super::{git, git_cmd};
+
+/// 传给 askpass 子进程的环境变量："<地址> <令牌>"
+const ENDPOINT_ENV: &str = "OPENLOOM_ASKPASS";
+/// 用户迟迟不回应时放弃，git 会按认证失败处理
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PromptKind {
+    Username,
+    Password,
+    Passphrase,
+    HostKey,
+    Other,
+}
+
+/// 从 git / ssh 的提示文本中识别出的信息
+#[derive(Debug, Clone, PartialEq)]
+struct PromptInfo {
+    kind: PromptKind,
+    /// HTTPS 的远程地址，或 ssh 私钥路径
+    target: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialPrompt {
+    pub id: u64,
+    pub prompt: String,
+    pub kind: PromptKind,
+    pub target: Option<String>,
+    /// 输入框是否应隐藏内容
+    pub secret: bool,
+    /// 是否可以记住到系统凭据管理器
+    pub can_remember: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HelperRequest {
+    token: String,
+    prompt: String,
+    cwd: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HelperResponse {
+    answer: Option<String>,
+}
+
+struct Answer {
+    value: Option<String>,
+    remember: bool,
+}
+
+type Notify = Arc<dyn Fn(CredentialPrompt) + Send + Sync>;
+
+struct Inner {
+    token: String,
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, mpsc::Sender<Answer>>>,
+    /// 同一远程先问用户名再问密码，记住用户名以便保存凭据
+    usernames: Mutex<HashMap<String, String>>,
+}
+
+/// 在 git 的 askpass 子进程和界面之间转发凭据提示
+pub struct AskpassBridge {
+    inner: Arc<Inner>,
+    endpoint: OnceLock<Result<String, String>>,
+}
+
+impl AskpassBridge {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                token: random_token(),
+                next_id: AtomicU64::new(1),
+                pending: Mutex::new(HashMap::new()),
+                usernames: Mutex::new(HashMap::new()),
+            }),
+            endpoint: OnceLock::new(),
+        }
+    }
+
+    /// 首次使用时在本机回环地址上监听，返回给子进程的连接信息
+    fn endpoint(&self, notify: Notify) -> Result<String, String> {
+        self.endpoint
+            .get_or_init(|| {
+                let listener = TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+                let addr = listener.local_addr().map_err(|e| e.to_string())?;
+                let inner = self.inner.clone();
+                std::thread::spawn(move || {
+                    for stream in listener.incoming().flatten() {
+                        let inner = inner.clone();
+                        let notify = notify.clone();
+                        std::thread::spawn(move || handle_connection(&inner, stream, &notify));
+                    }
+                });
+                Ok(format!("{} {}", addr, self.inner.token))
+            })
+            .clone()
+    }
+
+    /// 让命令在需要凭据时通过本应用询问用户
+    pub(super) fn configure(&self, app: &AppHandle, cwd: &str, cmd: &mut Command) -> Result<(), String> {
+        let app = app.clone();
+        let endpoint = self.endpoint(Arc::new(move |prompt| {
+            let _ = app.emit("git-credential-prompt", prompt);
+        }))?;
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        cmd.env(ENDPOINT_ENV, endpoint)
+            .env("GIT_ASKPASS", &exe)
+            .env("SSH_ASKPASS", &exe)
+            .env("SSH_ASKPASS_REQUIRE", "force")
+            .env("GIT_TERMINAL_PROMPT", "0");
+        // 旧版 ssh 只有在设置了 DISPLAY 时才会使用 SSH_ASKPASS
+        if std::env::var_os("DISPLAY").is_none() {
+            cmd.env("DISPLAY", ":0");
+        }
+        if let Some(helper) = helper_override(cwd) {
+            with_credential_helper(cmd, helper);
+        }
+        Ok(())
+    }
+
+    fn respond(&self, id: u64, value: Option<String>, remember: bool) -> bool {
+        match self.inner.pending.lock().unwrap().remove(&id) {
+            Some(tx) => tx.send(Answer { value, remember }).is_ok(),
+            None => false,
+        }
+    }
+}
+
+impl Default for AskpassBridge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn random_token() -> String {
+    let part = || {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos());
+        hasher.write_u32(std::process::id());
+        hasher.finish()
+    };
+    format!("{:016x}{:016x}", part(), part())
+}
+
+/// 取 marker 之后单引号内的内容，如 "Password for 'https://host': " → https://host
+fn quoted_after(prompt: &str, marker: &str) -> Option<String> {
+    let start = prompt.to_lowercase().find(marker)? + marker.len();
+    let rest = prompt.get(start..)?.strip_prefix('\'')?;
+    let end = rest.rfind('\'')?;
+    Some(rest[..end].to_string())
+}
+
+fn parse_prompt(prompt: &str) -> PromptInfo {
+    let lower = prompt.to_lowercase();
+    let (kind, target) = if lower.starts_with("username for ") {
+        (PromptKind::Username, quoted_after(prompt, "username for "))
+    } else if lower.starts_with("password for ") {
+        (PromptKind::Password, quoted_after(prompt, "password for "))
+    } else if lower.contains("passphrase") {
+        (PromptKind::Passphrase, quoted_after(prompt, "passphrase for key "))
+    } else if lower.contains("continue connecting") {
+        (PromptKind::HostKey, None)
+    } else if lower.contains("password") {
+        (PromptKind::Password, None)
+    } else {
+        (PromptKind::Other, None)
+    };
+    PromptInfo { kind, target }
+}
+
+/// 拆出 URL 中的用户名："https://user@host" → ("https://host", Some("user"))
+fn split_userinfo(url: &str) -> (String, Option<String>) {
+    if let Some((scheme, rest)) = url.split_once("://") {
+        if let Some((user, host)) = rest.split_once('@') {
+            return (format!("{}://{}", scheme, host), Some(user.to_string()));
+        }
+    }
+    (url.to_string(), None)
+}
+
+fn is_http_url(target: Option<&str>) -> bool {
+    target.is_some_and(|t| t.starts_with("https://") || t.starts_with("http://"))
+}
+
+fn os_credential_helper() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "manager"
+    } else if cfg!(target_os = "macos") {
+        "osxkeychain"
+    } else {
+        "libsecret"
+    }
+}
+
+fn helper_installed(name: &str) -> bool {
+    let exe = if cfg!(target_os = "windows") {
+        format!("git-credential-{}.exe", name)
+    } else {
+        format!("git-credential-{}", name)
+    };
+    git(&["--exec-path"], ".").is_ok_and(|dir| std::path::Path::new(&dir).join(&exe).exists())
+}
+
+fn has_configured_helper(cwd: &str) -> bool {
+    git(&["config", "--get-all", "credential.helper"], cwd).is_ok_and(|s| !s.trim().is_empty())
+}
+
+/// 需要额外启用的系统凭据管理器；用户已配置 credential.helper 时沿用用户的配置
+fn helper_override(cwd: &str) -> Option<&'static str> {
+    if has_configured_helper(cwd) {
+        return None;
+    }
+    Some(os_credential_helper()).filter(|h| helper_installed(h))
+}
+
+fn can_store_credentials(cwd: &str) -> bool {
+    has_configured_helper(cwd) || helper_override(cwd).is_some()
+}
+
+/// 通过环境变量追加 credential.helper，不改动用户的 git 配置
+fn with_credential_helper(cmd: &mut Command, helper: &str) {
+    cmd.env("GIT_CONFIG_COUNT", "1")
+        .env("GIT_CONFIG_KEY_0", "credential.helper")
+        .env("GIT_CONFIG_VALUE_0", helper);
+}
+
+/// 用 `git credential approve` 把凭据交给凭据管理器保存
+fn store_credential(cwd: &str, url: &str, username: &str, password: &str) -> Result<(), String> {
+    let mut cmd = git_cmd(&["credential", "approve"], cwd);
+    if let Some(helper) = helper_override(cwd) {
+        with_credential_helper(&mut cmd, helper);
+    }
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        write!(stdin, "url={}\nusername={}\npassword={}\n\n", url, username, password).map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).to_string())
+    }
+}
+
+fn handle_connection(inner: &Inner, stream: TcpStream, notify: &Notify) {
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    if reader.read_line(&mut line).is_err() {
+        return;
+    }
+    let request: HelperRequest = match serde_json::from_str(&line) {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+    if request.token != inner.token {
+        return;
+    }
+
+    let info = parse_prompt(&request.prompt);
+    let id = inner.next_id.fetch_add(1, Ordering::SeqCst);
+    let (tx, rx) = mpsc::channel();
+    inner.pending.lock().unwrap().insert(id, tx);
+
+    let can_remember = info.kind == PromptKind::Password
+        && is_http_url(info.target.as_deref())
+        && can_store_credentials(&request.cwd);
+    notify(CredentialPrompt {
+        id,
+        prompt: request.prompt.trim().to_string(),
+        kind: info.kind,
+        target: info.target.clone(),
+        secret: !matches!(info.kind, PromptKind::Username | PromptKind::HostKey),
+        can_remember,
+    });
+
+    let answer = rx.recv_timeout(PROMPT_TIMEOUT).unwrap_or(Answer { value: None, remember: false });
+    inner.pending.lock().unwrap().remove(&id);
+
+    if let (Some(target), Some(value)) = (info.target.as_deref(), answer.value.as_deref()) {
+        let (url, user) = split_userinfo(target);
+        match info.kind {
+            PromptKind::Username => {
+                inner.usernames.lock().unwrap().insert(url, value.to_string());
+            }
+            PromptKind::Password if answer.remember && can_remember => {
+                let username = user.or_else(|| inner.usernames.lock().unwrap().get(&url).cloned());
+                if let Some(username) = username {
+                    if let Err(e) = store_credential(&request.cwd, &url, &username, value) {
+                        eprintln!("[askpass] failed to store credential: {}", e.trim());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let response = serde_json::to_string(&HelperResponse { answer: answer.value }).unwrap_or_default();
+    let mut stream = &stream;
+    let _ = writeln!(stream, "{}", response);
+}
+
+/// askpass 子进程：把提示发给应用并等待用户回答
+fn request_answer(endpoint: &str, prompt: &str, cwd: &str) -> Result<Option<String>, String> {
+    let (addr, token) = endpoint.split_once(' ').ok_or("invalid askpass endpoint")?;
+    let stream = TcpStream::connect(addr).map_err(|e| e.to_string())?;
+    let request = HelperRequest { token: token.to_string(), prompt: prompt.to_string(), cwd: cwd.to_string() };
+    let mut writer = &stream;
+    writeln!(writer, "{}", serde_json::to_string(&request).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line).map_err(|e| e.to_string())?;
+    let response: HelperResponse = serde_json::from_str(&line).map_err(|e| e.to_string())?;
+    Ok(response.answer)
+}
+
+/// 程序被 git / ssh 作为 askpass 调用时的入口；不是 askpass 调用时返回 None
+pub fn run_helper() -> Option<i32> {
+    let endpoint = std::env::var(ENDPOINT_ENV).ok()?;
+    let prompt = std::env::args().nth(1).unwrap_or_default();
+    let cwd = std::env::current_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+    Some(match request_answer(&endpoint, &prompt, &cwd) {
+        Ok(Some(answer)) => {
+            println!("{}", answer);
+            0
+        }
+        Ok(None) => 1,
+        Err(e) => {
+            eprintln!("openloom askpass: {}", e);
+            1
+        }
+    })
+}
+
+#[tauri::command]
+pub async fn git_credential_respond(
+    askpass: State<'_, AskpassBridge>,
+    id: u64,
+    answer: Option<String>,
+    remember: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({ "ok": askpass.respond(id, answer, remember.unwrap_or(false)) }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_git_and_ssh_prompts() {
+        assert_eq!(
+            parse_prompt("Username for 'https://github.com': "),
+            PromptInfo { kind: PromptKind::Username, target: Some("https://github.com".into()) }
+        );
+        assert_eq!(
+            parse_prompt("Password for 'https://alice@github.com': "),
+            PromptInfo { kind: PromptKind::Password, target: Some("https://alice@github.com".into()) }
+        );
+        assert_eq!(
+            parse_prompt("Enter passphrase for key '/home/alice/.ssh/id_ed25519': "),
+            PromptInfo { kind: PromptKind::Passphrase, target: Some("/home/alice/.ssh/id_ed25519".into()) }
+        );
+        assert_eq!(parse_prompt("alice@example.com's password: ").kind, PromptKind::Password);
+        assert_eq!(
+            parse_prompt("Are you sure you want to continue connecting (yes/no/[fingerprint])? ").kind,
+            PromptKind::HostKey
+        );
+    }
+
+    #[test]
+    fn splits_username_out_of_url() {
+        assert_eq!(
+            split_userinfo("https://alice@github.com"),
+            ("https://github.com".to_string(), Some("alice".to_string()))
+        );
+        assert_eq!(split_userinfo("https://github.com"), ("https://github.com".to_string(), None));
+    }
+
+    #[test]
+    fn helper_round_trip_waits_for_ui_answer() {
+        let bridge = Arc::new(AskpassBridge::new());
+        let responder = bridge.clone();
+        let endpoint = bridge
+            .endpoint(Arc::new(move |prompt: CredentialPrompt| {
+                assert_eq!(prompt.kind, PromptKind::Passphrase);
+                assert!(prompt.secret);
+                let responder = responder.clone();
+                std::thread::spawn(move || responder.respond(prompt.id, Some("s3cret".into()), false));
+            }))
+            .unwrap();
+
+        let answer = request_answer(&endpoint, "Enter passphrase for key '/tmp/id': ", ".").unwrap();
+
+        assert_eq!(answer, Some("s3cret".into()));
+    }
+
+    #[test]
+    fn wrong_token_is_ignored() {
+        let bridge = AskpassBridge::new();
+        let endpoint = bridge.endpoint(Arc::new(|_| panic!("prompt must not be shown"))).unwrap();
+        let addr = endpoint.split_once(' ').unwrap().0;
+
+        assert!(request_answer(&format!("{} forged", addr), "Password: ", ".").is_err());
+    }
+}
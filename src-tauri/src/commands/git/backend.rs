@@ -0,0 +1,64 @@
+use std::path::Path;
+use crate::commands::config::{read_config, GitBackendKind};
+use super::libgit2::Libgit2Backend;
+use super::log::{log_page, LogQuery};
+use super::{branch, git_raw, show, status};
+
+/// 读操作的 git 实现。写操作（暂存、提交、推拉等）始终通过 git 命令行完成，
+/// 这里只收拢刷新时最频繁的几类读取，让它们可以换成进程内实现
+pub(crate) trait GitBackend: Send + Sync {
+    /// 与 `git status --porcelain=v2` 解析结果相同的结构
+    fn status(&self, cwd: &str) -> Result<serde_json::Value, String>;
+    /// 一页提交记录和下一页游标
+    fn log(&self, cwd: &str, query: &LogQuery) -> Result<(Vec<serde_json::Value>, Option<String>), String>;
+    fn show(&self, cwd: &str, hash: &str, parent: Option<usize>) -> Result<serde_json::Value, String>;
+    /// 读取 `rev:path` 的内容，rev 为 None 时读取暂存区
+    fn blob(&self, cwd: &str, rev: Option<&str>, path: &str) -> Result<Vec<u8>, String>;
+    fn branches(&self, cwd: &str) -> Result<serde_json::Value, String>;
+}
+
+/// 现有的命令行实现
+pub(crate) struct CliBackend;
+
+impl GitBackend for CliBackend {
+    fn status(&self, cwd: &str) -> Result<serde_json::Value, String> {
+        status::read_status(cwd)
+    }
+
+    fn log(&self, cwd: &str, query: &LogQuery) -> Result<(Vec<serde_json::Value>, Option<String>), String> {
+        log_page(cwd, query)
+    }
+
+    fn show(&self, cwd: &str, hash: &str, parent: Option<usize>) -> Result<serde_json::Value, String> {
+        show::read_show(cwd, hash, parent)
+    }
+
+    fn blob(&self, cwd: &str, rev: Option<&str>, path: &str) -> Result<Vec<u8>, String> {
+        let spec = format!("{}:{}", rev.unwrap_or(""), path);
+        git_raw(&["show", &spec], cwd).map(String::into_bytes)
+    }
+
+    fn branches(&self, cwd: &str) -> Result<serde_json::Value, String> {
+        branch::list_branches(cwd)
+    }
+}
+
+pub(crate) fn backend(kind: GitBackendKind) -> &'static dyn GitBackend {
+    match kind {
+        GitBackendKind::Cli => &CliBackend,
+        GitBackendKind::Libgit2 => &Libgit2Backend,
+    }
+}
+
+/// 按工作区配置选择实现
+pub(crate) fn for_root(root: &Path) -> &'static dyn GitBackend {
+    backend(read_config(root).git.backend)
+}
+
+/// 以文本形式读取文件的原始内容，不裁剪首尾空白；读取失败（例如该版本中不存在）时为空
+pub(super) fn blob_text(backend: &dyn GitBackend, cwd: &str, rev: Option<&str>, path: &str) -> String {
+    backend
+        .blob(cwd, rev, path)
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_default()
+}
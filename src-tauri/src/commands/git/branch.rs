@@ -0,0 +1,413 @@
+use tauri::State;
+use crate::state::AppState;
+use super::queue::GitQueue;
+use super::{backend, check_rev, git, git_raw};
+
+/// 切换分支时工作区有未提交改动的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DirtyStrategy {
+    /// 有改动时拒绝切换
+    #[default]
+    Refuse,
+    /// 交给 git 把改动带到目标分支（冲突时 git 会拒绝）
+    Carry,
+    /// 先 stash，切换后由用户决定是否恢复
+    Stash,
+}
+
+const BRANCH_FORMAT: &str = "%(refname)%00%(refname:short)%00%(objectname)%00%(upstream:short)%00%(upstream:track,nobracket)%00%(committerdate:iso-strict)%00%(contents:subject)%00%(HEAD)";
+
+/// 解析 `%(upstream:track,nobracket)`，如 "ahead 2, behind 1" / "gone"
+fn parse_track(track: &str) -> (i64, i64, bool) {
+    if track == "gone" {
+        return (0, 0, true);
+    }
+    let mut ahead = 0;
+    let mut behind = 0;
+    for part in track.split(", ") {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            ahead = n.trim().parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            behind = n.trim().parse().unwrap_or(0);
+        }
+    }
+    (ahead, behind, false)
+}
+
+pub(super) fn list_branches(cwd: &str) -> Result<serde_json::Value, String> {
+    let format = format!("--format={}", BRANCH_FORMAT);
+    let raw = git_raw(&["for-each-ref", &format, "refs/heads", "refs/remotes"], cwd)?;
+    // 空仓库（HEAD 尚无提交）时 --merged 会失败，视为全部未合并
+    let merged_raw = git_raw(
+        &["for-each-ref", "--merged", "HEAD", "--format=%(refname)", "refs/heads", "refs/remotes"],
+        cwd,
+    )
+    .unwrap_or_default();
+    let merged: std::collections::HashSet<&str> = merged_raw.lines().collect();
+
+    let mut current = git(&["branch", "--show-current"], cwd).unwrap_or_default();
+    let mut names: Vec<String> = Vec::new();
+    let mut local: Vec<serde_json::Value> = Vec::new();
+    let mut remote: Vec<serde_json::Value> = Vec::new();
+
+    for line in raw.lines().filter(|l| !l.is_empty()) {
+        let parts: Vec<&str> = line.split('\0').collect();
+        if parts.len() < 8 {
+            continue;
+        }
+        let ref_name = parts[0];
+        let name = parts[1];
+        let (ahead, behind, gone) = parse_track(parts[4]);
+        let is_head = parts[7] == "*";
+
+        if let Some(rest) = ref_name.strip_prefix("refs/remotes/") {
+            // origin/HEAD 只是指向默认分支的符号引用
+            if rest.ends_with("/HEAD") {
+                continue;
+            }
+            let remote_name = rest.split('/').next().unwrap_or("");
+            remote.push(serde_json::json!({
+                "name": name,
+                "refName": ref_name,
+                "remote": remote_name,
+                "hash": parts[2],
+                "lastCommitDate": parts[5],
+                "subject": parts[6],
+                "merged": merged.contains(ref_name),
+            }));
+        } else {
+            if is_head {
+                current = name.to_string();
+            }
+            names.push(name.to_string());
+            local.push(serde_json::json!({
+                "name": name,
+                "refName": ref_name,
+                "hash": parts[2],
+                "upstream": if parts[3].is_empty() { None } else { Some(parts[3]) },
+                "ahead": ahead,
+                "behind": behind,
+                "upstreamGone": gone,
+                "lastCommitDate": parts[5],
+                "subject": parts[6],
+                "merged": merged.contains(ref_name),
+                "current": is_head,
+            }));
+        }
+    }
+
+    Ok(serde_json::json!({
+        "current": current,
+        "branches": names,
+        "local": local,
+        "remote": remote,
+    }))
+}
+
+/// 工作区或暂存区是否有已跟踪文件的改动（未跟踪文件不影响切换）
+fn has_tracked_changes(cwd: &str) -> Result<bool, String> {
+    let raw = git_raw(&["status", "--porcelain", "--untracked-files=no"], cwd)?;
+    Ok(!raw.trim().is_empty())
+}
+
+fn create_branch(cwd: &str, name: &str, start_point: Option<&str>, checkout: bool) -> Result<(), String> {
+    check_rev(name)?;
+    let mut args = if checkout {
+        vec!["switch", "-c", name]
+    } else {
+        vec!["branch", name]
+    };
+    if let Some(start) = start_point.filter(|s| !s.is_empty()) {
+        args.push(check_rev(start)?);
+    }
+    git(&args, cwd)?;
+    Ok(())
+}
+
+fn checkout_branch(cwd: &str, name: &str, strategy: DirtyStrategy) -> Result<serde_json::Value, String> {
+    check_rev(name)?;
+    let dirty = has_tracked_changes(cwd)?;
+    let mut stash: Option<String> = None;
+
+    if dirty {
+        match strategy {
+            DirtyStrategy::Refuse => {
+                return Err("working tree has uncommitted changes".into());
+            }
+            DirtyStrategy::Carry => {}
+            DirtyStrategy::Stash => {
+                let from = git(&["branch", "--show-current"], cwd).unwrap_or_default();
+                let message = format!("openloom: auto-stash on {} before switching to {}", from, name);
+                git(&["stash", "push", "-m", &message], cwd)?;
+                stash = Some(git(&["rev-parse", "stash@{0}"], cwd)?);
+            }
+        }
+    }
+
+    if let Err(e) = git(&["switch", name], cwd) {
+        // 切换失败时把刚才 stash 的改动放回去，避免用户以为改动丢了
+        if stash.is_some() {
+            let _ = git(&["stash", "pop", "--index"], cwd);
+        }
+        return Err(e);
+    }
+
+    Ok(serde_json::json!({ "ok": true, "branch": name, "stash": stash }))
+}
+
+fn delete_branch(cwd: &str, name: &str, force: bool) -> Result<(), String> {
+    let flag = if force { "-D" } else { "-d" };
+    git(&["branch", flag, check_rev(name)?], cwd)?;
+    Ok(())
+}
+
+fn rename_branch(cwd: &str, old_name: &str, new_name: &str, force: bool) -> Result<(), String> {
+    let flag = if force { "-M" } else { "-m" };
+    git(&["branch", flag, check_rev(old_name)?, check_rev(new_name)?], cwd)?;
+    Ok(())
+}
+
+fn set_upstream(cwd: &str, name: &str, upstream: Option<&str>) -> Result<(), String> {
+    check_rev(name)?;
+    match upstream.filter(|u| !u.is_empty()) {
+        Some(up) => git(&["branch", &format!("--set-upstream-to={}", up), name], cwd)?,
+        None => git(&["branch", "--unset-upstream", name], cwd)?,
+    };
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn git_branches(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let root = state.get_root();
+    let cwd = root.to_string_lossy().to_string();
+    backend::for_root(&root).branches(&cwd)
+}
+
+#[tauri::command]
+pub async fn git_branch_create(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    name: String,
+    start_point: Option<String>,
+    checkout: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "branch-create").await;
+    create_branch(&cwd, &name, start_point.as_deref(), checkout.unwrap_or(false))?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+#[tauri::command]
+pub async fn git_checkout_branch(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    name: String,
+    dirty: Option<DirtyStrategy>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "checkout").await;
+    checkout_branch(&cwd, &name, dirty.unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn git_branch_rename(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    old_name: String,
+    new_name: String,
+    force: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "branch-rename").await;
+    rename_branch(&cwd, &old_name, &new_name, force.unwrap_or(false))?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+#[tauri::command]
+pub async fn git_branch_delete(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    name: String,
+    force: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "branch-delete").await;
+    delete_branch(&cwd, &name, force.unwrap_or(false))?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+#[tauri::command]
+pub async fn git_branch_set_upstream(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    name: String,
+    upstream: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "branch-set-upstream").await;
+    set_upstream(&cwd, &name, upstream.as_deref())?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{backends, commit_file, init_repo, run_git};
+    use std::fs;
+    use std::path::Path;
+
+    fn current_branch(cwd: &str) -> String {
+        git(&["branch", "--show-current"], cwd).unwrap()
+    }
+
+    fn find<'a>(list: &'a serde_json::Value, key: &str, name: &str) -> &'a serde_json::Value {
+        list[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|b| b["name"] == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn list_reports_upstream_tracking_and_merged_state() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        let main = current_branch(&cwd);
+
+        create_branch(&cwd, "feature", None, true).unwrap();
+        commit_file(&cwd, "b.txt", "b", "feature work");
+        set_upstream(&cwd, "feature", Some(&main)).unwrap();
+        run_git(&["switch", &main], &cwd);
+
+        for backend in backends() {
+            let list = backend.branches(&cwd).unwrap();
+            assert_eq!(list["current"], main);
+
+            let feature = find(&list, "local", "feature");
+            assert_eq!(feature["upstream"], main);
+            assert_eq!(feature["ahead"], 1);
+            assert_eq!(feature["behind"], 0);
+            assert_eq!(feature["subject"], "feature work");
+            assert_eq!(feature["merged"], false);
+            assert_eq!(find(&list, "local", &main)["merged"], true);
+        }
+    }
+
+    #[test]
+    fn backends_list_the_same_local_and_remote_branches() {
+        let (_dir, origin) = init_repo();
+        commit_file(&origin, "a.txt", "v1", "init");
+        create_branch(&origin, "feature", None, false).unwrap();
+        create_branch(&origin, "tmp", None, false).unwrap();
+        let clone_dir = tempfile::tempdir().unwrap();
+        let cwd = clone_dir.path().to_string_lossy().to_string();
+        run_git(&["clone", "-q", &origin, &cwd], &origin);
+        run_git(&["config", "user.email", "test@example.com"], &cwd);
+        run_git(&["config", "user.name", "Tester"], &cwd);
+
+        run_git(&["branch", "-q", "--track", "feature", "origin/feature"], &cwd);
+        run_git(&["branch", "-q", "--track", "old", "origin/tmp"], &cwd);
+        run_git(&["update-ref", "-d", "refs/remotes/origin/tmp"], &cwd);
+        commit_file(&cwd, "b.txt", "b", "local work");
+
+        let [cli, lib] = backends();
+        let expected = cli.branches(&cwd).unwrap();
+        assert_eq!(find(&expected, "local", "old")["upstreamGone"], true);
+        assert_eq!(find(&expected, "remote", "origin/feature")["merged"], true);
+        assert_eq!(lib.branches(&cwd).unwrap(), expected);
+    }
+
+    #[test]
+    fn create_from_commit_points_branch_at_that_commit() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "first");
+        let first = git(&["rev-parse", "HEAD"], &cwd).unwrap();
+        commit_file(&cwd, "a.txt", "v2", "second");
+
+        create_branch(&cwd, "old", Some(&first), false).unwrap();
+
+        assert_eq!(git(&["rev-parse", "old"], &cwd).unwrap(), first);
+        assert_ne!(current_branch(&cwd), "old");
+    }
+
+    #[test]
+    fn checkout_refuses_dirty_tree_by_default() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        create_branch(&cwd, "other", None, false).unwrap();
+        fs::write(Path::new(&cwd).join("a.txt"), "dirty").unwrap();
+
+        assert!(checkout_branch(&cwd, "other", DirtyStrategy::Refuse).is_err());
+        assert_ne!(current_branch(&cwd), "other");
+    }
+
+    #[test]
+    fn checkout_with_stash_strategy_stashes_changes() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        create_branch(&cwd, "other", None, false).unwrap();
+        fs::write(Path::new(&cwd).join("a.txt"), "dirty").unwrap();
+
+        let result = checkout_branch(&cwd, "other", DirtyStrategy::Stash).unwrap();
+
+        assert_eq!(current_branch(&cwd), "other");
+        assert!(result["stash"].is_string());
+        assert_eq!(fs::read_to_string(Path::new(&cwd).join("a.txt")).unwrap(), "v1");
+    }
+
+    #[test]
+    fn checkout_with_carry_strategy_keeps_changes() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        create_branch(&cwd, "other", None, false).unwrap();
+        fs::write(Path::new(&cwd).join("a.txt"), "dirty").unwrap();
+
+        checkout_branch(&cwd, "other", DirtyStrategy::Carry).unwrap();
+
+        assert_eq!(current_branch(&cwd), "other");
+        assert_eq!(fs::read_to_string(Path::new(&cwd).join("a.txt")).unwrap(), "dirty");
+    }
+
+    #[test]
+    fn safe_delete_refuses_unmerged_branch_but_force_deletes() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        let main = current_branch(&cwd);
+        create_branch(&cwd, "topic", None, true).unwrap();
+        commit_file(&cwd, "t.txt", "t", "topic");
+        run_git(&["switch", &main], &cwd);
+
+        assert!(delete_branch(&cwd, "topic", false).is_err());
+        delete_branch(&cwd, "topic", true).unwrap();
+        assert!(git(&["rev-parse", "--verify", "refs/heads/topic"], &cwd).is_err());
+    }
+
+    #[test]
+    fn option_like_names_are_rejected_instead_of_run() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        let main = current_branch(&cwd);
+        create_branch(&cwd, "topic", None, false).unwrap();
+        run_git(&["switch", "topic"], &cwd);
+
+        // 否则会变成 `git branch -D main`
+        assert!(create_branch(&cwd, "-D", Some(&main), false).is_err());
+        assert!(create_branch(&cwd, "ok", Some("--orphan"), false).is_err());
+        assert!(rename_branch(&cwd, "-D", &main, false).is_err());
+        assert!(git(&["rev-parse", "--verify", &format!("refs/heads/{}", main)], &cwd).is_ok());
+    }
+
+    #[test]
+    fn rename_moves_branch() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        create_branch(&cwd, "before", None, false).unwrap();
+
+        rename_branch(&cwd, "before", "after", false).unwrap();
+
+        assert!(git(&["rev-parse", "--verify", "refs/heads/after"], &cwd).is_ok());
+        assert!(git(&["rev-parse", "--verify", "refs/heads/before"], &cwd).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use std::io::{BufRead, BufReader};
+use std::process::Stdio;
+use serde::Deserialize;
+use tauri::State;
+use crate::state::AppState;
+use super::queue::GitQueue;
+use super::{check_rev, git, git_cmd, run_conflicting};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickOptions {
+    /// 在提交信息末尾追加 "(cherry picked from commit ...)"，仅 cherry-pick 支持
+    #[serde(default)]
+    pub record_origin: bool,
+    /// 处理合并提交时作为主线的父提交序号（从 1 开始）
+    pub mainline: Option<u32>,
+    /// 只把改动应用到工作区和暂存区，不创建提交
+    #[serde(default)]
+    pub no_commit: bool,
+}
+
+/// 按拓扑顺序排列选中的提交（同一秒内的提交按时间无法区分先后），newest_first 为 false 时从旧到新
+fn ordered_commits(cwd: &str, hashes: &[String], newest_first: bool) -> Result<Vec<String>, String> {
+    if hashes.is_empty() {
+        return Err("no commits selected".into());
+    }
+    let mut wanted = std::collections::HashSet::new();
+    for hash in hashes {
+        check_rev(hash)?;
+        wanted.insert(git(&["rev-parse", "--verify", &format!("{}^{{commit}}", hash)], cwd)?);
+    }
+
+    let mut args = vec!["rev-list", "--topo-order"];
+    args.extend(hashes.iter().map(|h| h.as_str()));
+    let mut child = git_cmd(&args, cwd)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    let mut commits: Vec<String> = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        // 找齐所有选中的提交后就不再继续遍历历史
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if wanted.contains(&line) {
+                commits.push(line);
+                if commits.len() == wanted.len() {
+                    break;
+                }
+            }
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+
+    if !newest_first {
+        commits.reverse();
+    }
+    Ok(commits)
+}
+
+/// cherry-pick 按从旧到新的顺序应用；revert 从新到旧撤销，避免后面的提交依赖前面的改动
+fn apply_commits(cwd: &str, operation: &str, hashes: &[String], options: &PickOptions) -> Result<serde_json::Value, String> {
+    if operation == "revert" && options.record_origin {
+        return Err("-x is only supported by cherry-pick".into());
+    }
+    let commits = ordered_commits(cwd, hashes, operation == "revert")?;
+
+    let mainline = options.mainline.map(|m| m.to_string());
+    let mut args: Vec<&str> = vec![operation];
+    if options.record_origin {
+        args.push("-x");
+    }
+    if let Some(mainline) = &mainline {
+        args.extend(["-m", mainline]);
+    }
+    if options.no_commit {
+        args.push("--no-commit");
+    }
+    args.extend(commits.iter().map(|c| c.as_str()));
+
+    // revert 默认会打开编辑器修改提交信息
+    let mut cmd = git_cmd(&args, cwd);
+    cmd.env("GIT_EDITOR", "true");
+    let mut result = run_conflicting(cmd, cwd)?;
+    result["commits"] = serde_json::json!(commits);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn git_cherry_pick(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    hashes: Vec<String>,
+    options: Option<PickOptions>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "cherry-pick").await;
+    apply_commits(&cwd, "cherry-pick", &hashes, &options.unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn git_revert(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    hashes: Vec<String>,
+    options: Option<PickOptions>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "revert").await;
+    apply_commits(&cwd, "revert", &hashes, &options.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::conflict::{abort_operation, continue_operation, in_progress_operation};
+    use super::super::tests::{commit_file, init_repo, run_git};
+
+    fn subjects(cwd: &str) -> Vec<String> {
+        git(&["log", "--format=%s"], cwd).unwrap().lines().map(String::from).collect()
+    }
+
+    fn head(cwd: &str) -> String {
+        git(&["rev-parse", "HEAD"], cwd).unwrap()
+    }
+
+    #[test]
+    fn cherry_picks_several_commits_oldest_first_with_origin() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "base");
+        let main = git(&["branch", "--show-current"], &cwd).unwrap();
+        run_git(&["switch", "-c", "feature"], &cwd);
+        commit_file(&cwd, "b.txt", "b\n", "add b");
+        let first = head(&cwd);
+        commit_file(&cwd, "c.txt", "c\n", "add c");
+        let second = head(&cwd);
+        run_git(&["switch", &main], &cwd);
+
+        let options = PickOptions { record_origin: true, ..Default::default() };
+        let result = apply_commits(&cwd, "cherry-pick", &[second, first.clone()], &options).unwrap();
+
+        assert_eq!(result["ok"], true);
+        assert_eq!(subjects(&cwd), vec!["add c", "add b", "base"]);
+        let body = git(&["log", "-1", "--skip=1", "--format=%b"], &cwd).unwrap();
+        assert!(body.contains(&format!("(cherry picked from commit {})", first)));
+    }
+
+    #[test]
+    fn reverts_newest_first_and_no_commit_only_stages() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "one\n", "base");
+        commit_file(&cwd, "a.txt", "two\n", "second");
+        let second = head(&cwd);
+        commit_file(&cwd, "a.txt", "three\n", "third");
+        let third = head(&cwd);
+
+        let staged = apply_commits(&cwd, "revert", std::slice::from_ref(&third), &PickOptions { no_commit: true, ..Default::default() }).unwrap();
+        assert_eq!(staged["ok"], true);
+        assert_eq!(head(&cwd), third);
+        assert_eq!(git(&["diff", "--cached", "--name-only"], &cwd).unwrap(), "a.txt");
+        run_git(&["reset", "--hard"], &cwd);
+
+        apply_commits(&cwd, "revert", &[second, third], &PickOptions::default()).unwrap();
+
+        assert_eq!(std::fs::read_to_string(std::path::Path::new(&cwd).join("a.txt")).unwrap(), "one\n");
+        assert_eq!(subjects(&cwd)[..2], ["Revert \"second\"", "Revert \"third\""]);
+    }
+
+    #[test]
+    fn reverts_merge_commit_with_mainline() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "base");
+        let main = git(&["branch", "--show-current"], &cwd).unwrap();
+        run_git(&["switch", "-c", "feature"], &cwd);
+        commit_file(&cwd, "b.txt", "b\n", "add b");
+        run_git(&["switch", &main], &cwd);
+        commit_file(&cwd, "c.txt", "c\n", "add c");
+        run_git(&["merge", "--no-ff", "-m", "merge feature", "feature"], &cwd);
+        let merge = head(&cwd);
+
+        assert!(apply_commits(&cwd, "revert", std::slice::from_ref(&merge), &PickOptions::default()).is_err());
+        let result = apply_commits(&cwd, "revert", &[merge], &PickOptions { mainline: Some(1), ..Default::default() }).unwrap();
+
+        assert_eq!(result["ok"], true);
+        assert!(!std::path::Path::new(&cwd).join("b.txt").exists());
+        assert!(std::path::Path::new(&cwd).join("c.txt").exists());
+    }
+
+    #[test]
+    fn conflicting_sequence_can_be_continued_or_aborted() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "base\n", "base");
+        let main = git(&["branch", "--show-current"], &cwd).unwrap();
+        run_git(&["switch", "-c", "feature"], &cwd);
+        commit_file(&cwd, "a.txt", "theirs\n", "change a");
+        let conflicting = head(&cwd);
+        commit_file(&cwd, "b.txt", "b\n", "add b");
+        let clean = head(&cwd);
+        run_git(&["switch", &main], &cwd);
+        commit_file(&cwd, "a.txt", "ours\n", "our change");
+        let before = head(&cwd);
+
+        let result = apply_commits(&cwd, "cherry-pick", &[conflicting.clone(), clean.clone()], &PickOptions::default()).unwrap();
+        assert_eq!(result["ok"], false);
+        assert_eq!(result["operation"], "cherry-pick");
+        assert_eq!(result["conflicts"][0]["path"], "a.txt");
+
+        abort_operation(&cwd, "cherry-pick").unwrap();
+        assert_eq!(head(&cwd), before);
+        assert_eq!(in_progress_operation(&cwd), None);
+
+        apply_commits(&cwd, "cherry-pick", &[conflicting, clean], &PickOptions::default()).unwrap();
+        std::fs::write(std::path::Path::new(&cwd).join("a.txt"), "resolved\n").unwrap();
+        run_git(&["add", "a.txt"], &cwd);
+        let continued = continue_operation(&cwd, "cherry-pick").unwrap();
+
+        assert_eq!(continued["ok"], true);
+        assert_eq!(subjects(&cwd)[..3], ["add b", "change a", "our change"]);
+        assert_eq!(in_progress_operation(&cwd), None);
+    }
+}
@@ -0,0 +1,501 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use crate::state::AppState;
+use super::queue::{self, GitQueue};
+use super::task::{emit_line, run_streamed, GitTasks, TaskError};
+use super::{git, git_cmd, git_path, git_raw, status};
+
+/// git_commit 的可选项，全部默认关闭
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitOptions {
+    #[serde(default)]
+    pub amend: bool,
+    /// 追加 Signed-off-by 尾注
+    #[serde(default)]
+    pub signoff: bool,
+    /// 覆盖作者，格式 "Name <email>"
+    pub author: Option<String>,
+    #[serde(default)]
+    pub allow_empty: bool,
+    /// 只提交这些路径的工作区内容，其余已暂存的改动保持不动（git commit --only）
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// 跳过 pre-commit / commit-msg 钩子
+    #[serde(default)]
+    pub no_verify: bool,
+    /// 整个提交（含钩子）的超时秒数，不填则不限时
+    pub timeout_secs: Option<u64>,
+    /// 用于取消和关联 git-task-output 事件，不填则自动生成
+    pub task_id: Option<String>,
+}
+
+/// 提交失败的类型，前端可按 kind 区分处理
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum CommitError {
+    /// 按仓库配置需要 GPG/SSH 签名，但签名失败
+    Signing { message: String },
+    /// 提交钩子返回非零，output 为钩子输出的全部行
+    Hook { message: String, output: Vec<String>, modified_files: Vec<String> },
+    TimedOut,
+    Cancelled,
+    Git { message: String },
+}
+
+impl From<String> for CommitError {
+    fn from(message: String) -> Self {
+        CommitError::Git { message }
+    }
+}
+
+fn is_signing_failure(stderr: &str) -> bool {
+    let lower = stderr.to_lowercase();
+    [
+        "gpg failed to sign",
+        "failed to sign the data",
+        "signing failed",
+        "ssh-keygen",
+        "couldn't load public key",
+        "no secret key",
+        "unsupported value for gpg.format",
+    ]
+    .iter()
+    .any(|needle| lower.contains(needle))
+}
+
+fn commit_args<'a>(message: Option<&'a str>, options: &'a CommitOptions) -> Result<Vec<&'a str>, CommitError> {
+    let mut args = vec!["commit"];
+    let message = message.filter(|m| !m.trim().is_empty());
+    if options.amend {
+        args.push("--amend");
+        if message.is_none() {
+            args.push("--no-edit");
+        }
+    } else if message.is_none() {
+        return Err(CommitError::Git { message: "commit message is required".into() });
+    }
+    if let Some(msg) = message {
+        args.push("-m");
+        args.push(msg);
+    }
+    if options.signoff {
+        args.push("--signoff");
+    }
+    if let Some(author) = options.author.as_deref().filter(|a| !a.is_empty()) {
+        args.push("--author");
+        args.push(author);
+    }
+    if options.allow_empty {
+        args.push("--allow-empty");
+    }
+    if options.no_verify {
+        args.push("--no-verify");
+    }
+    let paths: Vec<&str> = options.paths.iter().map(|p| p.as_str()).filter(|p| !p.is_empty()).collect();
+    if !paths.is_empty() {
+        args.push("--only");
+        args.push("--");
+        args.extend(paths);
+    }
+    Ok(args)
+}
+
+/// 会影响提交结果的钩子是否存在（兼容 core.hooksPath）
+fn has_commit_hooks(cwd: &str) -> bool {
+    ["pre-commit", "prepare-commit-msg", "commit-msg"]
+        .iter()
+        .any(|hook| git_path(cwd, &format!("hooks/{}", hook)).map(|p| p.is_file()).unwrap_or(false))
+}
+
+/// 有改动的文件在暂存区和工作区的 blob，用来比较钩子前后的变化
+fn snapshot_changes(cwd: &str) -> HashMap<String, (Option<String>, Option<String>)> {
+    let status = status::read_status(cwd).unwrap_or_default();
+    let paths: BTreeSet<String> = status["files"]
+        .as_array()
+        .map(|files| files.iter().filter_map(|f| f["path"].as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    blob_states(cwd, &paths)
+}
+
+fn blob_states(cwd: &str, paths: &BTreeSet<String>) -> HashMap<String, (Option<String>, Option<String>)> {
+    let mut states: HashMap<String, (Option<String>, Option<String>)> =
+        paths.iter().map(|p| (p.clone(), (None, None))).collect();
+    if paths.is_empty() {
+        return states;
+    }
+
+    let mut args = vec!["ls-files", "-s", "-z", "--"];
+    args.extend(paths.iter().map(|p| p.as_str()));
+    for record in git_raw(&args, cwd).unwrap_or_default().split('\0') {
+        // <mode> <blob> <stage>\t<path>
+        if let Some((meta, path)) = record.split_once('\t') {
+            if let (Some(blob), Some(state)) = (meta.split(' ').nth(1), states.get_mut(path)) {
+                state.0 = Some(blob.to_string());
+            }
+        }
+    }
+
+    let existing: Vec<&str> = paths
+        .iter()
+        .map(|p| p.as_str())
+        .filter(|p| std::path::Path::new(cwd).join(p).is_file())
+        .collect();
+    if !existing.is_empty() {
+        let mut args = vec!["hash-object", "--"];
+        args.extend(existing.iter().copied());
+        let hashes = git_raw(&args, cwd).unwrap_or_default();
+        for (path, hash) in existing.iter().zip(hashes.lines()) {
+            if let Some(state) = states.get_mut(*path) {
+                state.1 = Some(hash.to_string());
+            }
+        }
+    }
+    states
+}
+
+/// 对比钩子运行前后的快照，找出被钩子改动过的文件。
+/// 提交成功后已提交文件的暂存区 blob 就是提交内容，若与提交前暂存的不同说明钩子改写并重新暂存了它。
+fn files_modified_by_hooks(
+    cwd: &str,
+    before: &HashMap<String, (Option<String>, Option<String>)>,
+) -> Vec<String> {
+    let after_dirty = snapshot_changes(cwd);
+    let mut paths: BTreeSet<String> = before.keys().cloned().collect();
+    paths.extend(after_dirty.keys().cloned());
+    let after = blob_states(cwd, &paths);
+
+    paths
+        .into_iter()
+        .filter(|path| match before.get(path) {
+            Some(prev) => {
+                let now = &after[path];
+                // 提交后仍与 HEAD 一致的文件不会出现在 status 里，此时工作区 blob 等于暂存区 blob
+                let now_worktree = if after_dirty.contains_key(path) { now.1.clone() } else { now.0.clone() };
+                now.0 != prev.0 || now_worktree != prev.1
+            }
+            None => true,
+        })
+        .collect()
+}
+
+pub(super) fn create_commit(
+    cwd: &str,
+    message: Option<&str>,
+    options: &CommitOptions,
+    cancel: &AtomicBool,
+    on_line: &mut dyn FnMut(&'static str, &str),
+) -> Result<serde_json::Value, CommitError> {
+    let args = commit_args(message, options)?;
+    let hooks = !options.no_verify && has_commit_hooks(cwd);
+    let before = if hooks { snapshot_changes(cwd) } else { HashMap::new() };
+
+    let mut output: Vec<String> = Vec::new();
+    let mut collect = |stream: &'static str, line: &str| {
+        output.push(line.to_string());
+        on_line(stream, line);
+    };
+    let timeout = options.timeout_secs.map(Duration::from_secs);
+    let result = match run_streamed(git_cmd(&args, cwd), cancel, timeout, &mut collect) {
+        Ok(result) => result,
+        Err(TaskError::Spawn(message)) => return Err(CommitError::Git { message }),
+        Err(error) => {
+            // git 收到 SIGTERM 会自己删掉锁文件；被强杀后留下的锁也可能属于终端里的 git，只提示不删除
+            if let Some(message) = queue::leftover_lock(cwd) {
+                return Err(CommitError::Git { message });
+            }
+            return Err(if error == TaskError::Cancelled { CommitError::Cancelled } else { CommitError::TimedOut });
+        }
+    };
+    let modified_files = if hooks { files_modified_by_hooks(cwd, &before) } else { Vec::new() };
+
+    if !result.success {
+        let message = if result.stderr.is_empty() { result.stdout } else { result.stderr };
+        let lower = message.to_lowercase();
+        return Err(if is_signing_failure(&message) {
+            CommitError::Signing { message }
+        } else if hooks && !lower.contains("fatal:") && !lower.contains("nothing to commit") && !lower.contains("no changes added") {
+            CommitError::Hook { message, output, modified_files }
+        } else {
+            CommitError::Git { message: queue::explain_lock_error(cwd, message) }
+        });
+    }
+
+    let hash = git(&["rev-parse", "HEAD"], cwd).unwrap_or_default();
+    Ok(serde_json::json!({
+        "ok": true,
+        "result": result.stdout,
+        "hash": hash,
+        "output": output,
+        "modifiedFiles": modified_files,
+    }))
+}
+
+/// 提交过程中钩子的输出会以 `git-task-output` 事件逐行推送，可通过 git_cancel_task 取消
+#[tauri::command]
+pub async fn git_commit(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    tasks: State<'_, GitTasks>,
+    message: Option<String>,
+    options: Option<CommitOptions>,
+) -> Result<serde_json::Value, CommitError> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "commit").await;
+    let options = options.unwrap_or_default();
+    let (task_id, cancel) = tasks.register(options.task_id.clone());
+    let result = create_commit(&cwd, message.as_deref(), &options, &cancel, &mut |stream, line| {
+        emit_line(&app, &task_id, stream, line)
+    });
+    tasks.finish(&task_id);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo, run_git};
+    use std::fs;
+    use std::path::Path;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    fn commit(cwd: &str, message: Option<&str>, options: &CommitOptions) -> Result<serde_json::Value, CommitError> {
+        create_commit(cwd, message, options, &AtomicBool::new(false), &mut |_, _| {})
+    }
+
+    #[cfg(unix)]
+    fn install_hook(cwd: &str, name: &str, script: &str) {
+        use std::os::unix::fs::PermissionsExt;
+        let path = Path::new(cwd).join(".git").join("hooks").join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    fn last_message(cwd: &str) -> String {
+        git(&["log", "-1", "--format=%B"], cwd).unwrap()
+    }
+
+    #[test]
+    fn amend_without_message_keeps_message_and_adds_staged_changes() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "original");
+        fs::write(Path::new(&cwd).join("b.txt"), "b").unwrap();
+        run_git(&["add", "b.txt"], &cwd);
+
+        commit(&cwd, None, &CommitOptions { amend: true, ..Default::default() }).unwrap();
+
+        assert_eq!(last_message(&cwd), "original");
+        assert_eq!(git(&["rev-list", "--count", "HEAD"], &cwd).unwrap(), "1");
+        assert!(git(&["ls-files", "b.txt"], &cwd).unwrap().contains("b.txt"));
+    }
+
+    #[test]
+    fn amend_with_message_rewrites_message() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "original");
+
+        commit(&cwd, Some("reworded"), &CommitOptions { amend: true, ..Default::default() }).unwrap();
+
+        assert_eq!(last_message(&cwd), "reworded");
+    }
+
+    #[test]
+    fn signoff_author_and_allow_empty_are_applied() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+
+        let options = CommitOptions {
+            signoff: true,
+            author: Some("Someone Else <else@example.com>".into()),
+            allow_empty: true,
+            ..Default::default()
+        };
+        commit(&cwd, Some("empty"), &options).unwrap();
+
+        assert!(last_message(&cwd).contains("Signed-off-by: Tester <test@example.com>"));
+        assert_eq!(git(&["log", "-1", "--format=%an <%ae>"], &cwd).unwrap(), "Someone Else <else@example.com>");
+    }
+
+    #[test]
+    fn selected_paths_are_committed_without_touching_other_staged_files() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a1", "init a");
+        commit_file(&cwd, "b.txt", "b1", "init b");
+        fs::write(Path::new(&cwd).join("a.txt"), "a2").unwrap();
+        fs::write(Path::new(&cwd).join("b.txt"), "b2").unwrap();
+        run_git(&["add", "b.txt"], &cwd);
+
+        let options = CommitOptions { paths: vec!["a.txt".into()], ..Default::default() };
+        commit(&cwd, Some("only a"), &options).unwrap();
+
+        let changed = git(&["show", "--name-only", "--format=", "HEAD"], &cwd).unwrap();
+        assert_eq!(changed, "a.txt");
+        let staged = git_raw(&["diff", "--cached", "--name-only"], &cwd).unwrap();
+        assert_eq!(staged.trim(), "b.txt");
+    }
+
+    #[test]
+    fn missing_message_without_amend_is_rejected() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+
+        assert!(matches!(
+            commit(&cwd, Some("  "), &CommitOptions::default()),
+            Err(CommitError::Git { .. })
+        ));
+    }
+
+    #[test]
+    fn signing_failure_is_reported_as_signing_error() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        run_git(&["config", "commit.gpgsign", "true"], &cwd);
+        run_git(&["config", "gpg.program", "false"], &cwd);
+        fs::write(Path::new(&cwd).join("a.txt"), "v2").unwrap();
+        run_git(&["add", "a.txt"], &cwd);
+
+        let result = commit(&cwd, Some("signed"), &CommitOptions::default());
+
+        assert!(matches!(result, Err(CommitError::Signing { .. })));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hook_output_is_streamed_and_restaged_files_are_reported() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        install_hook(&cwd, "pre-commit", "echo formatting\nprintf formatted > a.txt\ngit add a.txt");
+        fs::write(Path::new(&cwd).join("a.txt"), "v2").unwrap();
+        run_git(&["add", "a.txt"], &cwd);
+
+        let mut streamed: Vec<String> = Vec::new();
+        let result = create_commit(
+            &cwd,
+            Some("with hook"),
+            &CommitOptions::default(),
+            &AtomicBool::new(false),
+            &mut |_, line| streamed.push(line.to_string()),
+        )
+        .unwrap();
+
+        assert!(streamed.iter().any(|l| l == "formatting"));
+        assert_eq!(result["modifiedFiles"], serde_json::json!(["a.txt"]));
+        assert_eq!(git(&["show", "HEAD:a.txt"], &cwd).unwrap(), "formatted");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_hook_is_reported_and_no_verify_bypasses_it() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        install_hook(&cwd, "pre-commit", "echo lint failed >&2\nexit 1");
+        fs::write(Path::new(&cwd).join("a.txt"), "v2").unwrap();
+        run_git(&["add", "a.txt"], &cwd);
+
+        match commit(&cwd, Some("blocked"), &CommitOptions::default()) {
+            Err(CommitError::Hook { output, .. }) => assert!(output.contains(&"lint failed".to_string())),
+            other => panic!("expected hook error, got {:?}", other),
+        }
+
+        let options = CommitOptions { no_verify: true, ..Default::default() };
+        commit(&cwd, Some("bypassed"), &options).unwrap();
+        assert_eq!(last_message(&cwd), "bypassed");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn slow_hook_times_out() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        install_hook(&cwd, "pre-commit", "sleep 5");
+
+        let options = CommitOptions { allow_empty: true, timeout_secs: Some(1), ..Default::default() };
+        let result = commit(&cwd, Some("slow"), &options);
+
+        assert!(matches!(result, Err(CommitError::TimedOut)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn running_commit_can_be_cancelled() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        install_hook(&cwd, "pre-commit", "sleep 5");
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            flag.store(true, Ordering::SeqCst);
+        });
+        let options = CommitOptions { allow_empty: true, ..Default::default() };
+        let result = create_commit(&cwd, Some("cancel me"), &options, &cancel, &mut |_, _| {});
+
+        assert!(matches!(result, Err(CommitError::Cancelled)));
+    }
+
+    /// 等待条件成立，最多 10 秒
+    #[cfg(target_os = "linux")]
+    fn wait_until(mut done: impl FnMut() -> bool) -> bool {
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while std::time::Instant::now() < deadline {
+            if done() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        done()
+    }
+
+    /// 进程是否还在运行（已退出但未回收的僵尸进程不算）
+    #[cfg(target_os = "linux")]
+    fn process_running(pid: &str) -> bool {
+        fs::read_to_string(format!("/proc/{}/stat", pid))
+            .is_ok_and(|stat| stat.rsplit(')').next().is_some_and(|rest| !rest.trim_start().starts_with('Z')))
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn cancelled_partial_commit_leaves_no_lock_or_hook_behind() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a1", "init a");
+        commit_file(&cwd, "b.txt", "b1", "init b");
+        install_hook(&cwd, "pre-commit", "echo $$ > hook.pid\nexec sleep 30");
+        fs::write(Path::new(&cwd).join("a.txt"), "a2").unwrap();
+        fs::write(Path::new(&cwd).join("b.txt"), "b2").unwrap();
+        run_git(&["add", "b.txt"], &cwd);
+
+        // 钩子开始运行（此时 git 已经建好锁）后再取消
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        let pid_file = Path::new(&cwd).join("hook.pid");
+        let hook_pid = std::thread::spawn(move || {
+            let mut pid = String::new();
+            wait_until(|| {
+                pid = fs::read_to_string(&pid_file).unwrap_or_default().trim().to_string();
+                !pid.is_empty()
+            });
+            flag.store(true, Ordering::SeqCst);
+            pid
+        });
+        let options = CommitOptions { paths: vec!["a.txt".into()], ..Default::default() };
+        let result = create_commit(&cwd, Some("cancel me"), &options, &cancel, &mut |_, _| {});
+
+        assert!(matches!(result, Err(CommitError::Cancelled)), "{:?}", result);
+        let locks: Vec<String> = fs::read_dir(Path::new(&cwd).join(".git"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".lock"))
+            .collect();
+        assert!(locks.is_empty(), "leftover locks: {:?}", locks);
+        run_git(&["add", "a.txt"], &cwd);
+        // 钩子随 git 一起被杀，不会在取消后继续跑完
+        let hook_pid = hook_pid.join().unwrap();
+        assert!(wait_until(|| !process_running(&hook_pid)), "hook {} still running", hook_pid);
+    }
+}
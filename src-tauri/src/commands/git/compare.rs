@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use tauri::State;
+use crate::state::AppState;
+use super::{check_rev, git, git_raw};
+
+/// 一次比较中内联返回的文件内容总量上限，超过后其余文件需用 git_compare_file 单独读取
+const MAX_INLINE_CONTENT: usize = 5 * 1024 * 1024;
+
+/// 比较方式：two-dot 直接比较两端；three-dot 比较 head 与两者的合并基点（即 PR 视角）
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CompareMode {
+    TwoDot,
+    #[default]
+    ThreeDot,
+}
+
+/// 解析出实际参与比较的旧端提交
+fn resolve_base(cwd: &str, base: &str, head: &str, mode: CompareMode) -> Result<String, String> {
+    check_rev(base)?;
+    check_rev(head)?;
+    match mode {
+        CompareMode::TwoDot => git(&["rev-parse", "--verify", &format!("{}^{{commit}}", base)], cwd),
+        CompareMode::ThreeDot => git(&["merge-base", base, head], cwd)
+            .map_err(|_| format!("{} 与 {} 没有共同祖先", base, head)),
+    }
+}
+
+/// 解析 `diff -z --numstat`：二进制文件的增删行数为 None
+pub(super) fn parse_numstat(raw: &str) -> HashMap<String, (Option<u64>, Option<u64>)> {
+    let mut stats = HashMap::new();
+    let mut tokens = raw.split('\0');
+    while let Some(token) = tokens.next() {
+        let mut parts = token.splitn(3, '\t');
+        let (Some(added), Some(deleted), Some(path)) = (parts.next(), parts.next(), parts.next()) else { continue };
+        // 重命名时路径为空，随后两个字段是旧路径和新路径
+        let path = if path.is_empty() {
+            tokens.next();
+            tokens.next().unwrap_or_default()
+        } else {
+            path
+        };
+        stats.insert(path.to_string(), (added.parse().ok(), deleted.parse().ok()));
+    }
+    stats
+}
+
+/// 解析 `diff -z --name-status`，返回 (状态, 旧路径, 新路径)
+pub(super) fn parse_name_status(raw: &str) -> Vec<(String, String, String)> {
+    let mut files = Vec::new();
+    let mut tokens = raw.split('\0').filter(|t| !t.is_empty());
+    while let Some(status) = tokens.next() {
+        let Some(path) = tokens.next() else { break };
+        if status.starts_with('R') || status.starts_with('C') {
+            let new_path = tokens.next().unwrap_or(path);
+            files.push((status[..1].to_string(), path.to_string(), new_path.to_string()));
+        } else {
+            files.push((status.to_string(), path.to_string(), path.to_string()));
+        }
+    }
+    files
+}
+
+/// 文件在某个提交中的原始内容，不裁剪首尾空白，行尾换行的变化也要能比较出来
+fn content_at(cwd: &str, rev: &str, path: &str) -> String {
+    git_raw(&["show", &format!("{}:{}", rev, path)], cwd).unwrap_or_default()
+}
+
+fn compare(cwd: &str, base: &str, head: &str, mode: CompareMode) -> Result<serde_json::Value, String> {
+    let old = resolve_base(cwd, base, head, mode)?;
+    let new = git(&["rev-parse", "--verify", &format!("{}^{{commit}}", head)], cwd)?;
+
+    let stats = parse_numstat(&git_raw(&["diff", "-z", "-M", "--numstat", &old, &new], cwd)?);
+    let changes = parse_name_status(&git_raw(&["diff", "-z", "-M", "--name-status", &old, &new], cwd)?);
+
+    let mut inline_budget = MAX_INLINE_CONTENT;
+    let files: Vec<serde_json::Value> = changes
+        .into_iter()
+        .map(|(status, old_path, new_path)| {
+            let (additions, deletions) = stats.get(&new_path).copied().unwrap_or((None, None));
+            let binary = additions.is_none() && deletions.is_none() && stats.contains_key(&new_path);
+            let mut entry = serde_json::json!({
+                "status": status,
+                "path": new_path,
+                "oldPath": old_path,
+                "additions": additions,
+                "deletions": deletions,
+                "binary": binary,
+            });
+            if binary {
+                return entry;
+            }
+            if inline_budget == 0 {
+                entry["contentOmitted"] = serde_json::json!(true);
+                return entry;
+            }
+            let old_content = if status == "A" { String::new() } else { content_at(cwd, &old, &old_path) };
+            let new_content = if status == "D" { String::new() } else { content_at(cwd, &new, &new_path) };
+            inline_budget = inline_budget.saturating_sub(old_content.len() + new_content.len());
+            entry["oldContent"] = serde_json::json!(old_content);
+            entry["newContent"] = serde_json::json!(new_content);
+            entry
+        })
+        .collect();
+
+    let commits: Vec<serde_json::Value> = git(&["log", "--format=%H%x00%h%x00%s", &format!("{}..{}", old, new)], cwd)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.splitn(3, '\0').collect();
+            (parts.len() == 3).then(|| serde_json::json!({ "hash": parts[0], "shortHash": parts[1], "message": parts[2] }))
+        })
+        .collect();
+
+    Ok(serde_json::json!({
+        "base": old,
+        "head": new,
+        "files": files,
+        "commits": commits,
+    }))
+}
+
+/// 比较两个分支、提交或任意引用，返回改动文件（含 numstat）及每个文件的新旧内容
+#[tauri::command]
+pub async fn git_compare(
+    state: State<'_, AppState>,
+    base: String,
+    head: String,
+    mode: Option<CompareMode>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    compare(&cwd, &base, &head, mode.unwrap_or_default())
+}
+
+/// 读取 git_compare 中因体积未内联的单个文件
+#[tauri::command]
+pub async fn git_compare_file(
+    state: State<'_, AppState>,
+    base: String,
+    head: String,
+    mode: Option<CompareMode>,
+    file: String,
+    old_file: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let old = resolve_base(&cwd, &base, &head, mode.unwrap_or_default())?;
+    let old_file = old_file.unwrap_or_else(|| file.clone());
+    Ok(serde_json::json!({
+        "oldContent": content_at(&cwd, &old, &old_file),
+        "newContent": content_at(&cwd, &head, &file),
+        "file": file,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo, run_git};
+
+    /// main 与 feature 自 base 提交起各自前进
+    fn diverged_repo() -> (tempfile::TempDir, String) {
+        let (dir, cwd) = init_repo();
+        commit_file(&cwd, "shared.txt", "base\n", "base");
+        run_git(&["checkout", "-q", "-b", "feature"], &cwd);
+        commit_file(&cwd, "feature.txt", "one\ntwo\n", "feature work");
+        run_git(&["mv", "shared.txt", "renamed.txt"], &cwd);
+        run_git(&["commit", "-q", "-m", "rename"], &cwd);
+        run_git(&["checkout", "-q", "-"], &cwd);
+        commit_file(&cwd, "main.txt", "main\n", "main work");
+        (dir, cwd)
+    }
+
+    fn paths(result: &serde_json::Value) -> Vec<String> {
+        let mut paths: Vec<String> = result["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| format!("{} {}", f["status"].as_str().unwrap(), f["path"].as_str().unwrap()))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn three_dot_compares_against_merge_base() {
+        let (_dir, cwd) = diverged_repo();
+        let main = git(&["rev-parse", "--abbrev-ref", "HEAD"], &cwd).unwrap();
+
+        let result = compare(&cwd, &main, "feature", CompareMode::ThreeDot).unwrap();
+
+        assert_eq!(paths(&result), vec!["A feature.txt", "R renamed.txt"]);
+        let added = result["files"].as_array().unwrap().iter().find(|f| f["path"] == "feature.txt").unwrap();
+        assert_eq!(added["additions"], 2);
+        assert_eq!(added["deletions"], 0);
+        assert_eq!(added["newContent"], "one\ntwo\n");
+        let renamed = result["files"].as_array().unwrap().iter().find(|f| f["path"] == "renamed.txt").unwrap();
+        assert_eq!(renamed["oldPath"], "shared.txt");
+        assert_eq!(renamed["oldContent"], "base\n");
+        assert_eq!(result["commits"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn two_dot_includes_changes_on_both_sides() {
+        let (_dir, cwd) = diverged_repo();
+        let main = git(&["rev-parse", "--abbrev-ref", "HEAD"], &cwd).unwrap();
+
+        let result = compare(&cwd, &main, "feature", CompareMode::TwoDot).unwrap();
+
+        assert_eq!(paths(&result), vec!["A feature.txt", "D main.txt", "R renamed.txt"]);
+    }
+
+    #[test]
+    fn binary_files_are_marked_without_content() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "init");
+        std::fs::write(std::path::Path::new(&cwd).join("img.bin"), [0u8, 1, 2, 0, 255]).unwrap();
+        run_git(&["add", "img.bin"], &cwd);
+        run_git(&["commit", "-q", "-m", "bin"], &cwd);
+
+        let result = compare(&cwd, "HEAD~1", "HEAD", CompareMode::TwoDot).unwrap();
+
+        assert_eq!(result["files"][0]["binary"], true);
+        assert!(result["files"][0]["additions"].is_null());
+        assert!(result["files"][0].get("newContent").is_none());
+    }
+
+    #[test]
+    fn unrelated_histories_have_no_merge_base() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "init");
+        let main = git(&["rev-parse", "HEAD"], &cwd).unwrap();
+        run_git(&["checkout", "-q", "--orphan", "other"], &cwd);
+        commit_file(&cwd, "b.txt", "b\n", "other root");
+
+        assert!(compare(&cwd, &main, "other", CompareMode::ThreeDot).is_err());
+        assert!(compare(&cwd, &main, "other", CompareMode::TwoDot).is_ok());
+    }
+}
@@ -0,0 +1,497 @@
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use crate::state::AppState;
+use super::queue::GitQueue;
+use super::{conflicted_files, git, git_cmd, git_path, git_raw, run_conflicting};
+
+/// 当前仓库中进行到一半的操作：rebase / cherry-pick / revert / merge
+pub(super) fn in_progress_operation(cwd: &str) -> Option<&'static str> {
+    let exists = |name: &str| git_path(cwd, name).map(|p| p.exists()).unwrap_or(false);
+    if exists("rebase-merge") || exists("rebase-apply") {
+        Some("rebase")
+    } else if exists("CHERRY_PICK_HEAD") {
+        Some("cherry-pick")
+    } else if exists("REVERT_HEAD") {
+        Some("revert")
+    } else if let Some(op) = sequencer_operation(cwd) {
+        Some(op)
+    } else if exists("MERGE_HEAD") {
+        Some("merge")
+    } else {
+        None
+    }
+}
+
+/// 多提交的 cherry-pick / revert 在两步之间（如冲突已提交但还未 --continue）只剩下 sequencer 目录
+fn sequencer_operation(cwd: &str) -> Option<&'static str> {
+    let todo = git_path(cwd, "sequencer/todo").ok()?;
+    let content = std::fs::read_to_string(todo).ok()?;
+    let first = content.lines().find(|l| !l.trim().is_empty() && !l.starts_with('#'))?;
+    Some(if first.starts_with("revert") { "revert" } else { "cherry-pick" })
+}
+
+/// 读取索引中某个阶段的文件内容：1 = base，2 = ours，3 = theirs；该阶段不存在时返回 None
+fn stage_content(cwd: &str, stage: u8, path: &str) -> Option<String> {
+    git_raw(&["show", &format!(":{}:{}", stage, path)], cwd).ok()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(super) enum MergeRegion {
+    Common { text: String },
+    Conflict { index: usize, ours: String, base: String, theirs: String },
+}
+
+/// merge_file 使用的冲突标记长度；文件里本来就有的 `=======` 等行不会被误认成标记
+const MARKER_SIZE: usize = 31;
+
+/// 是否为 MARKER_SIZE 个 ch 组成的冲突标记，后面可以跟一个空格和标签
+fn is_marker(line: &str, ch: char) -> bool {
+    let rest = line.trim_start_matches(ch);
+    line.len() - rest.len() == MARKER_SIZE && (rest.is_empty() || rest.starts_with(' '))
+}
+
+/// 解析 `git merge-file -p --diff3 --marker-size=MARKER_SIZE` 输出中的冲突标记
+pub(super) fn parse_merge_regions(merged: &str) -> Vec<MergeRegion> {
+    enum Section {
+        Common,
+        Ours,
+        Base,
+        Theirs,
+    }
+    let mut regions: Vec<MergeRegion> = Vec::new();
+    let mut section = Section::Common;
+    let (mut common, mut ours, mut base, mut theirs) = (String::new(), String::new(), String::new(), String::new());
+    let mut index = 0;
+
+    for line in merged.split_inclusive('\n') {
+        let marker = line.trim_end_matches(['\n', '\r']);
+        match section {
+            Section::Common if is_marker(marker, '<') => {
+                if !common.is_empty() {
+                    regions.push(MergeRegion::Common { text: std::mem::take(&mut common) });
+                }
+                section = Section::Ours;
+            }
+            Section::Ours if is_marker(marker, '|') => section = Section::Base,
+            Section::Ours | Section::Base if marker == "=".repeat(MARKER_SIZE) => section = Section::Theirs,
+            Section::Theirs if is_marker(marker, '>') => {
+                regions.push(MergeRegion::Conflict {
+                    index,
+                    ours: std::mem::take(&mut ours),
+                    base: std::mem::take(&mut base),
+                    theirs: std::mem::take(&mut theirs),
+                });
+                index += 1;
+                section = Section::Common;
+            }
+            Section::Common => common.push_str(line),
+            Section::Ours => ours.push_str(line),
+            Section::Base => base.push_str(line),
+            Section::Theirs => theirs.push_str(line),
+        }
+    }
+    if !common.is_empty() {
+        regions.push(MergeRegion::Common { text: common });
+    }
+    regions
+}
+
+/// 用 `git merge-file` 在临时文件上做三方合并，不修改工作区
+fn merge_file(cwd: &str, ours: &str, base: &str, theirs: &str) -> Result<String, String> {
+    let stamp = format!(
+        "openloom-merge-{}-{}",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0)
+    );
+    let dir = std::env::temp_dir();
+    let files = [
+        (dir.join(format!("{}-ours", stamp)), ours),
+        (dir.join(format!("{}-base", stamp)), base),
+        (dir.join(format!("{}-theirs", stamp)), theirs),
+    ];
+    for (path, content) in &files {
+        std::fs::write(path, content).map_err(|e| e.to_string())?;
+    }
+    let paths: Vec<String> = files.iter().map(|(p, _)| p.to_string_lossy().to_string()).collect();
+    let marker_size = format!("--marker-size={}", MARKER_SIZE);
+
+    let output = git_cmd(
+        &[
+            "merge-file", "-p", "--diff3", &marker_size,
+            "-L", "ours", "-L", "base", "-L", "theirs",
+            &paths[0], &paths[1], &paths[2],
+        ],
+        cwd,
+    )
+    .output();
+    for (path, _) in &files {
+        let _ = std::fs::remove_file(path);
+    }
+    let output = output.map_err(|e| e.to_string())?;
+
+    // 退出码是冲突数量，出错时为负数（在 Unix 上表现为 255）
+    match output.status.code() {
+        Some(code) if (0..=127).contains(&code) => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        _ => Err(String::from_utf8_lossy(&output.stderr).to_string()),
+    }
+}
+
+fn merge_regions(cwd: &str, path: &str) -> Result<Vec<MergeRegion>, String> {
+    let base = stage_content(cwd, 1, path).unwrap_or_default();
+    let ours = stage_content(cwd, 2, path).unwrap_or_default();
+    let theirs = stage_content(cwd, 3, path).unwrap_or_default();
+    let merged = merge_file(cwd, &ours, &base, &theirs)?;
+    Ok(parse_merge_regions(&merged))
+}
+
+/// 单个冲突区域的取舍
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "choice", rename_all = "lowercase")]
+pub enum RegionChoice {
+    Ours,
+    Theirs,
+    Base,
+    /// 先 ours 后 theirs
+    Both,
+    Custom { text: String },
+}
+
+pub(super) fn apply_choices(regions: &[MergeRegion], choices: &[RegionChoice]) -> Result<String, String> {
+    let conflict_count = regions
+        .iter()
+        .filter(|r| matches!(r, MergeRegion::Conflict { .. }))
+        .count();
+    if choices.len() != conflict_count {
+        return Err(format!("expected {} choices, got {}", conflict_count, choices.len()));
+    }
+
+    let mut result = String::new();
+    for region in regions {
+        match region {
+            MergeRegion::Common { text } => result.push_str(text),
+            MergeRegion::Conflict { index, ours, base, theirs } => match &choices[*index] {
+                RegionChoice::Ours => result.push_str(ours),
+                RegionChoice::Theirs => result.push_str(theirs),
+                RegionChoice::Base => result.push_str(base),
+                RegionChoice::Both => {
+                    result.push_str(ours);
+                    result.push_str(theirs);
+                }
+                RegionChoice::Custom { text } => result.push_str(text),
+            },
+        }
+    }
+    Ok(result)
+}
+
+fn write_resolved(cwd: &str, path: &str, content: &str) -> Result<(), String> {
+    let full_path = std::path::Path::new(cwd).join(path);
+    std::fs::write(full_path, content).map_err(|e| e.to_string())?;
+    git(&["add", "--", path], cwd)?;
+    Ok(())
+}
+
+/// 整个文件取某一方；该方已删除文件时直接删除
+fn take_side(cwd: &str, path: &str, side: &str) -> Result<(), String> {
+    let stage = match side {
+        "ours" => 2,
+        "theirs" => 3,
+        _ => return Err(format!("unknown side: {}", side)),
+    };
+    if stage_content(cwd, stage, path).is_none() {
+        git(&["rm", "--quiet", "--", path], cwd)?;
+    } else {
+        git(&["checkout", &format!("--{}", side), "--", path], cwd)?;
+        git(&["add", "--", path], cwd)?;
+    }
+    Ok(())
+}
+
+fn resolve_operation(cwd: &str, operation: Option<String>) -> Result<String, String> {
+    operation
+        .filter(|o| !o.is_empty())
+        .or_else(|| in_progress_operation(cwd).map(String::from))
+        .ok_or_else(|| "no merge, rebase, cherry-pick or revert in progress".to_string())
+}
+
+pub(super) fn abort_operation(cwd: &str, operation: &str) -> Result<(), String> {
+    match operation {
+        "merge" | "rebase" | "cherry-pick" | "revert" => {
+            git(&[operation, "--abort"], cwd)?;
+            Ok(())
+        }
+        _ => Err(format!("unknown operation: {}", operation)),
+    }
+}
+
+pub(super) fn continue_operation(cwd: &str, operation: &str) -> Result<serde_json::Value, String> {
+    if !matches!(operation, "merge" | "rebase" | "cherry-pick" | "revert") {
+        return Err(format!("unknown operation: {}", operation));
+    }
+    let conflicts = conflicted_files(cwd);
+    if !conflicts.is_empty() {
+        return Ok(serde_json::json!({
+            "ok": false,
+            "conflicts": conflicts,
+            "operation": operation,
+            "message": "resolve all conflicts before continuing",
+        }));
+    }
+    // 沿用默认提交信息，不弹出编辑器
+    let mut cmd = git_cmd(&[operation, "--continue"], cwd);
+    cmd.env("GIT_EDITOR", "true");
+    run_conflicting(cmd, cwd)
+}
+
+#[tauri::command]
+pub async fn git_conflicts(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    Ok(serde_json::json!({
+        "operation": in_progress_operation(&cwd),
+        "files": conflicted_files(&cwd),
+    }))
+}
+
+#[tauri::command]
+pub async fn git_conflict_versions(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    Ok(serde_json::json!({
+        "path": path,
+        "base": stage_content(&cwd, 1, &path),
+        "ours": stage_content(&cwd, 2, &path),
+        "theirs": stage_content(&cwd, 3, &path),
+    }))
+}
+
+#[tauri::command]
+pub async fn git_conflict_merge(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let regions = merge_regions(&cwd, &path)?;
+    let conflict_count = regions
+        .iter()
+        .filter(|r| matches!(r, MergeRegion::Conflict { .. }))
+        .count();
+    Ok(serde_json::json!({ "path": path, "regions": regions, "conflictCount": conflict_count }))
+}
+
+/// 按区域取舍写回文件并标记为已解决；传 content 时直接使用手工编辑的完整内容
+#[tauri::command]
+pub async fn git_conflict_resolve(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    path: String,
+    choices: Option<Vec<RegionChoice>>,
+    content: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "conflict-resolve").await;
+    let resolved = match content {
+        Some(content) => content,
+        None => apply_choices(&merge_regions(&cwd, &path)?, &choices.unwrap_or_default())?,
+    };
+    write_resolved(&cwd, &path, &resolved)?;
+    Ok(serde_json::json!({ "ok": true, "remaining": conflicted_files(&cwd) }))
+}
+
+#[tauri::command]
+pub async fn git_conflict_take(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    path: String,
+    side: String,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "conflict-resolve").await;
+    take_side(&cwd, &path, &side)?;
+    Ok(serde_json::json!({ "ok": true, "remaining": conflicted_files(&cwd) }))
+}
+
+#[tauri::command]
+pub async fn git_abort_operation(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    operation: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "abort").await;
+    let operation = resolve_operation(&cwd, operation)?;
+    abort_operation(&cwd, &operation)?;
+    Ok(serde_json::json!({ "ok": true, "operation": operation }))
+}
+
+#[tauri::command]
+pub async fn git_continue_operation(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    operation: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "continue").await;
+    let operation = resolve_operation(&cwd, operation)?;
+    continue_operation(&cwd, &operation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::git_conflicting;
+    use super::super::tests::{commit_file, init_repo, run_git};
+    use std::fs;
+    use std::path::Path;
+
+    /// 在 a.txt 第二行制造一个冲突，返回 merge 的结果
+    fn conflicting_merge(cwd: &str) -> serde_json::Value {
+        commit_file(cwd, "a.txt", "top\nbase\nbottom\n", "init");
+        run_git(&["switch", "-c", "other"], cwd);
+        commit_file(cwd, "a.txt", "top\ntheirs\nbottom\n", "theirs");
+        run_git(&["switch", "-"], cwd);
+        commit_file(cwd, "a.txt", "top\nours\nbottom\n", "ours");
+        git_conflicting(&["merge", "other"], cwd).unwrap()
+    }
+
+    #[test]
+    fn merge_conflict_is_reported_with_type_and_operation() {
+        let (_dir, cwd) = init_repo();
+
+        let result = conflicting_merge(&cwd);
+
+        assert_eq!(result["ok"], false);
+        assert_eq!(result["operation"], "merge");
+        assert_eq!(result["conflicts"][0]["path"], "a.txt");
+        assert_eq!(result["conflicts"][0]["conflict"], "both-modified");
+    }
+
+    #[test]
+    fn stage_contents_are_base_ours_and_theirs() {
+        let (_dir, cwd) = init_repo();
+        conflicting_merge(&cwd);
+
+        assert_eq!(stage_content(&cwd, 1, "a.txt").unwrap(), "top\nbase\nbottom\n");
+        assert_eq!(stage_content(&cwd, 2, "a.txt").unwrap(), "top\nours\nbottom\n");
+        assert_eq!(stage_content(&cwd, 3, "a.txt").unwrap(), "top\ntheirs\nbottom\n");
+    }
+
+    #[test]
+    fn merge_file_produces_structured_regions() {
+        let (_dir, cwd) = init_repo();
+        conflicting_merge(&cwd);
+
+        let regions = merge_regions(&cwd, "a.txt").unwrap();
+
+        assert_eq!(
+            regions,
+            vec![
+                MergeRegion::Common { text: "top\n".into() },
+                MergeRegion::Conflict {
+                    index: 0,
+                    ours: "ours\n".into(),
+                    base: "base\n".into(),
+                    theirs: "theirs\n".into(),
+                },
+                MergeRegion::Common { text: "bottom\n".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn marker_like_lines_in_content_stay_in_their_side() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "top\nbase\nbottom\n", "init");
+        run_git(&["switch", "-c", "other"], &cwd);
+        commit_file(&cwd, "a.txt", "top\ntheirs\n>>>>>>> x\nbottom\n", "theirs");
+        run_git(&["switch", "-"], &cwd);
+        commit_file(&cwd, "a.txt", "top\n<<<<<<< a\n=======\nours\nbottom\n", "ours");
+        git_conflicting(&["merge", "other"], &cwd).unwrap();
+
+        let regions = merge_regions(&cwd, "a.txt").unwrap();
+
+        assert_eq!(
+            regions[1],
+            MergeRegion::Conflict {
+                index: 0,
+                ours: "<<<<<<< a\n=======\nours\n".into(),
+                base: "base\n".into(),
+                theirs: "theirs\n>>>>>>> x\n".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn resolving_with_choices_writes_file_and_marks_resolved() {
+        let (_dir, cwd) = init_repo();
+        conflicting_merge(&cwd);
+
+        let regions = merge_regions(&cwd, "a.txt").unwrap();
+        let resolved = apply_choices(&regions, &[RegionChoice::Both]).unwrap();
+        write_resolved(&cwd, "a.txt", &resolved).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(Path::new(&cwd).join("a.txt")).unwrap(),
+            "top\nours\ntheirs\nbottom\n"
+        );
+        assert!(conflicted_files(&cwd).is_empty());
+    }
+
+    #[test]
+    fn wrong_number_of_choices_is_rejected() {
+        let merged = format!(
+            "a\n{} ours\nx\n{} base\n{}\ny\n{} theirs\n",
+            "<".repeat(MARKER_SIZE),
+            "|".repeat(MARKER_SIZE),
+            "=".repeat(MARKER_SIZE),
+            ">".repeat(MARKER_SIZE)
+        );
+        let regions = parse_merge_regions(&merged);
+
+        assert!(apply_choices(&regions, &[]).is_err());
+    }
+
+    #[test]
+    fn continue_after_resolution_creates_merge_commit() {
+        let (_dir, cwd) = init_repo();
+        conflicting_merge(&cwd);
+        take_side(&cwd, "a.txt", "theirs").unwrap();
+
+        let result = continue_operation(&cwd, "merge").unwrap();
+
+        assert_eq!(result["ok"], true);
+        assert!(in_progress_operation(&cwd).is_none());
+        let parents = git(&["rev-list", "--parents", "-n", "1", "HEAD"], &cwd).unwrap();
+        assert_eq!(parents.split_whitespace().count(), 3);
+    }
+
+    #[test]
+    fn continue_with_unresolved_conflicts_reports_them() {
+        let (_dir, cwd) = init_repo();
+        conflicting_merge(&cwd);
+
+        let result = continue_operation(&cwd, "merge").unwrap();
+
+        assert_eq!(result["ok"], false);
+        assert_eq!(result["conflicts"][0]["path"], "a.txt");
+    }
+
+    #[test]
+    fn abort_restores_pre_merge_state() {
+        let (_dir, cwd) = init_repo();
+        conflicting_merge(&cwd);
+
+        abort_operation(&cwd, "merge").unwrap();
+
+        assert!(in_progress_operation(&cwd).is_none());
+        assert_eq!(
+            fs::read_to_string(Path::new(&cwd).join("a.txt")).unwrap(),
+            "top\nours\nbottom\n"
+        );
+    }
+}
@@ -0,0 +1,533 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+use tauri::State;
+use crate::state::AppState;
+use super::backend::{self, GitBackend};
+use super::history::{revision_content, FileRevision};
+use super::{show, working_diff};
+
+const DEFAULT_CONTEXT_LINES: usize = 3;
+/// Myers/patience 超过该时间后退化为粗粒度结果，避免超大文件卡住
+const DIFF_DEADLINE: Duration = Duration::from_secs(2);
+/// 超过该长度的行不做行内高亮
+const MAX_INLINE_LINE_LEN: usize = 2000;
+/// 新旧行相似度低于该值时整行视为改动，不做行内高亮
+const MIN_INLINE_RATIO: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffAlgorithm {
+    #[default]
+    Myers,
+    Patience,
+    Histogram,
+}
+
+/// 行内高亮的粒度
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InlineMode {
+    #[default]
+    Word,
+    Char,
+    None,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffOptions {
+    #[serde(default)]
+    pub ignore_whitespace: bool,
+    pub context_lines: Option<usize>,
+    #[serde(default)]
+    pub algorithm: DiffAlgorithm,
+    #[serde(default)]
+    pub inline: InlineMode,
+}
+
+/// 要比较的内容来源，与现有的几个 diff 命令一一对应
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum DiffSource {
+    Commit { hash: String, file: String, parent: Option<usize> },
+    Working { file: String, staged: Option<bool> },
+    Revisions { from: FileRevision, to: FileRevision },
+    Text { old_text: String, new_text: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineKind {
+    Context,
+    Add,
+    Delete,
+}
+
+impl LineKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            LineKind::Context => "context",
+            LineKind::Add => "add",
+            LineKind::Delete => "delete",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DiffLine<'a> {
+    kind: LineKind,
+    old_line: Option<usize>,
+    new_line: Option<usize>,
+    content: &'a str,
+    /// 行尾：lf、crlf，文件末尾没有换行时为 none
+    eol: &'static str,
+    /// 单位为 UTF-16 码元，可直接用于 JS 字符串下标
+    highlights: Highlights,
+}
+
+impl<'a> DiffLine<'a> {
+    /// raw 带着行尾；比较时行尾算作内容的一部分，只改换行符或末尾缺换行也会显示为改动
+    fn new(kind: LineKind, old_line: Option<usize>, new_line: Option<usize>, raw: &'a str) -> Self {
+        let (content, eol) = if let Some(content) = raw.strip_suffix("\r\n") {
+            (content, "crlf")
+        } else if let Some(content) = raw.strip_suffix('\n') {
+            (content, "lf")
+        } else {
+            (raw, "none")
+        };
+        DiffLine { kind, old_line, new_line, content, eol, highlights: Vec::new() }
+    }
+}
+
+/// 一处连续改动：旧文件中的行范围被替换为新文件中的行范围
+type Change = (Range<usize>, Range<usize>);
+
+/// 行内改动范围列表，(起点, 终点) 为 UTF-16 码元下标
+type Highlights = Vec<(usize, usize)>;
+
+/// 忽略空白时按去掉所有空白后的内容比较，等同 `git diff -w`
+fn line_key(line: &str, ignore_whitespace: bool) -> String {
+    if ignore_whitespace {
+        line.chars().filter(|c| !c.is_whitespace()).collect()
+    } else {
+        line.to_string()
+    }
+}
+
+/// 把两侧的行映射为整数 id，供各算法使用
+fn intern(old: &[&str], new: &[&str], ignore_whitespace: bool) -> (Vec<u32>, Vec<u32>, u32) {
+    let mut ids: HashMap<String, u32> = HashMap::new();
+    let mut map = |lines: &[&str]| -> Vec<u32> {
+        lines
+            .iter()
+            .map(|line| {
+                let next = ids.len() as u32;
+                *ids.entry(line_key(line, ignore_whitespace)).or_insert(next)
+            })
+            .collect()
+    };
+    let old_ids = map(old);
+    let new_ids = map(new);
+    (old_ids, new_ids, ids.len() as u32)
+}
+
+/// 合并首尾相接的改动（例如 similar 输出的相邻 Delete + Insert）
+fn push_change(changes: &mut Vec<Change>, old: Range<usize>, new: Range<usize>) {
+    if old.is_empty() && new.is_empty() {
+        return;
+    }
+    if let Some(last) = changes.last_mut() {
+        if last.0.end == old.start && last.1.end == new.start {
+            last.0.end = old.end;
+            last.1.end = new.end;
+            return;
+        }
+    }
+    changes.push((old, new));
+}
+
+fn line_changes(old: &[u32], new: &[u32], num_tokens: u32, algorithm: DiffAlgorithm) -> Vec<Change> {
+    let mut changes = Vec::new();
+    match algorithm {
+        DiffAlgorithm::Myers | DiffAlgorithm::Patience => {
+            let algorithm = if algorithm == DiffAlgorithm::Myers {
+                similar::Algorithm::Myers
+            } else {
+                similar::Algorithm::Patience
+            };
+            let deadline = Some(Instant::now() + DIFF_DEADLINE);
+            for op in similar::capture_diff_slices_deadline(algorithm, old, new, deadline) {
+                let (tag, old_range, new_range) = op.as_tag_tuple();
+                if tag != similar::DiffTag::Equal {
+                    push_change(&mut changes, old_range, new_range);
+                }
+            }
+        }
+        DiffAlgorithm::Histogram => {
+            let before: Vec<imara_diff::intern::Token> = old.iter().map(|&t| t.into()).collect();
+            let after: Vec<imara_diff::intern::Token> = new.iter().map(|&t| t.into()).collect();
+            let sink = |b: Range<u32>, a: Range<u32>| {
+                push_change(&mut changes, b.start as usize..b.end as usize, a.start as usize..a.end as usize);
+            };
+            imara_diff::diff_with_tokens(imara_diff::Algorithm::Histogram, &before, &after, num_tokens, sink);
+        }
+    }
+    changes
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.chars().map(char::len_utf16).sum()
+}
+
+/// 一对新旧行的行内改动范围
+fn inline_highlights(old: &str, new: &str, mode: InlineMode) -> (Highlights, Highlights) {
+    if mode == InlineMode::None || old.len() > MAX_INLINE_LINE_LEN || new.len() > MAX_INLINE_LINE_LEN {
+        return (Vec::new(), Vec::new());
+    }
+    let mut config = similar::TextDiff::configure();
+    config.algorithm(similar::Algorithm::Myers);
+    let diff = match mode {
+        InlineMode::Char => config.diff_chars(old, new),
+        _ => config.diff_words(old, new),
+    };
+    if diff.ratio() < MIN_INLINE_RATIO {
+        return (Vec::new(), Vec::new());
+    }
+    let (mut old_ranges, mut new_ranges) = (Vec::new(), Vec::new());
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for change in diff.iter_all_changes() {
+        let len = utf16_len(change.value());
+        match change.tag() {
+            similar::ChangeTag::Equal => {
+                old_pos += len;
+                new_pos += len;
+            }
+            similar::ChangeTag::Delete => {
+                extend_range(&mut old_ranges, old_pos, old_pos + len);
+                old_pos += len;
+            }
+            similar::ChangeTag::Insert => {
+                extend_range(&mut new_ranges, new_pos, new_pos + len);
+                new_pos += len;
+            }
+        }
+    }
+    (old_ranges, new_ranges)
+}
+
+fn extend_range(ranges: &mut Highlights, start: usize, end: usize) {
+    match ranges.last_mut() {
+        Some(last) if last.1 == start => last.1 = end,
+        _ => ranges.push((start, end)),
+    }
+}
+
+/// 按上下文行数把相近的改动分组成 hunk
+fn group_changes(changes: &[Change], context: usize) -> Vec<&[Change]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for i in 1..changes.len() {
+        if changes[i].0.start - changes[i - 1].0.end > context * 2 {
+            groups.push(&changes[start..i]);
+            start = i;
+        }
+    }
+    if !changes.is_empty() {
+        groups.push(&changes[start..]);
+    }
+    groups
+}
+
+/// unified diff 头中的起始行：空范围时指向前一行，与 git 一致
+fn header_start(start: usize, len: usize) -> usize {
+    if len == 0 { start } else { start + 1 }
+}
+
+fn build_hunk<'a>(group: &[Change], old: &[&'a str], new: &[&'a str], context: usize, inline: InlineMode) -> serde_json::Value {
+    let first = &group[0];
+    let last = &group[group.len() - 1];
+    let lead = context.min(first.0.start).min(first.1.start);
+    let old_start = first.0.start - lead;
+    let new_start = first.1.start - lead;
+    let trail = context.min(old.len() - last.0.end).min(new.len() - last.1.end);
+    let old_end = last.0.end + trail;
+    let new_end = last.1.end + trail;
+
+    let mut lines: Vec<DiffLine<'a>> = Vec::new();
+    let (mut o, mut n) = (old_start, new_start);
+    let push_context = |lines: &mut Vec<DiffLine<'a>>, o: &mut usize, n: &mut usize, until: usize| {
+        while *o < until {
+            lines.push(DiffLine::new(LineKind::Context, Some(*o + 1), Some(*n + 1), new[*n]));
+            *o += 1;
+            *n += 1;
+        }
+    };
+    for (old_range, new_range) in group {
+        push_context(&mut lines, &mut o, &mut n, old_range.start);
+        let deleted = lines.len();
+        for i in old_range.clone() {
+            lines.push(DiffLine::new(LineKind::Delete, Some(i + 1), None, old[i]));
+        }
+        let added = lines.len();
+        for i in new_range.clone() {
+            lines.push(DiffLine::new(LineKind::Add, None, Some(i + 1), new[i]));
+        }
+        // 删除与新增按顺序配对做行内高亮
+        for k in 0..old_range.len().min(new_range.len()) {
+            let (old_hl, new_hl) = inline_highlights(lines[deleted + k].content, lines[added + k].content, inline);
+            lines[deleted + k].highlights = old_hl;
+            lines[added + k].highlights = new_hl;
+        }
+        o = old_range.end;
+        n = new_range.end;
+    }
+    push_context(&mut lines, &mut o, &mut n, old_end);
+
+    let old_lines = old_end - old_start;
+    let new_lines = new_end - new_start;
+    let header = format!(
+        "@@ -{},{} +{},{} @@",
+        header_start(old_start, old_lines), old_lines, header_start(new_start, new_lines), new_lines
+    );
+    let lines: Vec<serde_json::Value> = lines
+        .iter()
+        .map(|l| serde_json::json!({
+            "kind": l.kind.as_str(),
+            "oldLine": l.old_line,
+            "newLine": l.new_line,
+            "content": l.content,
+            "eol": l.eol,
+            "highlights": l.highlights,
+        }))
+        .collect();
+    serde_json::json!({
+        "oldStart": header_start(old_start, old_lines),
+        "oldLines": old_lines,
+        "newStart": header_start(new_start, new_lines),
+        "newLines": new_lines,
+        "header": header,
+        "lines": lines,
+    })
+}
+
+/// 比较两段文本，返回结构化的 hunk 列表。按原样切行并保留行尾，CRLF 和末尾换行的变化不会被吞掉
+pub(super) fn diff_text(old_text: &str, new_text: &str, options: &DiffOptions) -> serde_json::Value {
+    let old: Vec<&str> = old_text.split_inclusive('\n').collect();
+    let new: Vec<&str> = new_text.split_inclusive('\n').collect();
+    let (old_ids, new_ids, num_tokens) = intern(&old, &new, options.ignore_whitespace);
+    let changes = line_changes(&old_ids, &new_ids, num_tokens, options.algorithm);
+    let context = options.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES);
+
+    let additions: usize = changes.iter().map(|c| c.1.len()).sum();
+    let deletions: usize = changes.iter().map(|c| c.0.len()).sum();
+    let hunks: Vec<serde_json::Value> = group_changes(&changes, context)
+        .into_iter()
+        .map(|group| build_hunk(group, &old, &new, context, options.inline))
+        .collect();
+    serde_json::json!({ "hunks": hunks, "additions": additions, "deletions": deletions })
+}
+
+fn load_source(backend: &dyn GitBackend, cwd: &str, source: &DiffSource) -> Result<serde_json::Value, String> {
+    match source {
+        DiffSource::Commit { hash, file, parent } => show::file_diff(backend, cwd, hash, file, *parent),
+        DiffSource::Working { file, staged } => Ok(working_diff(backend, cwd, file, staged.unwrap_or(false))),
+        DiffSource::Revisions { from, to } => Ok(serde_json::json!({
+            "oldContent": revision_content(backend, cwd, from),
+            "newContent": revision_content(backend, cwd, to),
+            "file": to.path,
+            "oldFile": from.path,
+        })),
+        DiffSource::Text { old_text, new_text } => Ok(serde_json::json!({ "oldContent": old_text, "newContent": new_text })),
+    }
+}
+
+/// 结构化 diff：在后端完成行级和行内比较，只把 hunk 交给前端。
+/// 子模块、LFS、二进制等特殊结果原样保留其元数据，hunks 为空
+#[tauri::command]
+pub async fn git_diff_hunks(
+    state: State<'_, AppState>,
+    source: DiffSource,
+    options: Option<DiffOptions>,
+) -> Result<serde_json::Value, String> {
+    let root = state.get_root();
+    let cwd = root.to_string_lossy().to_string();
+    let options = options.unwrap_or_default();
+    let mut result = load_source(backend::for_root(&root), &cwd, &source)?;
+    let special = result.get("submodule").is_some() || result.get("lfs").is_some() || result["binary"] == true;
+    let diff = if special {
+        serde_json::json!({ "hunks": [], "additions": 0, "deletions": 0 })
+    } else {
+        diff_text(
+            result["oldContent"].as_str().unwrap_or_default(),
+            result["newContent"].as_str().unwrap_or_default(),
+            &options,
+        )
+    };
+    // 不再回传整份文件内容
+    if let Some(map) = result.as_object_mut() {
+        map.remove("oldContent");
+        map.remove("newContent");
+        map.insert("hunks".into(), diff["hunks"].clone());
+        map.insert("additions".into(), diff["additions"].clone());
+        map.insert("deletions".into(), diff["deletions"].clone());
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{backends, commit_file, init_repo};
+
+    fn kinds(hunk: &serde_json::Value) -> String {
+        hunk["lines"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| match l["kind"].as_str().unwrap() {
+                "add" => '+',
+                "delete" => '-',
+                _ => ' ',
+            })
+            .collect()
+    }
+
+    fn numbered(n: usize) -> String {
+        (1..=n).map(|i| format!("line {}\n", i)).collect()
+    }
+
+    #[test]
+    fn builds_hunks_with_context_and_line_numbers() {
+        let old = numbered(20);
+        let new = old.replace("line 3\n", "line three\n").replace("line 18\n", "");
+
+        let diff = diff_text(&old, &new, &DiffOptions::default());
+        let hunks = diff["hunks"].as_array().unwrap();
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0]["header"], "@@ -1,6 +1,6 @@");
+        assert_eq!(kinds(&hunks[0]), "  -+   ");
+        assert_eq!(hunks[1]["header"], "@@ -15,6 +15,5 @@");
+        assert_eq!(hunks[1]["lines"][3]["oldLine"], 18);
+        assert_eq!(diff["additions"], 1);
+        assert_eq!(diff["deletions"], 2);
+    }
+
+    #[test]
+    fn nearby_changes_share_a_hunk_and_context_is_configurable() {
+        let old = numbered(10);
+        let new = old.replace("line 2\n", "line two\n").replace("line 7\n", "line seven\n");
+
+        let merged = diff_text(&old, &new, &DiffOptions::default());
+        assert_eq!(merged["hunks"].as_array().unwrap().len(), 1);
+
+        let options = DiffOptions { context_lines: Some(0), ..Default::default() };
+        let split = diff_text(&old, &new, &options);
+        assert_eq!(split["hunks"].as_array().unwrap().len(), 2);
+        assert_eq!(split["hunks"][1]["header"], "@@ -7,1 +7,1 @@");
+    }
+
+    #[test]
+    fn word_and_char_highlights_use_utf16_offsets() {
+        let diff = diff_text("let 名字 = old_value;\n", "let 名字 = new_value;\n", &DiffOptions::default());
+        let lines = &diff["hunks"][0]["lines"];
+        assert_eq!(lines[0]["highlights"], serde_json::json!([[9, 19]]));
+        assert_eq!(lines[1]["highlights"], serde_json::json!([[9, 19]]));
+
+        let options = DiffOptions { inline: InlineMode::Char, ..Default::default() };
+        let diff = diff_text("let 名字 = old_value;\n", "let 名字 = new_value;\n", &options);
+        let lines = &diff["hunks"][0]["lines"];
+        assert_eq!(lines[1]["highlights"], serde_json::json!([[9, 12]]));
+    }
+
+    #[test]
+    fn ignore_whitespace_hides_indentation_changes() {
+        let old = "fn a() {\n  x();\n}\n";
+        let new = "fn a() {\n    x();\n}\n";
+
+        assert_eq!(diff_text(old, new, &DiffOptions::default())["hunks"].as_array().unwrap().len(), 1);
+        let options = DiffOptions { ignore_whitespace: true, ..Default::default() };
+        assert!(diff_text(old, new, &options)["hunks"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn all_algorithms_agree_on_simple_edits() {
+        let old = "a\nb\nc\nd\ne\n";
+        let new = "a\nc\nd\nX\ne\n";
+        for algorithm in [DiffAlgorithm::Myers, DiffAlgorithm::Patience, DiffAlgorithm::Histogram] {
+            let options = DiffOptions { algorithm, context_lines: Some(0), ..Default::default() };
+            let diff = diff_text(old, new, &options);
+            assert_eq!(diff["additions"], 1, "{:?}", algorithm);
+            assert_eq!(diff["deletions"], 1, "{:?}", algorithm);
+            assert_eq!(diff["hunks"].as_array().unwrap().len(), 2, "{:?}", algorithm);
+        }
+    }
+
+    #[test]
+    fn empty_sides_produce_pure_additions_or_deletions() {
+        let diff = diff_text("", "a\nb\n", &DiffOptions::default());
+        assert_eq!(diff["hunks"][0]["header"], "@@ -0,0 +1,2 @@");
+        assert_eq!(kinds(&diff["hunks"][0]), "++");
+
+        let diff = diff_text("a\n", "", &DiffOptions::default());
+        assert_eq!(diff["hunks"][0]["header"], "@@ -1,1 +0,0 @@");
+    }
+
+    #[test]
+    fn leading_blank_lines_keep_line_numbers_and_indentation() {
+        let old = "\n\n    fn a() {}\n";
+        let new = "\n\n    fn b() {}\n";
+
+        let diff = diff_text(old, new, &DiffOptions::default());
+        let lines = &diff["hunks"][0]["lines"];
+        assert_eq!(diff["hunks"][0]["header"], "@@ -1,3 +1,3 @@");
+        assert_eq!(lines[2]["oldLine"], 3);
+        assert_eq!(lines[2]["content"], "    fn a() {}");
+    }
+
+    #[test]
+    fn line_ending_and_missing_final_newline_are_changes() {
+        let diff = diff_text("a\r\nb\n", "a\nb\n", &DiffOptions::default());
+        assert_eq!(kinds(&diff["hunks"][0]), "-+ ");
+        assert_eq!(diff["hunks"][0]["lines"][0]["eol"], "crlf");
+        assert_eq!(diff["hunks"][0]["lines"][1]["eol"], "lf");
+
+        let diff = diff_text("a\nb\n", "a\nb", &DiffOptions::default());
+        assert_eq!(kinds(&diff["hunks"][0]), " -+");
+        assert_eq!(diff["hunks"][0]["lines"][2]["content"], "b");
+        assert_eq!(diff["hunks"][0]["lines"][2]["eol"], "none");
+
+        let options = DiffOptions { ignore_whitespace: true, ..Default::default() };
+        assert!(diff_text("a\r\nb\n", "a\nb", &options)["hunks"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn untouched_working_file_has_no_hunks() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "\n  indented\nlast\n", "init");
+        std::fs::write(std::path::Path::new(&cwd).join("b.txt"), "x\n").unwrap();
+
+        for backend in backends() {
+            for staged in [false, true] {
+                let source = DiffSource::Working { file: "a.txt".into(), staged: Some(staged) };
+                let result = load_source(backend, &cwd, &source).unwrap();
+                let diff = diff_text(
+                    result["oldContent"].as_str().unwrap(),
+                    result["newContent"].as_str().unwrap(),
+                    &DiffOptions::default(),
+                );
+                assert!(diff["hunks"].as_array().unwrap().is_empty(), "staged: {}", staged);
+            }
+        }
+    }
+
+    #[test]
+    fn source_is_parsed_from_tagged_json() {
+        let source: DiffSource =
+            serde_json::from_value(serde_json::json!({ "kind": "text", "oldText": "a", "newText": "b" })).unwrap();
+        assert!(matches!(source, DiffSource::Text { .. }));
+        let source: DiffSource =
+            serde_json::from_value(serde_json::json!({ "kind": "commit", "hash": "HEAD", "file": "a.txt" })).unwrap();
+        assert!(matches!(source, DiffSource::Commit { parent: None, .. }));
+    }
+}
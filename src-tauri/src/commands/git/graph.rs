@@ -0,0 +1,376 @@
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use crate::state::AppState;
+use super::backend::{self, GitBackend};
+use super::log::{LogCursor, LogQuery};
+
+/// 前端调色板的颜色数，颜色下标按分配顺序循环
+const LANE_COLORS: usize = 12;
+
+/// 一条活动泳道：正在等待哪个提交出现
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Lane {
+    hash: String,
+    color: usize,
+    /// 有多少个子提交的边汇入这条泳道，大于 1 说明该提交是分叉点
+    children: u32,
+}
+
+/// 分页之间需要延续的布局状态，序列化后放进游标
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(super) struct GraphLayout {
+    lanes: Vec<Option<Lane>>,
+    next_color: usize,
+    /// 第一页解析出的起点提交中还没出现的（排除的提交写成 ^hash）
+    pending: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct GraphEdge {
+    /// 当前行的泳道
+    from: usize,
+    /// 下一行的泳道
+    to: usize,
+    color: usize,
+    /// 指向的父提交；直通其他提交的泳道为 None
+    parent: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct GraphRow {
+    lane: usize,
+    color: usize,
+    is_merge: bool,
+    /// 多个子提交从这里分出
+    is_branch_point: bool,
+    /// 没有子提交指向它（分支顶端）
+    is_branch_tip: bool,
+    /// 当前行到下一行的所有连线，包括直通的泳道
+    edges: Vec<GraphEdge>,
+    /// 本行绘制需要的泳道数
+    width: usize,
+}
+
+impl GraphLayout {
+    fn from_cursor(cursor: &str) -> Result<Self, String> {
+        let bytes = general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| format!("invalid cursor: {}", cursor))?;
+        serde_json::from_slice(&bytes).map_err(|_| format!("invalid cursor: {}", cursor))
+    }
+
+    fn to_cursor(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        general_purpose::URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// 下一页从哪些提交继续遍历：泳道里等待的父提交，加上还没出现的分支顶端。
+    /// git log --date-order 保证子提交先于父提交输出，没出现的提交的祖先也都还没出现，
+    /// 因此从这里续读不会重复也不需要跳过已读的提交；只有同一秒内的提交先后可能与一次读完不同
+    fn frontier(&self) -> Vec<String> {
+        let mut start = self.pending.clone();
+        for lane in self.lanes.iter().flatten() {
+            if !start.contains(&lane.hash) {
+                start.push(lane.hash.clone());
+            }
+        }
+        start
+    }
+
+    fn alloc_color(&mut self) -> usize {
+        let color = self.next_color % LANE_COLORS;
+        self.next_color += 1;
+        color
+    }
+
+    fn find_lane(&self, hash: &str) -> Option<usize> {
+        self.lanes
+            .iter()
+            .position(|l| l.as_ref().is_some_and(|l| l.hash == hash))
+    }
+
+    /// 第一个空位的下标，没有空位时在右侧追加一个
+    fn free_slot(&mut self) -> usize {
+        match self.lanes.iter().position(|l| l.is_none()) {
+            Some(i) => i,
+            None => {
+                self.lanes.push(None);
+                self.lanes.len() - 1
+            }
+        }
+    }
+
+    /// 按 --date-order 的顺序（子提交先于父提交）放入一个提交，返回它这一行的布局。
+    /// 同一个父提交只会被一条泳道等待：后到的子提交直接连线到已有泳道，
+    /// 因此泳道不会在父提交那一行才汇合。
+    pub(super) fn push(&mut self, hash: &str, parents: &[String]) -> GraphRow {
+        let waiting = self.find_lane(hash);
+        let is_branch_tip = waiting.is_none();
+        let lane = match waiting {
+            Some(i) => i,
+            None => self.free_slot(),
+        };
+        let current = match self.lanes[lane].take() {
+            Some(l) => l,
+            None => Lane { hash: hash.to_string(), color: self.alloc_color(), children: 0 },
+        };
+
+        // 其他活动泳道原样延续到下一行
+        let mut edges: Vec<GraphEdge> = self
+            .lanes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| slot.as_ref().map(|l| GraphEdge { from: i, to: i, color: l.color, parent: None }))
+            .collect();
+
+        for (i, parent) in parents.iter().enumerate() {
+            let (target, color) = match self.find_lane(parent) {
+                Some(target) => {
+                    let Some(l) = self.lanes[target].as_mut() else { continue };
+                    l.children += 1;
+                    (target, l.color)
+                }
+                // 第一父提交沿用本泳道和颜色，其余父提交（被合并的分支）新开泳道
+                None if i == 0 => {
+                    self.lanes[lane] = Some(Lane { hash: parent.clone(), color: current.color, children: 1 });
+                    (lane, current.color)
+                }
+                None => {
+                    let color = self.alloc_color();
+                    let target = self.free_slot();
+                    self.lanes[target] = Some(Lane { hash: parent.clone(), color, children: 1 });
+                    (target, color)
+                }
+            };
+            edges.push(GraphEdge { from: lane, to: target, color, parent: Some(parent.clone()) });
+        }
+        edges.sort_by_key(|e| (e.from, e.to));
+
+        let width = self.lanes.len().max(lane + 1);
+        while matches!(self.lanes.last(), Some(None)) {
+            self.lanes.pop();
+        }
+
+        GraphRow {
+            lane,
+            color: current.color,
+            is_merge: parents.len() > 1,
+            is_branch_point: current.children > 1,
+            is_branch_tip,
+            edges,
+            width,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQuery {
+    /// 上一页返回的 nextCursor，内含泳道状态
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub refs: Vec<String>,
+}
+
+fn graph_page(backend: &dyn GitBackend, cwd: &str, query: &GraphQuery) -> Result<serde_json::Value, String> {
+    let (mut layout, log_cursor) = match query.cursor.as_deref().filter(|c| !c.is_empty()) {
+        Some(c) => {
+            let layout = GraphLayout::from_cursor(c)?;
+            let start = LogCursor { tips: layout.frontier(), offset: 0 };
+            (layout, Some(start.to_cursor()))
+        }
+        None => (GraphLayout::default(), None),
+    };
+    let log_query = LogQuery {
+        cursor: log_cursor,
+        limit: query.limit,
+        refs: query.refs.clone(),
+        ..Default::default()
+    };
+    let (entries, next) = backend.log(cwd, &log_query)?;
+    // 第一页的游标里带着解析好的起点提交
+    if query.cursor.as_deref().is_none_or(|c| c.is_empty()) {
+        if let Some(next) = &next {
+            layout.pending = LogCursor::from_cursor(next)?.tips;
+        }
+    }
+
+    let mut rows: Vec<serde_json::Value> = Vec::with_capacity(entries.len());
+    for mut entry in entries {
+        let hash = entry["hash"].as_str().unwrap_or_default().to_string();
+        let parents: Vec<String> = entry["parents"]
+            .as_array()
+            .map(|a| a.iter().filter_map(|p| p.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+        let row = layout.push(&hash, &parents);
+        layout.pending.retain(|tip| *tip != hash);
+        entry["graph"] = serde_json::to_value(row).map_err(|e| e.to_string())?;
+        rows.push(entry);
+    }
+
+    let next_cursor = next.map(|_| layout.to_cursor());
+    Ok(serde_json::json!({ "rows": rows, "nextCursor": next_cursor }))
+}
+
+#[tauri::command]
+pub async fn git_graph(
+    state: State<'_, AppState>,
+    query: Option<GraphQuery>,
+) -> Result<serde_json::Value, String> {
+    let root = state.get_root();
+    let cwd = root.to_string_lossy().to_string();
+    graph_page(backend::for_root(&root), &cwd, &query.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::git_cmd;
+    use super::super::tests::{backends, init_repo, run_git};
+
+    fn h(s: &str) -> String {
+        s.to_string()
+    }
+
+    /// 以递增的提交时间执行 git；同一秒内的提交先后不固定，从中间续读时可能与一次读完不同
+    fn git_at(cwd: &str, time: u32, args: &[&str]) {
+        let date = format!("{} +0000", 1_700_000_000 + time);
+        let output = git_cmd(args, cwd)
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    fn commit_at(cwd: &str, time: u32, name: &str, content: &str, message: &str) {
+        std::fs::write(std::path::Path::new(cwd).join(name), content).unwrap();
+        run_git(&["add", "--", name], cwd);
+        git_at(cwd, time, &["commit", "-m", message]);
+    }
+
+    #[test]
+    fn linear_history_stays_in_one_lane() {
+        let mut layout = GraphLayout::default();
+        let a = layout.push("c", &[h("b")]);
+        let b = layout.push("b", &[h("a")]);
+        let c = layout.push("a", &[]);
+
+        for row in [&a, &b, &c] {
+            assert_eq!(row.lane, 0);
+            assert_eq!(row.width, 1);
+        }
+        assert!(a.is_branch_tip);
+        assert!(!b.is_branch_tip);
+        assert_eq!(b.edges, vec![GraphEdge { from: 0, to: 0, color: 0, parent: Some(h("a")) }]);
+        assert!(c.edges.is_empty());
+    }
+
+    #[test]
+    fn merge_opens_lane_for_second_parent_and_fork_is_branch_point() {
+        // m 合并 x（主线）和 y（分支），两者都从 base 分出
+        let mut layout = GraphLayout::default();
+        let m = layout.push("m", &[h("x"), h("y")]);
+        assert!(m.is_merge);
+        assert_eq!(m.edges.len(), 2);
+        assert_eq!(m.edges[1].to, 1);
+        assert_ne!(m.edges[0].color, m.edges[1].color);
+
+        let x = layout.push("x", &[h("base")]);
+        assert_eq!(x.lane, 0);
+        // y 的泳道仍在右侧直通
+        assert!(x.edges.contains(&GraphEdge { from: 1, to: 1, color: m.edges[1].color, parent: None }));
+
+        let y = layout.push("y", &[h("base")]);
+        assert_eq!(y.lane, 1);
+        // base 已经在泳道 0 等待，y 直接连线过去并释放自己的泳道
+        assert_eq!(
+            y.edges,
+            vec![
+                GraphEdge { from: 0, to: 0, color: 0, parent: None },
+                GraphEdge { from: 1, to: 0, color: 0, parent: Some(h("base")) },
+            ]
+        );
+
+        let base = layout.push("base", &[]);
+        assert_eq!(base.lane, 0);
+        assert!(base.is_branch_point);
+        assert_eq!(base.width, 1);
+    }
+
+    #[test]
+    fn layout_over_pages_matches_single_pass() {
+        let (_dir, cwd) = init_repo();
+        commit_at(&cwd, 1, "a.txt", "1", "base");
+        run_git(&["switch", "-c", "side"], &cwd);
+        commit_at(&cwd, 2, "b.txt", "1", "side 1");
+        commit_at(&cwd, 3, "b.txt", "2", "side 2");
+        run_git(&["switch", "-"], &cwd);
+        commit_at(&cwd, 4, "a.txt", "2", "main 1");
+        git_at(&cwd, 5, &["merge", "--no-ff", "-m", "merge side", "side"]);
+        commit_at(&cwd, 6, "a.txt", "3", "main 2");
+
+        for backend in backends() {
+            let whole = graph_page(backend, &cwd, &GraphQuery::default()).unwrap();
+
+            let mut paged: Vec<serde_json::Value> = Vec::new();
+            let mut query = GraphQuery { limit: Some(2), ..Default::default() };
+            loop {
+                let page = graph_page(backend, &cwd, &query).unwrap();
+                paged.extend(page["rows"].as_array().unwrap().iter().cloned());
+                match page["nextCursor"].as_str() {
+                    Some(c) => query.cursor = Some(c.to_string()),
+                    None => break,
+                }
+            }
+
+            assert_eq!(paged.len(), 6);
+            assert_eq!(&paged, whole["rows"].as_array().unwrap());
+            let merge = paged.iter().find(|r| r["message"] == "merge side").unwrap();
+            assert_eq!(merge["graph"]["isMerge"], true);
+        }
+    }
+
+    #[test]
+    fn moving_refs_between_pages_do_not_duplicate_or_drop_rows() {
+        let (_dir, cwd) = init_repo();
+        commit_at(&cwd, 1, "a.txt", "1", "base");
+        run_git(&["switch", "-c", "side"], &cwd);
+        commit_at(&cwd, 2, "b.txt", "1", "side 1");
+        run_git(&["switch", "-"], &cwd);
+        commit_at(&cwd, 3, "a.txt", "2", "main 1");
+        commit_at(&cwd, 4, "a.txt", "3", "main 2");
+        run_git(&["tag", "-a", "v1", "-m", "release", "side"], &cwd);
+
+        // 附注标签作为起点时同样要按提交去重
+        for refs in [vec![], vec![h("HEAD"), h("v1")]] {
+            for backend in backends() {
+                let whole = graph_page(backend, &cwd, &GraphQuery { refs: refs.clone(), ..Default::default() }).unwrap();
+                let expected: Vec<String> =
+                    whole["rows"].as_array().unwrap().iter().map(|r| r["hash"].as_str().unwrap().to_string()).collect();
+
+                let mut query = GraphQuery { limit: Some(1), refs: refs.clone(), ..Default::default() };
+                let mut hashes: Vec<String> = Vec::new();
+                loop {
+                    let page = graph_page(backend, &cwd, &query).unwrap();
+                    hashes.extend(page["rows"].as_array().unwrap().iter().map(|r| r["hash"].as_str().unwrap().to_string()));
+                    // 每翻一页都有新提交和新分支出现，已取的页不该因此错位
+                    run_git(&["commit", "-q", "--allow-empty", "-m", "meanwhile"], &cwd);
+                    run_git(&["branch", "-f", "moved", "HEAD"], &cwd);
+                    match page["nextCursor"].as_str() {
+                        Some(c) => query.cursor = Some(c.to_string()),
+                        None => break,
+                    }
+                }
+
+                assert_eq!(hashes, expected);
+                run_git(&["reset", "-q", "--hard", &expected[0]], &cwd);
+                run_git(&["branch", "-D", "moved"], &cwd);
+            }
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use tauri::State;
+use crate::state::AppState;
+use super::backend::{self, blob_text, GitBackend};
+use super::log::LogCursor;
+use super::{git, git_raw};
+
+const DEFAULT_PAGE_SIZE: usize = 30;
+const MAX_PAGE_SIZE: usize = 500;
+
+/// 每条记录以 RS 开头，字段用 NUL 分隔，之后是 --name-status 或 -L 的补丁输出
+const HISTORY_FORMAT: &str = "--format=%x1e%H%x00%h%x00%P%x00%an%x00%ae%x00%ci%x00%s%x00";
+const HISTORY_FIELDS: usize = 7;
+
+/// git_file_history 的参数；给出 startLine/endLine 或 function 时改用 `git log -L`
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileHistoryQuery {
+    pub path: String,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub start_line: Option<u32>,
+    pub end_line: Option<u32>,
+    /// 函数名（正则），对应 `-L :<function>:<path>`
+    pub function: Option<String>,
+}
+
+impl FileHistoryQuery {
+    fn page_size(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// 与 git_log 相同的游标：第一页时的 HEAD 加上已读取的条数，翻页期间 HEAD 移动也不会错位
+    fn log_cursor(&self, cwd: &str) -> Result<Option<LogCursor>, String> {
+        if let Some(c) = self.cursor.as_deref().filter(|c| !c.is_empty()) {
+            return LogCursor::from_cursor(c).map(Some);
+        }
+        // 空仓库没有 HEAD
+        Ok(git(&["rev-parse", "-q", "--verify", "HEAD"], cwd)
+            .ok()
+            .map(|head| LogCursor { tips: vec![head], offset: 0 }))
+    }
+
+    /// 行范围历史的 -L 参数，未指定范围时为 None
+    fn line_range(&self) -> Result<Option<String>, String> {
+        if let Some(function) = self.function.as_deref().filter(|f| !f.is_empty()) {
+            return Ok(Some(format!("-L:{}:{}", function, self.path)));
+        }
+        match (self.start_line, self.end_line) {
+            (None, None) => Ok(None),
+            (Some(start), end) => {
+                let end = end.unwrap_or(start);
+                if start == 0 || end < start {
+                    return Err(format!("invalid line range: {}-{}", start, end));
+                }
+                Ok(Some(format!("-L{},{}:{}", start, end, self.path)))
+            }
+            (None, Some(_)) => Err("startLine is required".into()),
+        }
+    }
+}
+
+/// 文件在某个提交中的版本
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct FileRevision {
+    pub hash: String,
+    pub path: String,
+}
+
+fn commit_fields(fields: &[&str]) -> serde_json::Value {
+    let parents: Vec<&str> = fields[2].split_whitespace().collect();
+    serde_json::json!({
+        "hash": fields[0],
+        "shortHash": fields[1],
+        "parents": parents,
+        "author": fields[3],
+        "email": fields[4],
+        "date": fields[5],
+        "message": fields[6],
+    })
+}
+
+/// 解析 `--follow -z --name-status` 的输出，为每个提交附上当时的路径。
+/// 合并提交没有 name-status 输出，沿用较新一条记录的路径
+fn parse_follow_records(raw: &str, path: &str) -> Vec<serde_json::Value> {
+    let mut current_path = path.to_string();
+    raw.split('\x1e')
+        .filter(|r| !r.trim().is_empty())
+        .filter_map(|record| {
+            let tokens: Vec<&str> = record.split('\0').collect();
+            if tokens.len() < HISTORY_FIELDS {
+                return None;
+            }
+            let mut entry = commit_fields(&tokens[..HISTORY_FIELDS]);
+            let changes: Vec<&str> = tokens[HISTORY_FIELDS..]
+                .iter()
+                .map(|t| t.trim_start_matches('\n'))
+                .filter(|t| !t.is_empty())
+                .collect();
+            let status = changes.first().copied().unwrap_or("");
+            let (old_path, new_path) = match (status.chars().next(), changes.len()) {
+                (Some('R') | Some('C'), 3) => (Some(changes[1]), changes[2]),
+                (_, 2) => (None, changes[1]),
+                _ => (None, current_path.as_str()),
+            };
+            entry["path"] = serde_json::json!(new_path);
+            entry["status"] = serde_json::json!(status.chars().next().map(|c| c.to_string()));
+            entry["oldPath"] = serde_json::json!(old_path);
+            // 更早的提交里文件还叫旧名字
+            current_path = old_path.unwrap_or(new_path).to_string();
+            Some(entry)
+        })
+        .collect()
+}
+
+/// 解析 `git log -L` 的输出，每个提交附上该行范围的补丁
+fn parse_line_records(raw: &str) -> Vec<serde_json::Value> {
+    raw.split('\x1e')
+        .filter(|r| !r.trim().is_empty())
+        .filter_map(|record| {
+            let tokens: Vec<&str> = record.splitn(HISTORY_FIELDS + 1, '\0').collect();
+            if tokens.len() < HISTORY_FIELDS {
+                return None;
+            }
+            let mut entry = commit_fields(&tokens[..HISTORY_FIELDS]);
+            entry["patch"] = serde_json::json!(tokens.get(HISTORY_FIELDS).map(|p| p.trim()).unwrap_or(""));
+            Some(entry)
+        })
+        .collect()
+}
+
+fn file_history(cwd: &str, query: &FileHistoryQuery) -> Result<serde_json::Value, String> {
+    if query.path.is_empty() {
+        return Err("path is required".into());
+    }
+    let page_size = query.page_size();
+    let line_range = query.line_range()?;
+    let Some(cursor) = query.log_cursor(cwd)? else {
+        return Ok(serde_json::json!({ "entries": [], "nextCursor": null }));
+    };
+
+    let mut args: Vec<String> = vec![
+        "log".into(),
+        HISTORY_FORMAT.into(),
+        format!("--skip={}", cursor.offset),
+        format!("--max-count={}", page_size + 1),
+    ];
+    // -L 不能和路径参数同时使用，路径已经写在范围里
+    match &line_range {
+        Some(range) => args.push(range.clone()),
+        None => args.extend(["--follow".into(), "-z".into(), "--name-status".into()]),
+    }
+    args.push("--end-of-options".into());
+    args.extend(cursor.tips.iter().cloned());
+    if line_range.is_none() {
+        args.extend(["--".into(), query.path.clone()]);
+    }
+    let arg_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let raw = git_raw(&arg_refs, cwd)?;
+
+    let mut entries = match line_range {
+        Some(_) => parse_line_records(&raw),
+        None => parse_follow_records(&raw, &query.path),
+    };
+    let has_more = entries.len() > page_size;
+    entries.truncate(page_size);
+    let next_cursor = has_more.then(|| LogCursor { offset: cursor.offset + entries.len(), tips: cursor.tips }.to_cursor());
+    Ok(serde_json::json!({ "entries": entries, "nextCursor": next_cursor }))
+}
+
+pub(super) fn revision_content(backend: &dyn GitBackend, cwd: &str, revision: &FileRevision) -> String {
+    blob_text(backend, cwd, Some(&revision.hash), &revision.path)
+}
+
+#[tauri::command]
+pub async fn git_file_history(
+    state: State<'_, AppState>,
+    query: FileHistoryQuery,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    file_history(&cwd, &query)
+}
+
+/// 比较文件的任意两个历史版本，返回与 git_file_diff 相同的结构
+#[tauri::command]
+pub async fn git_file_revision_diff(
+    state: State<'_, AppState>,
+    from: FileRevision,
+    to: FileRevision,
+) -> Result<serde_json::Value, String> {
+    let root = state.get_root();
+    let cwd = root.to_string_lossy().to_string();
+    let backend = backend::for_root(&root);
+    Ok(serde_json::json!({
+        "oldContent": revision_content(backend, &cwd, &from),
+        "newContent": revision_content(backend, &cwd, &to),
+        "file": to.path,
+        "oldFile": from.path,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo, run_git};
+
+    fn query(path: &str) -> FileHistoryQuery {
+        FileHistoryQuery { path: path.into(), ..Default::default() }
+    }
+
+    #[test]
+    fn follows_renames_and_reports_path_per_revision() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "old name.txt", "a\nb\n", "create");
+        commit_file(&cwd, "other.txt", "x\n", "unrelated");
+        run_git(&["mv", "old name.txt", "new name.txt"], &cwd);
+        run_git(&["commit", "-q", "-m", "rename"], &cwd);
+        commit_file(&cwd, "new name.txt", "a\nb\nc\n", "edit");
+
+        let history = file_history(&cwd, &query("new name.txt")).unwrap();
+        let entries = history["entries"].as_array().unwrap();
+
+        let summary: Vec<(&str, &str)> = entries
+            .iter()
+            .map(|e| (e["message"].as_str().unwrap(), e["path"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("edit", "new name.txt"), ("rename", "new name.txt"), ("create", "old name.txt")]
+        );
+        assert_eq!(entries[1]["oldPath"], "old name.txt");
+        assert_eq!(entries[1]["status"], "R");
+    }
+
+    #[test]
+    fn paginates_with_cursor() {
+        let (_dir, cwd) = init_repo();
+        for i in 0..5 {
+            commit_file(&cwd, "a.txt", &format!("{}\n", i), &format!("c{}", i));
+        }
+        let messages = |page: &serde_json::Value| -> Vec<String> {
+            page["entries"].as_array().unwrap().iter().map(|e| e["message"].as_str().unwrap().to_string()).collect()
+        };
+
+        let mut q = query("a.txt");
+        q.limit = Some(2);
+        let first = file_history(&cwd, &q).unwrap();
+        assert_eq!(messages(&first), vec!["c4", "c3"]);
+
+        // 翻页期间有新提交，后面的页不受影响
+        commit_file(&cwd, "a.txt", "5\n", "c5");
+        q.cursor = first["nextCursor"].as_str().map(String::from);
+        let second = file_history(&cwd, &q).unwrap();
+        assert_eq!(messages(&second), vec!["c2", "c1"]);
+
+        q.cursor = second["nextCursor"].as_str().map(String::from);
+        let last = file_history(&cwd, &q).unwrap();
+        assert_eq!(messages(&last), vec!["c0"]);
+        assert!(last["nextCursor"].is_null());
+    }
+
+    #[test]
+    fn line_range_history_only_lists_commits_touching_the_range() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "lib.rs", "fn one() {\n    1\n}\n\nfn two() {\n    2\n}\n", "add");
+        commit_file(&cwd, "lib.rs", "fn one() {\n    1\n}\n\nfn two() {\n    22\n}\n", "change two");
+        commit_file(&cwd, "lib.rs", "fn one() {\n    11\n}\n\nfn two() {\n    22\n}\n", "change one");
+
+        let mut q = query("lib.rs");
+        q.function = Some("two".into());
+        let history = file_history(&cwd, &q).unwrap();
+        let messages: Vec<&str> = history["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["message"].as_str().unwrap())
+            .collect();
+        assert_eq!(messages, vec!["change two", "add"]);
+        assert!(history["entries"][0]["patch"].as_str().unwrap().contains("+    22"));
+
+        q.function = None;
+        q.start_line = Some(1);
+        q.end_line = Some(3);
+        let history = file_history(&cwd, &q).unwrap();
+        assert_eq!(history["entries"][0]["message"], "change one");
+        assert_eq!(history["entries"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn invalid_line_range_is_rejected() {
+        let mut q = query("a.txt");
+        q.start_line = Some(5);
+        q.end_line = Some(2);
+        assert!(q.line_range().is_err());
+        q.start_line = None;
+        assert!(q.line_range().is_err());
+    }
+}
@@ -0,0 +1,336 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use serde::Deserialize;
+use tauri::State;
+use crate::state::AppState;
+use super::queue::GitQueue;
+use super::{git, git_cmd, git_path, git_raw};
+
+/// 规则写到哪个文件
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IgnoreTarget {
+    /// 仓库根目录的 .gitignore
+    #[default]
+    Root,
+    /// 目标所在目录的 .gitignore
+    Nested,
+    /// .git/info/exclude，只对本地生效、不会提交
+    Exclude,
+}
+
+/// 忽略的对象
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IgnoreKind {
+    #[default]
+    File,
+    Folder,
+    /// 与 path 同扩展名的所有文件
+    Extension,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoreOptions {
+    /// 相对仓库根目录的路径
+    pub path: String,
+    #[serde(default)]
+    pub kind: IgnoreKind,
+    #[serde(default)]
+    pub target: IgnoreTarget,
+    /// 已被跟踪的文件用 `git rm --cached` 停止跟踪（保留工作区文件）
+    #[serde(default)]
+    pub untrack: bool,
+}
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/")
+        .trim_start_matches("./")
+        .trim_matches('/')
+        .to_string()
+}
+
+/// 转义 gitignore 中有特殊含义的字符，使规则按字面匹配
+fn escape_pattern(name: &str) -> String {
+    let mut escaped = String::new();
+    for c in name.chars() {
+        if matches!(c, '*' | '?' | '[' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    if escaped.starts_with('#') || escaped.starts_with('!') {
+        escaped.insert(0, '\\');
+    }
+    // 行尾空格会被 git 忽略，需要转义
+    if escaped.ends_with(' ') {
+        escaped.insert(escaped.len() - 1, '\\');
+    }
+    escaped
+}
+
+/// 规则要写入的目录（相对根目录，根目录为空串）、规则本身，以及对应的 pathspec（用于停止跟踪）
+fn ignore_rule(options: &IgnoreOptions) -> Result<(String, String, String), String> {
+    let path = normalize(&options.path);
+    if path.is_empty() {
+        return Err("path is required".into());
+    }
+    let (parent, name) = match path.rsplit_once('/') {
+        Some((parent, name)) => (parent.to_string(), name.to_string()),
+        None => (String::new(), path.clone()),
+    };
+    let base = if options.target == IgnoreTarget::Nested { parent.clone() } else { String::new() };
+    // 相对规则所在目录的路径
+    let relative = if base.is_empty() { path.clone() } else { name.clone() };
+
+    match options.kind {
+        IgnoreKind::File => Ok((base, format!("/{}", escape_pattern(&relative)), format!(":(literal){}", path))),
+        IgnoreKind::Folder => Ok((base, format!("/{}/", escape_pattern(&relative)), format!(":(literal){}", path))),
+        IgnoreKind::Extension => {
+            let ext = name
+                .rsplit_once('.')
+                .filter(|(stem, ext)| !stem.is_empty() && !ext.is_empty())
+                .map(|(_, ext)| ext.to_string())
+                .ok_or_else(|| format!("{} 没有扩展名", name))?;
+            let pattern = format!("*.{}", escape_pattern(&ext));
+            let spec = if base.is_empty() {
+                format!(":(glob)**/{}", pattern)
+            } else {
+                format!(":(glob){}/**/{}", base, pattern)
+            };
+            Ok((base, pattern, spec))
+        }
+    }
+}
+
+fn ignore_file(cwd: &str, target: IgnoreTarget, base: &str) -> Result<PathBuf, String> {
+    match target {
+        IgnoreTarget::Exclude => git_path(cwd, "info/exclude"),
+        _ => Ok(Path::new(cwd).join(base).join(".gitignore")),
+    }
+}
+
+/// 追加一行规则，已存在时不重复写入
+fn append_rule(file: &Path, pattern: &str) -> Result<bool, String> {
+    let mut content = std::fs::read_to_string(file).unwrap_or_default();
+    if content.lines().any(|l| l.trim_end() == pattern) {
+        return Ok(false);
+    }
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(pattern);
+    content.push('\n');
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(file, content).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+fn tracked_files(cwd: &str, spec: &str) -> Vec<String> {
+    git_raw(&["ls-files", "-z", "--", spec], cwd)
+        .unwrap_or_default()
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn add_ignore(cwd: &str, options: &IgnoreOptions) -> Result<serde_json::Value, String> {
+    let (base, pattern, spec) = ignore_rule(options)?;
+    let file = ignore_file(cwd, options.target, &base)?;
+    let added = append_rule(&file, &pattern)?;
+
+    let mut tracked = tracked_files(cwd, &spec);
+    let mut untracked: Vec<String> = Vec::new();
+    if options.untrack && !tracked.is_empty() {
+        git(&["rm", "-r", "-q", "--cached", "--", &spec], cwd)?;
+        untracked = std::mem::take(&mut tracked);
+    }
+
+    let display = file.strip_prefix(cwd).unwrap_or(&file).to_string_lossy().replace('\\', "/");
+    Ok(serde_json::json!({
+        "ok": true,
+        "file": display,
+        "pattern": pattern,
+        "added": added,
+        // 规则对这些仍被跟踪的文件不起作用
+        "tracked": tracked,
+        "untracked": untracked,
+    }))
+}
+
+/// 解析 `git check-ignore -v -n -z` 的一条记录：来源、行号、规则、路径
+fn parse_check_ignore(raw: &str) -> Option<serde_json::Value> {
+    let fields: Vec<&str> = raw.split('\0').collect();
+    if fields.len() < 4 || fields[0].is_empty() {
+        return None;
+    }
+    let pattern = fields[2];
+    Some(serde_json::json!({
+        "source": fields[0],
+        "line": fields[1].parse::<u64>().ok(),
+        "pattern": pattern,
+        "negated": pattern.starts_with('!'),
+    }))
+}
+
+fn check_ignore(cwd: &str, path: &str) -> Result<serde_json::Value, String> {
+    let path = normalize(path);
+    // --no-index：已跟踪的文件也报告会匹配到的规则；路径经 stdin 传入，避免引号转义
+    let mut child = git_cmd(&["check-ignore", "-v", "-n", "-z", "--no-index", "--stdin"], cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(format!("{}\0", path).as_bytes()).map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    // 退出码 1 表示没有被忽略，不是错误
+    if !matches!(output.status.code(), Some(0) | Some(1)) {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+
+    let rule = parse_check_ignore(&String::from_utf8_lossy(&output.stdout));
+    let tracked = !tracked_files(cwd, &format!(":(literal){}", path)).is_empty();
+    let matched = rule.as_ref().is_some_and(|r| r["negated"] == false);
+    Ok(serde_json::json!({
+        "path": path,
+        // 已跟踪的文件不受忽略规则影响
+        "ignored": matched && !tracked,
+        "tracked": tracked,
+        "rule": rule,
+    }))
+}
+
+/// 把文件、目录或扩展名加入忽略规则，可选地停止跟踪已提交的文件
+#[tauri::command]
+pub async fn git_ignore_add(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    options: IgnoreOptions,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "ignore").await;
+    add_ignore(&cwd, &options)
+}
+
+/// 说明路径是否被忽略，以及是哪个文件的哪条规则忽略（或以 ! 重新包含）了它
+#[tauri::command]
+pub async fn git_check_ignore(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    check_ignore(&cwd, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo};
+    use std::fs;
+
+    fn options(path: &str, kind: IgnoreKind, target: IgnoreTarget) -> IgnoreOptions {
+        IgnoreOptions { path: path.into(), kind, target, untrack: false }
+    }
+
+    #[test]
+    fn writes_rule_to_root_nested_or_exclude_file() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a", "init");
+        fs::create_dir_all(Path::new(&cwd).join("sub/build")).unwrap();
+        fs::write(Path::new(&cwd).join("sub/build/out.o"), "o").unwrap();
+        fs::write(Path::new(&cwd).join("sub/debug.log"), "l").unwrap();
+        fs::write(Path::new(&cwd).join("secret.txt"), "s").unwrap();
+
+        let root = add_ignore(&cwd, &options("secret.txt", IgnoreKind::File, IgnoreTarget::Root)).unwrap();
+        assert_eq!(root["file"], ".gitignore");
+        assert_eq!(root["pattern"], "/secret.txt");
+        assert_eq!(root["added"], true);
+
+        let nested = add_ignore(&cwd, &options("sub/build", IgnoreKind::Folder, IgnoreTarget::Nested)).unwrap();
+        assert_eq!(nested["file"], "sub/.gitignore");
+        assert_eq!(nested["pattern"], "/build/");
+
+        let exclude = add_ignore(&cwd, &options("sub/debug.log", IgnoreKind::Extension, IgnoreTarget::Exclude)).unwrap();
+        assert_eq!(exclude["file"], ".git/info/exclude");
+        assert_eq!(exclude["pattern"], "*.log");
+
+        let again = add_ignore(&cwd, &options("secret.txt", IgnoreKind::File, IgnoreTarget::Root)).unwrap();
+        assert_eq!(again["added"], false);
+        assert_eq!(fs::read_to_string(Path::new(&cwd).join(".gitignore")).unwrap(), "/secret.txt\n");
+
+        let status = git(&["status", "--porcelain", "--untracked-files=all"], &cwd).unwrap();
+        assert!(!status.contains("secret.txt"));
+        assert!(!status.contains("out.o"));
+        assert!(!status.contains("debug.log"));
+    }
+
+    #[test]
+    fn tracked_file_is_reported_and_optionally_untracked() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "logs/app.log", "x", "add log");
+
+        let first = add_ignore(&cwd, &options("logs/app.log", IgnoreKind::Extension, IgnoreTarget::Root)).unwrap();
+        assert_eq!(first["tracked"], serde_json::json!(["logs/app.log"]));
+
+        let mut opts = options("logs/app.log", IgnoreKind::Extension, IgnoreTarget::Root);
+        opts.untrack = true;
+        let second = add_ignore(&cwd, &opts).unwrap();
+        assert_eq!(second["untracked"], serde_json::json!(["logs/app.log"]));
+        assert!(git(&["ls-files"], &cwd).unwrap().is_empty());
+        assert!(Path::new(&cwd).join("logs/app.log").exists());
+    }
+
+    #[test]
+    fn special_characters_are_escaped() {
+        assert_eq!(escape_pattern("#notes"), "\\#notes");
+        assert_eq!(escape_pattern("!important"), "\\!important");
+        assert_eq!(escape_pattern("a[1]*.txt"), "a\\[1]\\*.txt");
+        assert_eq!(escape_pattern("trailing "), "trailing\\ ");
+        let err = ignore_rule(&options("Makefile", IgnoreKind::Extension, IgnoreTarget::Root));
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn check_ignore_explains_matching_rule() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "tracked.log", "t", "init");
+        fs::write(Path::new(&cwd).join(".gitignore"), "*.log\n!keep.log\nbuild/\n").unwrap();
+        fs::create_dir_all(Path::new(&cwd).join("sub")).unwrap();
+        fs::write(Path::new(&cwd).join("sub/.gitignore"), "/only here.txt\n").unwrap();
+
+        let log = check_ignore(&cwd, "debug.log").unwrap();
+        assert_eq!(log["ignored"], true);
+        assert_eq!(log["rule"]["source"], ".gitignore");
+        assert_eq!(log["rule"]["line"], 1);
+        assert_eq!(log["rule"]["pattern"], "*.log");
+
+        let keep = check_ignore(&cwd, "keep.log").unwrap();
+        assert_eq!(keep["ignored"], false);
+        assert_eq!(keep["rule"]["negated"], true);
+        assert_eq!(keep["rule"]["line"], 2);
+
+        let nested = check_ignore(&cwd, "sub/only here.txt").unwrap();
+        assert_eq!(nested["ignored"], true);
+        assert_eq!(nested["rule"]["source"], "sub/.gitignore");
+
+        let inside = check_ignore(&cwd, "build/out.o").unwrap();
+        assert_eq!(inside["rule"]["pattern"], "build/");
+
+        let tracked = check_ignore(&cwd, "tracked.log").unwrap();
+        assert_eq!(tracked["tracked"], true);
+        assert_eq!(tracked["ignored"], false);
+        assert_eq!(tracked["rule"]["pattern"], "*.log");
+
+        let plain = check_ignore(&cwd, "src/main.rs").unwrap();
+        assert_eq!(plain["ignored"], false);
+        assert!(plain["rule"].is_null());
+    }
+}
@@ -0,0 +1,292 @@
+use std::path::{Path, PathBuf};
+use tauri::State;
+use crate::commands::config::read_config;
+use crate::state::AppState;
+use super::queue::GitQueue;
+use super::{git, git_raw};
+
+const POINTER_VERSION: &str = "version https://git-lfs.github.com/spec/v1";
+const TRACK_ATTRIBUTES: &str = "filter=lfs diff=lfs merge=lfs -text";
+
+/// LFS 指针文件中的对象信息
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct LfsPointer {
+    oid: String,
+    size: u64,
+}
+
+/// 解析 LFS 指针文本；普通文件内容返回 None
+pub(super) fn parse_pointer(content: &str) -> Option<LfsPointer> {
+    // 指针文件很小，超过 1KB 的内容不可能是指针
+    if content.len() > 1024 || !content.starts_with(POINTER_VERSION) {
+        return None;
+    }
+    let mut oid = None;
+    let mut size = None;
+    for line in content.lines() {
+        if let Some(value) = line.strip_prefix("oid ") {
+            oid = Some(value.to_string());
+        } else if let Some(value) = line.strip_prefix("size ") {
+            size = value.trim().parse().ok();
+        }
+    }
+    Some(LfsPointer { oid: oid?, size: size? })
+}
+
+fn lfs_installed(cwd: &str) -> bool {
+    git(&["lfs", "version"], cwd).is_ok()
+}
+
+/// 按 .gitattributes 判断路径是否由 LFS 管理
+fn is_lfs_tracked(cwd: &str, path: &str) -> bool {
+    git(&["check-attr", "filter", "--", path], cwd).is_ok_and(|out| out.ends_with(": filter: lfs"))
+}
+
+/// 本地对象库中 LFS 对象的位置：<common-dir>/lfs/objects/aa/bb/<sha256>
+fn object_path(cwd: &str, oid: &str) -> Option<PathBuf> {
+    let hash = oid.strip_prefix("sha256:")?;
+    if hash.len() < 4 {
+        return None;
+    }
+    let common = git(&["rev-parse", "--git-common-dir"], cwd).ok()?;
+    Some(Path::new(cwd).join(common).join("lfs").join("objects").join(&hash[..2]).join(&hash[2..4]).join(hash))
+}
+
+fn pointer_meta(cwd: &str, pointer: &LfsPointer) -> serde_json::Value {
+    let present = object_path(cwd, &pointer.oid).is_some_and(|p| p.is_file());
+    serde_json::json!({ "oid": pointer.oid, "size": pointer.size, "present": present })
+}
+
+fn summary(meta: &serde_json::Value) -> String {
+    if meta.is_null() {
+        return String::new();
+    }
+    let oid = meta["oid"].as_str().unwrap_or("(未计算)");
+    format!("Git LFS object\noid: {}\nsize: {} bytes\n", oid, meta["size"])
+}
+
+/// LFS 文件的差异：返回两侧对象的 oid/大小而不是指针文本；两侧都不是 LFS 时返回 None。
+/// `worktree` 为 true 时新内容来自工作区中已检出的真实文件
+pub(super) fn lfs_diff(cwd: &str, file: &str, old: &str, new: &str, worktree: bool) -> Option<serde_json::Value> {
+    let old_meta = parse_pointer(old).map(|p| pointer_meta(cwd, &p));
+    let new_meta = match parse_pointer(new) {
+        Some(pointer) => Some(pointer_meta(cwd, &pointer)),
+        None if worktree && (old_meta.is_some() || is_lfs_tracked(cwd, file)) => std::fs::metadata(Path::new(cwd).join(file))
+            .ok()
+            .map(|m| serde_json::json!({ "oid": null, "size": m.len(), "present": true })),
+        None => None,
+    };
+    if old_meta.is_none() && new_meta.is_none() {
+        return None;
+    }
+    let old_meta = old_meta.unwrap_or(serde_json::Value::Null);
+    let new_meta = new_meta.unwrap_or(serde_json::Value::Null);
+    Some(serde_json::json!({
+        "file": file,
+        "lfs": { "old": old_meta, "new": new_meta },
+        "oldContent": summary(&old_meta),
+        "newContent": summary(&new_meta),
+    }))
+}
+
+/// .gitattributes 中以 LFS 管理的模式
+fn tracked_patterns(cwd: &str) -> Vec<String> {
+    std::fs::read_to_string(Path::new(cwd).join(".gitattributes"))
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| line.split_whitespace().skip(1).any(|attr| attr == "filter=lfs"))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|pattern| pattern.to_string())
+        .collect()
+}
+
+/// gitattributes 模式中空格需要写成 [[:space:]]，与 `git lfs track` 一致
+fn escape_pattern(pattern: &str) -> String {
+    pattern.replace(' ', "[[:space:]]")
+}
+
+fn track_patterns(cwd: &str, patterns: &[String]) -> Result<Vec<String>, String> {
+    let existing = tracked_patterns(cwd);
+    let added: Vec<String> = patterns
+        .iter()
+        .map(|p| escape_pattern(p))
+        .filter(|p| !existing.contains(p))
+        .fold(Vec::new(), |mut acc, p| {
+            if !acc.contains(&p) {
+                acc.push(p);
+            }
+            acc
+        });
+    if added.is_empty() {
+        return Ok(added);
+    }
+    let path = Path::new(cwd).join(".gitattributes");
+    let mut content = std::fs::read_to_string(&path).unwrap_or_default();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    for pattern in &added {
+        content.push_str(&format!("{} {}\n", pattern, TRACK_ATTRIBUTES));
+    }
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    Ok(added)
+}
+
+fn lfs_files(cwd: &str) -> Result<Vec<serde_json::Value>, String> {
+    let raw = git_raw(&["ls-files", "-z", "--", ":(attr:filter=lfs)"], cwd)?;
+    Ok(raw
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(|path| {
+            let blob = git_raw(&["cat-file", "blob", &format!(":{}", path)], cwd).unwrap_or_default();
+            match parse_pointer(&blob) {
+                Some(pointer) => {
+                    let mut meta = pointer_meta(cwd, &pointer);
+                    meta["path"] = serde_json::json!(path);
+                    meta
+                }
+                // 匹配了 LFS 规则但以普通文件提交（例如添加规则前已提交）
+                None => serde_json::json!({ "path": path, "oid": null, "size": blob.len(), "present": true, "pointer": false }),
+            }
+        })
+        .collect())
+}
+
+/// 待暂存的文件中超过阈值且未走 LFS 的大文件
+pub(super) fn large_files(cwd: &str, paths: &[String], threshold_mb: u64) -> Vec<serde_json::Value> {
+    if threshold_mb == 0 || paths.is_empty() {
+        return Vec::new();
+    }
+    let threshold = threshold_mb * 1024 * 1024;
+    let mut args = vec!["ls-files", "-z", "-m", "-o", "--exclude-standard", "--"];
+    args.extend(paths.iter().map(|p| p.as_str()));
+    let Ok(raw) = git_raw(&args, cwd) else { return Vec::new() };
+    let mut seen = Vec::new();
+    raw.split('\0')
+        .filter(|p| !p.is_empty())
+        .filter_map(|path| {
+            if seen.contains(&path) {
+                return None;
+            }
+            seen.push(path);
+            let size = std::fs::metadata(Path::new(cwd).join(path)).ok()?.len();
+            (size > threshold && !is_lfs_tracked(cwd, path)).then(|| serde_json::json!({ "path": path, "size": size }))
+        })
+        .collect()
+}
+
+/// 暂存前的大文件检查，供 git_stage 使用
+pub(super) fn stage_guard(cwd: &str, root: &Path, paths: &[String]) -> Option<serde_json::Value> {
+    let threshold_mb = read_config(root).git.lfs_threshold_mb;
+    let large = large_files(cwd, paths, threshold_mb);
+    if large.is_empty() {
+        return None;
+    }
+    Some(serde_json::json!({
+        "ok": false,
+        "largeFiles": large,
+        "thresholdMb": threshold_mb,
+        "lfsInstalled": lfs_installed(cwd),
+    }))
+}
+
+#[tauri::command]
+pub async fn git_lfs_status(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let files = lfs_files(&cwd)?;
+    let missing = files.iter().filter(|f| f["present"] == false).count();
+    Ok(serde_json::json!({
+        "installed": lfs_installed(&cwd),
+        "patterns": tracked_patterns(&cwd),
+        "files": files,
+        "missing": missing,
+    }))
+}
+
+/// 等同 `git lfs track`：把模式写入根目录 .gitattributes
+#[tauri::command]
+pub async fn git_lfs_track(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    patterns: Vec<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "lfs-track").await;
+    let added = track_patterns(&cwd, &patterns)?;
+    Ok(serde_json::json!({ "ok": true, "added": added }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo};
+
+    fn pointer(oid: &str, size: u64) -> String {
+        format!("{}\noid sha256:{}\nsize {}\n", POINTER_VERSION, oid, size)
+    }
+
+    #[test]
+    fn parses_pointer_and_rejects_plain_text() {
+        let oid = "a".repeat(64);
+
+        assert_eq!(
+            parse_pointer(&pointer(&oid, 1234)),
+            Some(LfsPointer { oid: format!("sha256:{}", oid), size: 1234 })
+        );
+        assert_eq!(parse_pointer("hello\n"), None);
+    }
+
+    #[test]
+    fn diff_reports_object_metadata_and_local_presence() {
+        let (_dir, cwd) = init_repo();
+        let old_oid = "1".repeat(64);
+        let new_oid = "2".repeat(64);
+        let object = object_path(&cwd, &format!("sha256:{}", new_oid)).unwrap();
+        std::fs::create_dir_all(object.parent().unwrap()).unwrap();
+        std::fs::write(&object, "data").unwrap();
+
+        let diff = lfs_diff(&cwd, "big.bin", &pointer(&old_oid, 10), &pointer(&new_oid, 4), false).unwrap();
+
+        assert_eq!(diff["lfs"]["old"]["size"], 10);
+        assert_eq!(diff["lfs"]["old"]["present"], false);
+        assert_eq!(diff["lfs"]["new"]["oid"], format!("sha256:{}", new_oid));
+        assert_eq!(diff["lfs"]["new"]["present"], true);
+        assert!(lfs_diff(&cwd, "a.txt", "a\n", "b\n", false).is_none());
+    }
+
+    #[test]
+    fn track_appends_patterns_once_and_lists_tracked_files() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, ".gitattributes", "*.txt text\n", "attrs");
+
+        let added = track_patterns(&cwd, &["*.psd".into(), "my file.bin".into(), "*.psd".into()]).unwrap();
+        assert_eq!(added, vec!["*.psd", "my[[:space:]]file.bin"]);
+        assert!(track_patterns(&cwd, &["*.psd".into()]).unwrap().is_empty());
+        assert_eq!(tracked_patterns(&cwd), vec!["*.psd", "my[[:space:]]file.bin"]);
+
+        let oid = "3".repeat(64);
+        commit_file(&cwd, "art.psd", &pointer(&oid, 99), "art");
+        let files = lfs_files(&cwd).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["path"], "art.psd");
+        assert_eq!(files[0]["size"], 99);
+        assert_eq!(files[0]["present"], false);
+    }
+
+    #[test]
+    fn large_untracked_files_are_flagged_unless_routed_through_lfs() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "init");
+        let big = vec![0u8; 1024 * 1024 + 1];
+        std::fs::write(Path::new(&cwd).join("big.bin"), &big).unwrap();
+        std::fs::write(Path::new(&cwd).join("small.bin"), b"x").unwrap();
+
+        let large = large_files(&cwd, &[".".into()], 1);
+        assert_eq!(large.len(), 1);
+        assert_eq!(large[0]["path"], "big.bin");
+        assert!(large_files(&cwd, &[".".into()], 0).is_empty());
+
+        track_patterns(&cwd, &["*.bin".into()]).unwrap();
+        assert!(large_files(&cwd, &[".".into()], 1).is_empty());
+    }
+}
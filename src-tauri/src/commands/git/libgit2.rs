@@ -0,0 +1,663 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use git2::{
+    BranchType, Commit, DiffFindOptions, FileMode, Oid, Patch, Repository, Sort, Status, StatusOptions,
+    SubmoduleIgnore, SubmoduleStatus, Time,
+};
+use super::backend::{CliBackend, GitBackend};
+use super::log::{LogCursor, LogQuery};
+use super::show::{select_parent, ChangedFile};
+use super::status::{conflict_type, file_entry, status_name};
+
+/// 进程内实现遇到的错误。Unsupported 表示这次查询超出了它能与命令行保持一致的范围，
+/// 交给 CliBackend 处理
+enum LibError {
+    Unsupported,
+    Git(String),
+}
+
+impl From<git2::Error> for LibError {
+    fn from(e: git2::Error) -> Self {
+        LibError::Git(e.message().to_string())
+    }
+}
+
+type LibResult<T> = Result<T, LibError>;
+
+/// 基于 libgit2 的读实现，省去每次读取启动 git 进程的开销
+pub(crate) struct Libgit2Backend;
+
+fn open(cwd: &str) -> LibResult<Repository> {
+    // 打不开仓库时交给命令行，由它给出用户熟悉的报错
+    Repository::discover(cwd).map_err(|_| LibError::Unsupported)
+}
+
+/// 执行进程内实现，Unsupported 时改用命令行
+fn run<T>(lib: impl FnOnce() -> LibResult<T>, cli: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    match lib() {
+        Ok(value) => Ok(value),
+        Err(LibError::Git(e)) => Err(e),
+        Err(LibError::Unsupported) => cli(),
+    }
+}
+
+/// `%ci` 格式：2024-01-02 03:04:05 +0800
+fn format_time(time: Time, format: &str) -> String {
+    chrono::FixedOffset::east_opt(time.offset_minutes() * 60)
+        .and_then(|offset| chrono::DateTime::from_timestamp(time.seconds(), 0).map(|t| t.with_timezone(&offset)))
+        .map(|t| t.format(format).to_string())
+        .unwrap_or_default()
+}
+
+fn commit_date(commit: &Commit) -> String {
+    format_time(commit.committer().when(), "%Y-%m-%d %H:%M:%S %z")
+}
+
+fn short_hash(commit: &Commit) -> LibResult<String> {
+    Ok(commit.as_object().short_id()?.as_str().unwrap_or_default().to_string())
+}
+
+/// refs/remotes/origin/main → origin/main，refs/heads/main → main
+fn short_ref(name: &str) -> &str {
+    name.strip_prefix("refs/remotes/")
+        .or_else(|| name.strip_prefix("refs/heads/"))
+        .unwrap_or(name)
+}
+
+/// HEAD 指向的分支名，未出生的分支也算；分离 HEAD 时为 None
+fn head_branch(repo: &Repository) -> Option<String> {
+    let head = repo.find_reference("HEAD").ok()?;
+    head.symbolic_target()?.strip_prefix("refs/heads/").map(String::from)
+}
+
+fn head_oid(repo: &Repository) -> Option<Oid> {
+    repo.head().ok().and_then(|h| h.target())
+}
+
+// ---- status ----
+
+fn status_codes(status: Status) -> (char, char) {
+    let index = if status.contains(Status::INDEX_NEW) {
+        'A'
+    } else if status.contains(Status::INDEX_DELETED) {
+        'D'
+    } else if status.contains(Status::INDEX_RENAMED) {
+        'R'
+    } else if status.contains(Status::INDEX_TYPECHANGE) {
+        'T'
+    } else if status.contains(Status::INDEX_MODIFIED) {
+        'M'
+    } else {
+        '.'
+    };
+    let worktree = if status.contains(Status::WT_DELETED) {
+        'D'
+    } else if status.contains(Status::WT_TYPECHANGE) {
+        'T'
+    } else if status.contains(Status::WT_MODIFIED) {
+        'M'
+    } else {
+        '.'
+    };
+    (index, worktree)
+}
+
+/// 冲突条目按三个阶段是否存在还原成 porcelain 的 XY
+fn conflict_xy(ancestor: bool, ours: bool, theirs: bool) -> &'static str {
+    match (ancestor, ours, theirs) {
+        (true, false, false) => "DD",
+        (false, true, false) => "AU",
+        (true, true, false) => "UD",
+        (false, false, true) => "UA",
+        (true, false, true) => "DU",
+        (false, true, true) => "AA",
+        _ => "UU",
+    }
+}
+
+fn conflicts(repo: &Repository) -> LibResult<Vec<(String, &'static str)>> {
+    let index = repo.index()?;
+    let mut list = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        let path = [&conflict.ancestor, &conflict.our, &conflict.their]
+            .iter()
+            .find_map(|e| e.as_ref().map(|e| String::from_utf8_lossy(&e.path).to_string()))
+            .unwrap_or_default();
+        let xy = conflict_xy(conflict.ancestor.is_some(), conflict.our.is_some(), conflict.their.is_some());
+        list.push((path, xy));
+    }
+    Ok(list)
+}
+
+/// 子模块的 `<sub>` 字段，与 porcelain v2 的 "S<c><m><u>" 相同
+fn submodule_field(repo: &Repository, path: &str) -> String {
+    let Ok(status) = repo.submodule_status(path, SubmoduleIgnore::Unspecified) else {
+        return "S...".into();
+    };
+    let flag = |set: bool, c: char| if set { c } else { '.' };
+    format!(
+        "S{}{}{}",
+        flag(status.intersects(SubmoduleStatus::INDEX_MODIFIED | SubmoduleStatus::WD_MODIFIED), 'C'),
+        flag(status.intersects(SubmoduleStatus::WD_INDEX_MODIFIED | SubmoduleStatus::WD_WD_MODIFIED), 'M'),
+        flag(status.contains(SubmoduleStatus::WD_UNTRACKED), 'U'),
+    )
+}
+
+fn branch_header(repo: &Repository) -> LibResult<serde_json::Value> {
+    let detached = repo.head_detached().unwrap_or(false);
+    let oid = head_oid(repo);
+    let head = if detached { None } else { head_branch(repo) };
+
+    let mut upstream: Option<String> = None;
+    let (mut ahead, mut behind) = (0, 0);
+    if let Some(name) = &head {
+        let refname = format!("refs/heads/{}", name);
+        if let Ok(buf) = repo.branch_upstream_name(&refname) {
+            let upstream_ref = buf.as_str().unwrap_or_default().to_string();
+            let upstream_oid = repo.refname_to_id(&upstream_ref).ok();
+            if let (Some(local), Some(remote)) = (oid, upstream_oid) {
+                (ahead, behind) = repo.graph_ahead_behind(local, remote)?;
+            }
+            upstream = Some(short_ref(&upstream_ref).to_string());
+        }
+    }
+    let stash_count = repo.reflog("refs/stash").map(|r| r.len()).unwrap_or(0);
+
+    Ok(serde_json::json!({
+        "oid": oid.map(|o| o.to_string()),
+        "head": head,
+        "upstream": upstream,
+        "ahead": ahead,
+        "behind": behind,
+        "detached": detached,
+        "stashCount": stash_count,
+    }))
+}
+
+fn read_status(repo: &Repository) -> LibResult<serde_json::Value> {
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .renames_head_to_index(true)
+        .exclude_submodules(false);
+    let statuses = repo.statuses(Some(&mut options))?;
+
+    let conflicted = conflicts(repo)?;
+    let mut tracked: Vec<(String, serde_json::Value)> = Vec::new();
+    let mut untracked: Vec<serde_json::Value> = Vec::new();
+
+    for entry in statuses.iter() {
+        let status = entry.status();
+        if status.contains(Status::CONFLICTED) || status.contains(Status::IGNORED) {
+            continue;
+        }
+        if status.contains(Status::WT_NEW) && !status.intersects(Status::INDEX_NEW | Status::INDEX_MODIFIED) {
+            let path = entry.path().unwrap_or_default();
+            untracked.push(file_entry(path, None, "..", "N...", "untracked", false));
+            continue;
+        }
+
+        // entry.path() 在重命名时给出的是旧路径，新路径要从 diff 里取
+        let staged = entry.head_to_index();
+        let unstaged = entry.index_to_workdir();
+        let new_path = staged
+            .as_ref()
+            .or(unstaged.as_ref())
+            .and_then(|d| d.new_file().path())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|| entry.path().unwrap_or_default().to_string());
+        let orig_path = staged
+            .as_ref()
+            .filter(|_| status.contains(Status::INDEX_RENAMED))
+            .and_then(|d| d.old_file().path())
+            .map(|p| p.to_string_lossy().to_string());
+        let is_submodule = [&staged, &unstaged]
+            .iter()
+            .filter_map(|d| d.as_ref())
+            .any(|d| d.new_file().mode() == FileMode::Commit || d.old_file().mode() == FileMode::Commit);
+        let sub = if is_submodule { submodule_field(repo, &new_path) } else { "N...".into() };
+
+        let (x, y) = status_codes(status);
+        let xy: String = [x, y].iter().collect();
+        let mut files = Vec::new();
+        if let Some(name) = status_name(x) {
+            files.push(file_entry(&new_path, orig_path.as_deref(), &xy, &sub, name, true));
+        }
+        if let Some(name) = status_name(y) {
+            files.push(file_entry(&new_path, orig_path.as_deref(), &xy, &sub, name, false));
+        }
+        tracked.extend(files.into_iter().map(|f| (new_path.clone(), f)));
+    }
+
+    for (path, xy) in conflicted {
+        let mut entry = file_entry(&path, None, xy, "N...", "conflicted", false);
+        entry["conflict"] = conflict_type(xy).into();
+        tracked.push((path, entry));
+    }
+    // 与 git status 一致：已跟踪的改动按路径排序，未跟踪文件排在最后
+    tracked.sort_by(|a, b| a.0.cmp(&b.0));
+    let files: Vec<serde_json::Value> = tracked.into_iter().map(|(_, f)| f).chain(untracked).collect();
+
+    Ok(serde_json::json!({ "branch": branch_header(repo)?, "files": files }))
+}
+
+// ---- log ----
+
+/// 只支持普通文本和 '.' 通配的模式，其余正则语法交给 git
+fn literal_pattern(pattern: &str) -> LibResult<&str> {
+    if pattern.chars().any(|c| "\\^$*+?()[]{}|".contains(c)) {
+        return Err(LibError::Unsupported);
+    }
+    Ok(pattern)
+}
+
+/// 子串匹配，模式中的 '.' 匹配任意字符
+fn matches(haystack: &str, pattern: &str, ignore_case: bool) -> bool {
+    let fold = |s: &str| -> Vec<char> {
+        if ignore_case { s.to_lowercase().chars().collect() } else { s.chars().collect() }
+    };
+    let hay = fold(haystack);
+    let pat = fold(pattern);
+    if pat.is_empty() {
+        return true;
+    }
+    hay.windows(pat.len())
+        .any(|w| w.iter().zip(&pat).all(|(h, p)| *p == '.' || h == p))
+}
+
+/// 提交的树中 paths 下的内容，与某个父提交相同时视为未改动（TREESAME）
+fn same_paths(a: &git2::Tree, b: Option<&git2::Tree>, paths: &[&str]) -> LibResult<bool> {
+    for path in paths {
+        let path = Path::new(path.trim_end_matches('/'));
+        let left = a.get_path(path).ok().map(|e| e.id());
+        let right = b.and_then(|t| t.get_path(path).ok()).map(|e| e.id());
+        if left != right {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// 按路径过滤时提交是否显示。合并提交与部分父提交相同时，git 默认的历史简化会剪掉另一侧，
+/// 这里不模拟剪枝，交给命令行
+fn touches_paths(commit: &Commit, paths: &[&str]) -> LibResult<bool> {
+    let tree = commit.tree()?;
+    if commit.parent_count() == 0 {
+        return Ok(!same_paths(&tree, None, paths)?);
+    }
+    let mut same = Vec::new();
+    for parent in commit.parents() {
+        same.push(same_paths(&tree, Some(&parent.tree()?), paths)?);
+    }
+    if same.len() > 1 && same.iter().any(|s| *s) && !same.iter().all(|s| *s) {
+        return Err(LibError::Unsupported);
+    }
+    Ok(!same.iter().any(|s| *s))
+}
+
+/// 每个提交上的引用，格式与 `%D` 相同：HEAD 在前，其余按引用名倒序
+fn decorations(repo: &Repository) -> LibResult<HashMap<Oid, Vec<String>>> {
+    let mut refs: Vec<(String, Oid)> = Vec::new();
+    for reference in repo.references()? {
+        let reference = reference?;
+        let Some(name) = reference.name().map(String::from) else { continue };
+        if let Ok(commit) = reference.peel_to_commit() {
+            refs.push((name, commit.id()));
+        }
+    }
+    refs.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let current = if repo.head_detached().unwrap_or(false) { None } else { head_branch(repo) };
+    let mut map: HashMap<Oid, Vec<String>> = HashMap::new();
+    if let Some(oid) = head_oid(repo) {
+        let label = match &current {
+            Some(branch) => format!("HEAD -> {}", branch),
+            None => "HEAD".into(),
+        };
+        map.entry(oid).or_default().push(label);
+    }
+    for (name, oid) in refs {
+        let label = if let Some(branch) = name.strip_prefix("refs/heads/") {
+            if current.as_deref() == Some(branch) {
+                continue;
+            }
+            branch.to_string()
+        } else if let Some(tag) = name.strip_prefix("refs/tags/") {
+            format!("tag: {}", tag)
+        } else if let Some(remote) = name.strip_prefix("refs/remotes/") {
+            remote.to_string()
+        } else {
+            name
+        };
+        map.entry(oid).or_default().push(label);
+    }
+    Ok(map)
+}
+
+fn log_entry(commit: &Commit, refs: &HashMap<Oid, Vec<String>>) -> LibResult<serde_json::Value> {
+    let parents: Vec<String> = commit.parent_ids().map(|p| p.to_string()).collect();
+    let author = commit.author();
+    Ok(serde_json::json!({
+        "hash": commit.id().to_string(),
+        "shortHash": short_hash(commit)?,
+        "parents": parents,
+        "author": author.name().unwrap_or_default(),
+        "email": author.email().unwrap_or_default(),
+        "date": commit_date(commit),
+        "refs": refs.get(&commit.id()).map(|r| r.join(", ")).unwrap_or_default(),
+        "message": commit.summary().unwrap_or_default(),
+    }))
+}
+
+/// 与命令行版本相同：把引用解析成提交哈希，未指定时取所有引用和 HEAD
+fn resolve_tips(repo: &Repository, refs: &[String]) -> LibResult<Vec<String>> {
+    let refs: Vec<&String> = refs.iter().filter(|r| !r.is_empty()).collect();
+    let mut oids: Vec<Oid> = Vec::new();
+    if refs.is_empty() {
+        for reference in repo.references()? {
+            if let Ok(commit) = reference?.peel_to_commit() {
+                oids.push(commit.id());
+            }
+        }
+        // 空仓库时 HEAD 无法解析，列表为空
+        oids.extend(head_oid(repo));
+    } else {
+        for r in refs {
+            if r.contains("..") {
+                return Err(LibError::Unsupported);
+            }
+            oids.push(repo.revparse_single(r)?.peel_to_commit()?.id());
+        }
+    }
+    let mut seen = HashSet::new();
+    Ok(oids.into_iter().filter(|oid| seen.insert(*oid)).map(|oid| oid.to_string()).collect())
+}
+
+fn read_log(repo: &Repository, query: &LogQuery) -> LibResult<(Vec<serde_json::Value>, Option<String>)> {
+    let non_empty = |s: &Option<String>| s.as_deref().filter(|s| !s.is_empty()).map(String::from);
+    // --since/--until 接受 git 的各种相对日期写法，交给命令行解析
+    if non_empty(&query.since).is_some() || non_empty(&query.until).is_some() {
+        return Err(LibError::Unsupported);
+    }
+    let paths: Vec<&str> = query.paths.iter().map(|p| p.as_str()).filter(|p| !p.is_empty()).collect();
+    if paths.iter().any(|p| p.contains(['*', '?', '[']) || p.starts_with(':')) {
+        return Err(LibError::Unsupported);
+    }
+    let author = non_empty(&query.author);
+    let grep = non_empty(&query.grep);
+    if let Some(a) = &author {
+        literal_pattern(a)?;
+    }
+    if let Some(g) = &grep {
+        literal_pattern(g)?;
+    }
+    let page_size = query.page_size();
+    let cursor = match query.log_cursor().map_err(LibError::Git)? {
+        Some(cursor) => cursor,
+        None => LogCursor { tips: resolve_tips(repo, &query.refs)?, offset: 0 },
+    };
+    let offset = cursor.offset;
+
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    for tip in &cursor.tips {
+        let (hide, hash) = match tip.strip_prefix('^') {
+            Some(hash) => (true, hash),
+            None => (false, tip.as_str()),
+        };
+        let oid = repo.find_object(Oid::from_str(hash)?, None)?.peel_to_commit()?.id();
+        if hide {
+            walk.hide(oid)?;
+        } else {
+            walk.push(oid)?;
+        }
+    }
+
+    let decorations = decorations(repo)?;
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        if let Some(a) = &author {
+            let who = commit.author();
+            let ident = format!("{} <{}>", who.name().unwrap_or_default(), who.email().unwrap_or_default());
+            // --regexp-ignore-case 同样作用于 --author
+            if !matches(&ident, a, grep.is_some()) {
+                continue;
+            }
+        }
+        if let Some(g) = &grep {
+            if !matches(commit.message().unwrap_or_default(), g, true) {
+                continue;
+            }
+        }
+        if !paths.is_empty() && !touches_paths(&commit, &paths)? {
+            continue;
+        }
+        if skipped < offset {
+            skipped += 1;
+            continue;
+        }
+        entries.push(log_entry(&commit, &decorations)?);
+        // 多取一条用来判断是否还有下一页
+        if entries.len() > page_size {
+            break;
+        }
+    }
+
+    let has_more = entries.len() > page_size;
+    entries.truncate(page_size);
+    let next_cursor = has_more.then(|| LogCursor { offset: offset + entries.len(), tips: cursor.tips }.to_cursor());
+    Ok((entries, next_cursor))
+}
+
+// ---- show ----
+
+fn changed_files(repo: &Repository, parent: Option<&Commit>, commit: &Commit) -> LibResult<Vec<ChangedFile>> {
+    let old_tree = parent.map(|p| p.tree()).transpose()?;
+    let new_tree = commit.tree()?;
+    let mut diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+    let mut files = Vec::new();
+    for (i, delta) in diff.deltas().enumerate() {
+        let status = match delta.status() {
+            git2::Delta::Added => "A",
+            git2::Delta::Deleted => "D",
+            git2::Delta::Renamed => "R",
+            git2::Delta::Copied => "C",
+            git2::Delta::Typechange => "T",
+            _ => "M",
+        };
+        let path_of = |f: git2::DiffFile| f.path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+        let path = path_of(delta.new_file());
+        let old_path = path_of(delta.old_file());
+        let patch = Patch::from_diff(&diff, i)?;
+        let binary = patch.as_ref().map_or(delta.flags().is_binary(), |p| p.delta().flags().is_binary());
+        let (additions, deletions) = match patch.filter(|_| !binary) {
+            Some(patch) => {
+                let (_, additions, deletions) = patch.line_stats()?;
+                (Some(additions as u64), Some(deletions as u64))
+            }
+            None => (None, None),
+        };
+        files.push(ChangedFile { status: status.into(), path, old_path, additions, deletions, binary });
+    }
+    Ok(files)
+}
+
+fn read_show(repo: &Repository, hash: &str, parent: Option<usize>) -> LibResult<serde_json::Value> {
+    let commit = repo.revparse_single(hash)?.peel_to_commit()?;
+    let full_hash = commit.id().to_string();
+    let parents: Vec<Commit> = commit.parents().collect();
+    let selected = select_parent(&full_hash, &parents, parent).map_err(LibError::Git)?;
+    let files: Vec<serde_json::Value> = changed_files(repo, selected.as_ref(), &commit)?
+        .iter()
+        .map(ChangedFile::to_json)
+        .collect();
+    let parent_ids: Vec<String> = parents.iter().map(|p| p.id().to_string()).collect();
+    let author = commit.author();
+
+    Ok(serde_json::json!({
+        "hash": full_hash, "shortHash": short_hash(&commit)?,
+        "subject": commit.summary().unwrap_or_default(), "author": author.name().unwrap_or_default(),
+        "email": author.email().unwrap_or_default(), "date": commit_date(&commit),
+        "body": commit.body().unwrap_or_default().trim(),
+        "parents": parent_ids,
+        "parent": selected.map(|p| p.id().to_string()),
+        "files": files,
+    }))
+}
+
+// ---- blob ----
+
+fn read_blob(repo: &Repository, rev: Option<&str>, path: &str) -> LibResult<Vec<u8>> {
+    let id = match rev {
+        None => {
+            let index = repo.index()?;
+            let entry = index
+                .get_path(Path::new(path), 0)
+                .ok_or_else(|| LibError::Git(format!("path '{}' is not in the index", path)))?;
+            entry.id
+        }
+        Some(rev) => {
+            let tree = repo.revparse_single(rev)?.peel_to_tree()?;
+            tree.get_path(Path::new(path))?.id()
+        }
+    };
+    Ok(repo.find_blob(id)?.content().to_vec())
+}
+
+// ---- branches ----
+
+fn read_branches(repo: &Repository) -> LibResult<serde_json::Value> {
+    let head = head_oid(repo);
+    let detached = repo.head_detached().unwrap_or(false);
+    let mut current = if detached { String::new() } else { head_branch(repo).unwrap_or_default() };
+
+    let mut branches = Vec::new();
+    for branch in repo.branches(None)? {
+        let (branch, kind) = branch?;
+        let Some(ref_name) = branch.get().name().map(String::from) else { continue };
+        branches.push((ref_name, branch, kind));
+    }
+    branches.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut names: Vec<String> = Vec::new();
+    let mut local: Vec<serde_json::Value> = Vec::new();
+    let mut remote: Vec<serde_json::Value> = Vec::new();
+    for (ref_name, branch, kind) in branches {
+        let name = short_ref(&ref_name).to_string();
+        let Ok(commit) = branch.get().peel_to_commit() else { continue };
+        let tip = commit.id();
+        let merged = head.is_some_and(|h| h == tip || repo.graph_descendant_of(h, tip).unwrap_or(false));
+        let date = format_time(commit.committer().when(), "%Y-%m-%dT%H:%M:%S%:z");
+        let subject = commit.summary().unwrap_or_default().to_string();
+
+        if kind == BranchType::Remote {
+            // origin/HEAD 只是指向默认分支的符号引用
+            if ref_name.ends_with("/HEAD") {
+                continue;
+            }
+            let remote_name = name.split('/').next().unwrap_or("").to_string();
+            remote.push(serde_json::json!({
+                "name": name,
+                "refName": ref_name,
+                "remote": remote_name,
+                "hash": tip.to_string(),
+                "lastCommitDate": date,
+                "subject": subject,
+                "merged": merged,
+            }));
+            continue;
+        }
+
+        let (mut ahead, mut behind, mut gone) = (0, 0, false);
+        let mut upstream: Option<String> = None;
+        if let Ok(buf) = repo.branch_upstream_name(&ref_name) {
+            let upstream_ref = buf.as_str().unwrap_or_default().to_string();
+            match repo.refname_to_id(&upstream_ref) {
+                Ok(target) => (ahead, behind) = repo.graph_ahead_behind(tip, target)?,
+                Err(_) => gone = true,
+            }
+            upstream = Some(short_ref(&upstream_ref).to_string());
+        }
+        let is_head = branch.is_head();
+        if is_head {
+            current = name.clone();
+        }
+        names.push(name.clone());
+        local.push(serde_json::json!({
+            "name": name,
+            "refName": ref_name,
+            "hash": tip.to_string(),
+            "upstream": upstream,
+            "ahead": ahead,
+            "behind": behind,
+            "upstreamGone": gone,
+            "lastCommitDate": date,
+            "subject": subject,
+            "merged": merged,
+            "current": is_head,
+        }));
+    }
+
+    Ok(serde_json::json!({
+        "current": current,
+        "branches": names,
+        "local": local,
+        "remote": remote,
+    }))
+}
+
+impl GitBackend for Libgit2Backend {
+    fn status(&self, cwd: &str) -> Result<serde_json::Value, String> {
+        run(|| read_status(&open(cwd)?), || CliBackend.status(cwd))
+    }
+
+    fn log(&self, cwd: &str, query: &LogQuery) -> Result<(Vec<serde_json::Value>, Option<String>), String> {
+        run(|| read_log(&open(cwd)?, query), || CliBackend.log(cwd, query))
+    }
+
+    fn show(&self, cwd: &str, hash: &str, parent: Option<usize>) -> Result<serde_json::Value, String> {
+        run(|| read_show(&open(cwd)?, hash, parent), || CliBackend.show(cwd, hash, parent))
+    }
+
+    fn blob(&self, cwd: &str, rev: Option<&str>, path: &str) -> Result<Vec<u8>, String> {
+        run(|| read_blob(&open(cwd)?, rev, path), || CliBackend.blob(cwd, rev, path))
+    }
+
+    fn branches(&self, cwd: &str) -> Result<serde_json::Value, String> {
+        run(|| read_branches(&open(cwd)?), || CliBackend.branches(cwd))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_in_pattern_matches_any_character() {
+        assert!(matches("Fix: a.b", "fix", true));
+        assert!(!matches("Fix: a.b", "fix", false));
+        assert!(matches("alice <a@x.io>", "a@x.io", false));
+        assert!(matches("alice <a@xyio>", "a@x.io", false));
+    }
+
+    #[test]
+    fn regex_syntax_falls_back_to_cli() {
+        assert!(literal_pattern("fix|feat").is_err());
+        assert!(literal_pattern("^Merge").is_err());
+        assert!(literal_pattern("a.b").is_ok());
+    }
+
+    #[test]
+    fn conflict_stages_map_to_porcelain_codes() {
+        assert_eq!(conflict_xy(true, true, true), "UU");
+        assert_eq!(conflict_xy(false, true, true), "AA");
+        assert_eq!(conflict_xy(true, true, false), "UD");
+        assert_eq!(conflict_xy(true, false, true), "DU");
+    }
+}
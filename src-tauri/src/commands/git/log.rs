@@ -0,0 +1,345 @@
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use crate::state::AppState;
+use super::backend::{self, GitBackend};
+use super::{check_rev, git_raw, git_stdin};
+
+const DEFAULT_PAGE_SIZE: usize = 30;
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// 每条提交的字段，用 NUL 分隔，配合 `-z` 让提交之间也用 NUL 分隔
+const LOG_FORMAT: &str = "--format=%H%x00%h%x00%P%x00%an%x00%ae%x00%ci%x00%D%x00%s";
+const LOG_FIELDS: usize = 8;
+
+/// git_log 的分页与过滤参数，全部可选
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQuery {
+    /// 上一页返回的 nextCursor
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub paths: Vec<String>,
+    pub author: Option<String>,
+    /// 按提交信息过滤（git log --grep，忽略大小写）
+    pub grep: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// 要遍历的分支/引用，为空时等价于 --all
+    #[serde(default)]
+    pub refs: Vec<String>,
+}
+
+impl LogQuery {
+    pub(super) fn page_size(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// 上一页返回的游标，第一页为 None
+    pub(super) fn log_cursor(&self) -> Result<Option<LogCursor>, String> {
+        self.cursor.as_deref().filter(|c| !c.is_empty()).map(LogCursor::from_cursor).transpose()
+    }
+}
+
+/// 分页游标：第一页解析出的起点提交（排除的提交写成 ^hash）和已读取的条数。
+/// 之后的页都从这组固定的提交开始遍历，期间有新提交或 fetch 移动了引用也不会让分页错位
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(super) struct LogCursor {
+    pub(super) tips: Vec<String>,
+    pub(super) offset: usize,
+}
+
+impl LogCursor {
+    pub(super) fn from_cursor(cursor: &str) -> Result<Self, String> {
+        let bytes = general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| format!("invalid cursor: {}", cursor))?;
+        serde_json::from_slice(&bytes).map_err(|_| format!("invalid cursor: {}", cursor))
+    }
+
+    pub(super) fn to_cursor(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        general_purpose::URL_SAFE_NO_PAD.encode(json)
+    }
+}
+
+/// 把要遍历的引用解析成提交哈希；未指定引用时取所有引用和 HEAD（等价于 --all）
+fn resolve_tips(cwd: &str, refs: &[String]) -> Result<Vec<String>, String> {
+    let refs: Vec<&str> = refs.iter().map(|r| r.as_str()).filter(|r| !r.is_empty()).collect();
+    let raw = if refs.is_empty() {
+        // --no-walk 只列出各引用指向的提交，指向树的标签会被跳过
+        git_raw(&["log", "--all", "--no-walk=unsorted", "--format=%H"], cwd)?
+    } else {
+        // rev-parse 不支持 --end-of-options，选项形式的参数直接拒绝
+        for r in &refs {
+            check_rev(r)?;
+        }
+        let mut args = vec!["rev-parse"];
+        args.extend(&refs);
+        args.push("--");
+        // 附注标签解析出来是标签对象，再剥到提交，游标里只放提交哈希
+        let peeled: Vec<String> = git_raw(&args, cwd)?
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && *l != "--")
+            .map(|l| format!("{}^{{commit}}", l))
+            .collect();
+        let mut args = vec!["rev-parse"];
+        args.extend(peeled.iter().map(|l| l.as_str()));
+        git_raw(&args, cwd)?
+    };
+    Ok(raw.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect())
+}
+
+/// 解析 LOG_FORMAT + `-z` 的输出
+pub(super) fn parse_log_records(raw: &str) -> Vec<serde_json::Value> {
+    let fields: Vec<&str> = raw.trim_end_matches('\0').split('\0').collect();
+    fields
+        .chunks(LOG_FIELDS)
+        .filter(|c| c.len() == LOG_FIELDS && !c[0].trim().is_empty())
+        .map(|c| {
+            let parents: Vec<&str> = c[2].split_whitespace().collect();
+            serde_json::json!({
+                "hash": c[0].trim(),
+                "shortHash": c[1],
+                "parents": parents,
+                "author": c[3],
+                "email": c[4],
+                "date": c[5],
+                "refs": c[6],
+                "message": c[7],
+            })
+        })
+        .collect()
+}
+
+/// 读取一页提交记录，返回 (记录, 下一页游标)
+pub(super) fn log_page(cwd: &str, query: &LogQuery) -> Result<(Vec<serde_json::Value>, Option<String>), String> {
+    let page_size = query.page_size();
+    let cursor = match query.log_cursor()? {
+        Some(cursor) => cursor,
+        None => LogCursor { tips: resolve_tips(cwd, &query.refs)?, offset: 0 },
+    };
+    // 空仓库没有任何提交
+    if cursor.tips.is_empty() {
+        return Ok((Vec::new(), None));
+    }
+
+    let mut args: Vec<String> = vec![
+        "log".into(),
+        "-z".into(),
+        "--date-order".into(),
+        LOG_FORMAT.into(),
+        format!("--skip={}", cursor.offset),
+        // 多取一条用来判断是否还有下一页
+        format!("--max-count={}", page_size + 1),
+    ];
+    if let Some(author) = query.author.as_deref().filter(|s| !s.is_empty()) {
+        args.push(format!("--author={}", author));
+    }
+    if let Some(grep) = query.grep.as_deref().filter(|s| !s.is_empty()) {
+        args.push(format!("--grep={}", grep));
+        args.push("--regexp-ignore-case".into());
+    }
+    if let Some(since) = query.since.as_deref().filter(|s| !s.is_empty()) {
+        args.push(format!("--since={}", since));
+    }
+    if let Some(until) = query.until.as_deref().filter(|s| !s.is_empty()) {
+        args.push(format!("--until={}", until));
+    }
+    // 未指定引用时起点是仓库里的全部引用，可能有上千个，经 stdin 传入以免超出命令行长度
+    args.push("--stdin".into());
+    args.push("--".into());
+    args.extend(query.paths.iter().filter(|p| !p.is_empty()).cloned());
+    let mut input = String::new();
+    for tip in &cursor.tips {
+        // 游标同样来自前端；stdin 里的 `--` 会被当成路径的开始
+        check_rev(tip)?;
+        input.push_str(tip);
+        input.push('\n');
+    }
+
+    let arg_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let raw = git_stdin(&arg_refs, &input, cwd)?;
+
+    let mut entries = parse_log_records(&raw);
+    let has_more = entries.len() > page_size;
+    entries.truncate(page_size);
+    let next_cursor = has_more.then(|| {
+        LogCursor { offset: cursor.offset + entries.len(), tips: cursor.tips }.to_cursor()
+    });
+    Ok((entries, next_cursor))
+}
+
+pub(super) fn read_log(backend: &dyn GitBackend, cwd: &str, query: &LogQuery) -> Result<serde_json::Value, String> {
+    let (entries, next_cursor) = backend.log(cwd, query)?;
+    Ok(serde_json::json!({ "entries": entries, "nextCursor": next_cursor }))
+}
+
+#[tauri::command]
+pub async fn git_log(
+    state: State<'_, AppState>,
+    query: Option<LogQuery>,
+) -> Result<serde_json::Value, String> {
+    let root = state.get_root();
+    let cwd = root.to_string_lossy().to_string();
+    read_log(backend::for_root(&root), &cwd, &query.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::git;
+    use super::super::tests::{backends, commit_file, init_repo, run_git};
+
+    fn messages(page: &serde_json::Value) -> Vec<String> {
+        page["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["message"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn pages_through_history_with_cursor() {
+        let (_dir, cwd) = init_repo();
+        for i in 0..5 {
+            commit_file(&cwd, "a.txt", &i.to_string(), &format!("c{}", i));
+        }
+
+        for backend in backends() {
+            let mut query = LogQuery { limit: Some(2), ..Default::default() };
+            let first = read_log(backend, &cwd, &query).unwrap();
+            assert_eq!(messages(&first), vec!["c4", "c3"]);
+
+            query.cursor = first["nextCursor"].as_str().map(String::from);
+            let second = read_log(backend, &cwd, &query).unwrap();
+            assert_eq!(messages(&second), vec!["c2", "c1"]);
+
+            query.cursor = second["nextCursor"].as_str().map(String::from);
+            let last = read_log(backend, &cwd, &query).unwrap();
+            assert_eq!(messages(&last), vec!["c0"]);
+            assert!(last["nextCursor"].is_null());
+        }
+    }
+
+    #[test]
+    fn new_commits_do_not_shift_later_pages() {
+        let (_dir, cwd) = init_repo();
+        for i in 0..4 {
+            commit_file(&cwd, "a.txt", &i.to_string(), &format!("c{}", i));
+        }
+
+        for backend in backends() {
+            let mut query = LogQuery { limit: Some(2), ..Default::default() };
+            let first = read_log(backend, &cwd, &query).unwrap();
+            assert_eq!(messages(&first), vec!["c3", "c2"]);
+
+            // 翻页之间分支前进（新提交、fetch 都会这样）
+            run_git(&["commit", "--allow-empty", "-m", "meanwhile"], &cwd);
+            query.cursor = first["nextCursor"].as_str().map(String::from);
+            let second = read_log(backend, &cwd, &query).unwrap();
+            assert_eq!(messages(&second), vec!["c1", "c0"]);
+            run_git(&["reset", "-q", "--hard", "HEAD~1"], &cwd);
+        }
+    }
+
+    #[test]
+    fn subjects_with_separators_are_kept_intact() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "1", "fix: a | b | c");
+
+        for backend in backends() {
+            let page = read_log(backend, &cwd, &LogQuery::default()).unwrap();
+            assert_eq!(messages(&page), vec!["fix: a | b | c"]);
+        }
+    }
+
+    #[test]
+    fn includes_parent_hashes() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "1", "first");
+        let first = git(&["rev-parse", "HEAD"], &cwd).unwrap();
+        commit_file(&cwd, "a.txt", "2", "second");
+
+        for backend in backends() {
+            let page = read_log(backend, &cwd, &LogQuery::default()).unwrap();
+            assert_eq!(page["entries"][0]["parents"][0], first);
+            assert_eq!(page["entries"][1]["parents"].as_array().unwrap().len(), 0);
+        }
+    }
+
+    #[test]
+    fn entries_match_between_backends() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "1", "first\n\nbody text");
+        run_git(&["tag", "-a", "v1", "-m", "release"], &cwd);
+        run_git(&["switch", "-c", "side"], &cwd);
+        commit_file(&cwd, "b.txt", "1", "side");
+        run_git(&["switch", "-"], &cwd);
+
+        let [cli, lib] = backends();
+        let expected = read_log(cli, &cwd, &LogQuery::default()).unwrap();
+        assert_eq!(read_log(lib, &cwd, &LogQuery::default()).unwrap(), expected);
+    }
+
+    #[test]
+    fn filters_by_path_author_grep_and_ref() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "1", "feat: add a");
+        commit_file(&cwd, "b.txt", "1", "fix: touch b");
+        run_git(&["switch", "-c", "side"], &cwd);
+        run_git(&["-c", "user.name=Other", "commit", "--allow-empty", "-m", "side work"], &cwd);
+        run_git(&["switch", "-"], &cwd);
+
+        for backend in backends() {
+            let by_path = read_log(backend, &cwd, &LogQuery { paths: vec!["a.txt".into()], ..Default::default() }).unwrap();
+            assert_eq!(messages(&by_path), vec!["feat: add a"]);
+
+            let by_author = read_log(backend, &cwd, &LogQuery { author: Some("Other".into()), ..Default::default() }).unwrap();
+            assert_eq!(messages(&by_author), vec!["side work"]);
+
+            let by_grep = read_log(backend, &cwd, &LogQuery { grep: Some("FIX".into()), ..Default::default() }).unwrap();
+            assert_eq!(messages(&by_grep), vec!["fix: touch b"]);
+
+            let head_only = read_log(backend, &cwd, &LogQuery { refs: vec!["HEAD".into()], ..Default::default() }).unwrap();
+            assert!(!messages(&head_only).contains(&"side work".to_string()));
+        }
+    }
+
+    #[test]
+    fn refs_are_never_parsed_as_options() {
+        let (dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "1", "init");
+        let target = dir.path().join("written.txt");
+
+        for backend in backends() {
+            let query = LogQuery { refs: vec![format!("--output={}", target.display())], ..Default::default() };
+            assert!(read_log(backend, &cwd, &query).is_err());
+            assert!(!target.exists());
+        }
+    }
+
+    #[test]
+    fn forged_cursor_tips_are_rejected() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "1", "init");
+
+        let cursor = LogCursor { tips: vec!["--".into(), "a.txt".into()], offset: 0 }.to_cursor();
+        let query = LogQuery { cursor: Some(cursor), ..Default::default() };
+        assert!(read_log(backends()[0], &cwd, &query).is_err());
+    }
+
+    #[test]
+    fn empty_repository_returns_empty_page() {
+        let (_dir, cwd) = init_repo();
+
+        for backend in backends() {
+            let page = read_log(backend, &cwd, &LogQuery::default()).unwrap();
+            assert!(page["entries"].as_array().unwrap().is_empty());
+            assert!(page["nextCursor"].is_null());
+        }
+    }
+}
@@ -0,0 +1,450 @@
+use std::process::Command;
+use std::path::Path;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+use tauri::State;
+use crate::state::AppState;
+use queue::GitQueue;
+
+pub mod askpass;
+pub mod backend;
+pub mod branch;
+pub mod cherry_pick;
+pub mod commit;
+pub mod compare;
+pub mod conflict;
+pub mod diff;
+pub mod graph;
+pub mod history;
+pub mod ignore;
+pub mod lfs;
+pub mod libgit2;
+pub mod log;
+pub mod queue;
+pub mod rebase;
+pub mod remote;
+pub mod reset;
+pub mod show;
+pub mod stash;
+pub mod status;
+pub mod submodule;
+pub mod sync;
+pub mod tag;
+pub mod task;
+pub mod worktree;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+fn git_cmd(args: &[&str], cwd: &str) -> Command {
+    let mut cmd = Command::new("git");
+    // status、diff 等读命令默认会顺手抢 index.lock 刷新索引，和队列里的写操作、终端里的 git 撞锁
+    cmd.args(args).current_dir(cwd).env("GIT_OPTIONAL_LOCKS", "0");
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+fn git(args: &[&str], cwd: &str) -> Result<String, String> {
+    git_raw(args, cwd).map(|out| out.trim().to_string())
+}
+
+/// 执行 git 并返回未裁剪的 stdout；其他 git 进程占着锁文件时会稍等重试
+fn git_raw(args: &[&str], cwd: &str) -> Result<String, String> {
+    let output = queue::retry_on_lock(|| git_cmd(args, cwd).output())?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        return Err(queue::explain_lock_error(cwd, stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 执行 git 并把 input 写入 stdin，返回未裁剪的 stdout；用于数量不定、可能超出命令行长度的参数
+fn git_stdin(args: &[&str], input: &str, cwd: &str) -> Result<String, String> {
+    let mut child = git_cmd(args, cwd)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    // git 读完 stdin 才开始输出，先整体写入再收集输出不会互相阻塞
+    if let Some(mut stdin) = child.stdin.take() {
+        std::io::Write::write_all(&mut stdin, input.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 解析 `.git` 内部文件的真实路径（兼容 worktree 和子模块）
+fn git_path(cwd: &str, name: &str) -> Result<std::path::PathBuf, String> {
+    let path = git(&["rev-parse", "--git-path", name], cwd)?;
+    Ok(Path::new(cwd).join(path))
+}
+
+/// 检查来自前端的分支名、标签名或提交：它们会原样进入 git 的参数列表，以 `-` 开头会被当成选项
+pub(super) fn check_rev(rev: &str) -> Result<&str, String> {
+    if rev.starts_with('-') {
+        return Err(format!("invalid ref: {}", rev));
+    }
+    Ok(rev)
+}
+
+/// 列出当前索引中处于未合并状态的文件及其冲突类型
+fn conflicted_files(cwd: &str) -> Vec<serde_json::Value> {
+    let status = status::read_status(cwd).unwrap_or_default();
+    status["files"]
+        .as_array()
+        .map(|files| {
+            files
+                .iter()
+                .filter(|f| f["status"] == "conflicted")
+                .map(|f| serde_json::json!({ "path": f["path"], "conflict": f["conflict"] }))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 执行可能产生冲突的命令（merge/apply/cherry-pick 等）。
+/// 失败且留下未合并文件时返回结构化结果，而不是把 stderr 当作错误抛出。
+fn git_conflicting(args: &[&str], cwd: &str) -> Result<serde_json::Value, String> {
+    run_conflicting(git_cmd(args, cwd), cwd)
+}
+
+fn run_conflicting(mut cmd: Command, cwd: &str) -> Result<serde_json::Value, String> {
+    let output = queue::retry_on_lock(|| cmd.output())?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+
+    if output.status.success() {
+        return Ok(serde_json::json!({ "ok": true, "conflicts": [], "result": stdout }));
+    }
+
+    let conflicts = conflicted_files(cwd);
+    if conflicts.is_empty() {
+        return Err(if stderr.is_empty() { stdout } else { queue::explain_lock_error(cwd, stderr) });
+    }
+    let message = [stdout, stderr]
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(serde_json::json!({
+        "ok": false,
+        "conflicts": conflicts,
+        "operation": conflict::in_progress_operation(cwd),
+        "message": message,
+    }))
+}
+
+fn path_exists(cwd: &str, path: &str) -> bool {
+    Path::new(cwd).join(path).exists()
+}
+
+fn discard_git_paths(cwd: &str, paths: &[String]) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let mut tracked_paths: Vec<&str> = Vec::new();
+    let mut untracked_paths: Vec<&str> = Vec::new();
+
+    for path in paths {
+        if git_cmd(&["ls-files", "--error-unmatch", "--", path], cwd)
+            .output()
+            .map_err(|e| e.to_string())?
+            .status
+            .success()
+        {
+            tracked_paths.push(path.as_str());
+        } else {
+            untracked_paths.push(path.as_str());
+        }
+    }
+
+    if !tracked_paths.is_empty() {
+        let mut args = vec!["restore", "--staged", "--worktree", "--source=HEAD", "--"];
+        args.extend(tracked_paths.iter().copied());
+        git(&args, cwd)?;
+    }
+
+    if !untracked_paths.is_empty() {
+        let mut clean_args = vec!["clean", "-fd", "--"];
+        clean_args.extend(untracked_paths.iter().copied());
+        git(&clean_args, cwd)?;
+    }
+
+    Ok(())
+}
+
+fn discard_all_git_changes(cwd: &str) -> Result<(), String> {
+    git(&["restore", "--staged", "--worktree", "."], cwd)?;
+    git(&["clean", "-fd"], cwd)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn git_stage(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    paths: Vec<String>,
+    force: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let root = state.get_root();
+    let cwd = root.to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "stage").await;
+    // 大文件先交给前端确认是否改走 LFS，force 时直接暂存
+    if !force.unwrap_or(false) {
+        if let Some(blocked) = lfs::stage_guard(&cwd, &root, &paths) {
+            return Ok(blocked);
+        }
+    }
+    let mut args = vec!["add"];
+    let path_refs: Vec<&str> = paths.iter().map(|s| s.as_str()).collect();
+    args.extend(path_refs);
+    git(&args, &cwd)?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+#[tauri::command]
+pub async fn git_unstage(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    paths: Vec<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "unstage").await;
+    let mut args = vec!["reset", "HEAD"];
+    let path_refs: Vec<&str> = paths.iter().map(|s| s.as_str()).collect();
+    args.extend(path_refs);
+    git(&args, &cwd)?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+#[tauri::command]
+pub async fn git_discard_paths(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    paths: Vec<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "discard").await;
+    discard_git_paths(&cwd, &paths)?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+#[tauri::command]
+pub async fn git_discard_all(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "discard").await;
+    discard_all_git_changes(&cwd)?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+/// parent 选择合并提交比较的父提交（从 1 开始），默认第一个父提交
+#[tauri::command]
+pub async fn git_show(
+    state: State<'_, AppState>,
+    hash: String,
+    parent: Option<usize>,
+) -> Result<serde_json::Value, String> {
+    let root = state.get_root();
+    let cwd = root.to_string_lossy().to_string();
+    backend::for_root(&root).show(&cwd, &hash, parent)
+}
+
+#[tauri::command]
+pub async fn git_file_diff(
+    state: State<'_, AppState>,
+    hash: String,
+    file: String,
+    parent: Option<usize>,
+) -> Result<serde_json::Value, String> {
+    let root = state.get_root();
+    let cwd = root.to_string_lossy().to_string();
+    show::file_diff(backend::for_root(&root), &cwd, &hash, &file, parent)
+}
+
+#[tauri::command]
+pub async fn git_staged_diff(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let stat = git(&["diff", "--cached", "--stat"], &cwd).unwrap_or_default();
+
+    let diff_raw = git_raw(&["diff", "--cached"], &cwd).unwrap_or_default();
+    let max_len = 30 * 1024;
+    let diff = if diff_raw.len() > max_len {
+        format!("{}\n... [diff truncated]", &diff_raw[..max_len])
+    } else {
+        diff_raw
+    };
+
+    let status_out = git(&["diff", "--cached", "--name-status"], &cwd).unwrap_or_default();
+    let files: Vec<serde_json::Value> = status_out
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            let parts: Vec<&str> = line.splitn(2, '\t').collect();
+            let status = parts.first().unwrap_or(&"").to_string();
+            let path = parts.get(1).unwrap_or(&"").to_string();
+            serde_json::json!({ "status": status, "path": path })
+        })
+        .collect();
+
+    Ok(serde_json::json!({ "stat": stat, "diff": diff, "files": files }))
+}
+
+#[tauri::command]
+pub async fn git_sync_status(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    Ok(sync::sync_status(&cwd))
+}
+
+/// 工作区或暂存区相对上一层的文件内容
+fn working_diff(backend: &dyn backend::GitBackend, cwd: &str, file: &str, is_staged: bool) -> serde_json::Value {
+    let index_link = submodule::gitlink_at(cwd, None, file);
+    let head_link = submodule::gitlink_at(cwd, Some("HEAD"), file);
+    if index_link.is_some() || head_link.is_some() {
+        let (old, new) = if is_staged {
+            (head_link, index_link)
+        } else {
+            (index_link.or(head_link), submodule::checked_out_commit(cwd, file))
+        };
+        return submodule::submodule_diff(cwd, file, old.as_deref(), new.as_deref());
+    }
+
+    let (old_content, new_content) = if is_staged {
+        let old = backend::blob_text(backend, cwd, Some("HEAD"), file);
+        let new = backend::blob_text(backend, cwd, None, file);
+        (old, new)
+    } else {
+        let old = backend
+            .blob(cwd, None, file)
+            .or_else(|_| backend.blob(cwd, Some("HEAD"), file))
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default();
+        let full_path = std::path::Path::new(cwd).join(file);
+        let new = std::fs::read(&full_path)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default();
+        (old, new)
+    };
+
+    if let Some(diff) = lfs::lfs_diff(cwd, file, &old_content, &new_content, !is_staged) {
+        return diff;
+    }
+
+    serde_json::json!({
+        "oldContent": old_content,
+        "newContent": new_content,
+        "file": file,
+    })
+}
+
+#[tauri::command]
+pub async fn git_working_diff(
+    state: State<'_, AppState>,
+    file: String,
+    staged: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let root = state.get_root();
+    let cwd = root.to_string_lossy().to_string();
+    Ok(working_diff(backend::for_root(&root), &cwd, &file, staged.unwrap_or(false)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    pub(super) fn run_git(args: &[&str], cwd: &str) {
+        git(args, cwd).unwrap();
+    }
+
+    pub(super) fn commit_file(cwd: &str, name: &str, content: &str, message: &str) {
+        let path = Path::new(cwd).join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(path, content).unwrap();
+        run_git(&["add", "--", name], cwd);
+        run_git(&["commit", "-m", message], cwd);
+    }
+
+    /// 读操作的测试在两种实现上各跑一遍，结果应当一致
+    pub(super) fn backends() -> [&'static dyn backend::GitBackend; 2] {
+        [&backend::CliBackend, &libgit2::Libgit2Backend]
+    }
+
+    pub(super) fn init_repo() -> (tempfile::TempDir, String) {
+        let dir = tempdir().unwrap();
+        let cwd = dir.path().to_string_lossy().to_string();
+        run_git(&["init"], &cwd);
+        run_git(&["config", "user.email", "test@example.com"], &cwd);
+        run_git(&["config", "user.name", "Tester"], &cwd);
+        (dir, cwd)
+    }
+
+    #[test]
+    fn discard_tracked_and_staged_file_restores_head_content() {
+        let (_dir, cwd) = init_repo();
+        fs::write(Path::new(&cwd).join("a.txt"), "v1").unwrap();
+        run_git(&["add", "a.txt"], &cwd);
+        run_git(&["commit", "-m", "init"], &cwd);
+
+        fs::write(Path::new(&cwd).join("a.txt"), "v2").unwrap();
+        run_git(&["add", "a.txt"], &cwd);
+
+        discard_git_paths(&cwd, &["a.txt".to_string()]).unwrap();
+
+        let content = fs::read_to_string(Path::new(&cwd).join("a.txt")).unwrap();
+        assert_eq!(content, "v1");
+
+        let status = git_raw(&["status", "--porcelain=v1"], &cwd).unwrap();
+        assert!(status.trim().is_empty());
+    }
+
+    #[test]
+    fn discard_untracked_file_and_dir_removes_them() {
+        let (_dir, cwd) = init_repo();
+        fs::write(Path::new(&cwd).join("tracked.txt"), "base").unwrap();
+        run_git(&["add", "tracked.txt"], &cwd);
+        run_git(&["commit", "-m", "init"], &cwd);
+
+        fs::write(Path::new(&cwd).join("new.txt"), "temp").unwrap();
+        fs::create_dir_all(Path::new(&cwd).join("temp-dir")).unwrap();
+        fs::write(Path::new(&cwd).join("temp-dir").join("x.txt"), "temp").unwrap();
+
+        discard_git_paths(&cwd, &["new.txt".to_string(), "temp-dir".to_string()]).unwrap();
+
+        assert!(!path_exists(&cwd, "new.txt"));
+        assert!(!path_exists(&cwd, "temp-dir"));
+    }
+
+    #[test]
+    fn discard_all_clears_tracked_and_untracked_changes() {
+        let (_dir, cwd) = init_repo();
+        fs::write(Path::new(&cwd).join("a.txt"), "v1").unwrap();
+        run_git(&["add", "a.txt"], &cwd);
+        run_git(&["commit", "-m", "init"], &cwd);
+
+        fs::write(Path::new(&cwd).join("a.txt"), "v2").unwrap();
+        run_git(&["add", "a.txt"], &cwd);
+        fs::write(Path::new(&cwd).join("b.txt"), "temp").unwrap();
+
+        discard_all_git_changes(&cwd).unwrap();
+
+        let content = fs::read_to_string(Path::new(&cwd).join("a.txt")).unwrap();
+        assert_eq!(content, "v1");
+        assert!(!path_exists(&cwd, "b.txt"));
+
+        let status = git_raw(&["status", "--porcelain=v1"], &cwd).unwrap();
+        assert!(status.trim().is_empty());
+    }
+}
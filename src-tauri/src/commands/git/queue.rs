@@ -0,0 +1,385 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::process::Output;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::Notify;
+use crate::state::AppState;
+use super::git_path;
+
+/// 其他 git 进程持有锁时的重试次数，等待时间从 LOCK_RETRY_DELAY 开始逐次翻倍
+const LOCK_RETRIES: u32 = 5;
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(100);
+/// index.lock 超过该时间没有更新，多半是 git 异常退出后的残留
+const STALE_LOCK_AGE: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedOperation {
+    pub id: u64,
+    /// 操作名，如 stage、commit、checkout
+    pub name: String,
+    /// 入队时间（Unix 毫秒）
+    pub queued_at: u64,
+    /// 开始执行的时间，排队中为 None
+    pub started_at: Option<u64>,
+}
+
+#[derive(Default)]
+struct RepoQueue {
+    running: Option<QueuedOperation>,
+    waiting: VecDeque<QueuedOperation>,
+}
+
+/// 按仓库串行执行会写索引、HEAD 或引用的 git 操作，避免前端并发的命令互相撞上 index.lock。
+/// 只读命令和只写远程跟踪分支的推送、获取不进队列；排队是异步等待，不占 tokio 工作线程
+pub struct GitQueue {
+    repos: Mutex<HashMap<String, RepoQueue>>,
+    changed: Notify,
+    next_id: AtomicU64,
+    app: Mutex<Option<AppHandle>>,
+}
+
+/// 持有期间独占该仓库的队列，释放时让给下一个操作
+pub(super) struct QueueGuard<'a> {
+    queue: &'a GitQueue,
+    cwd: String,
+}
+
+impl Drop for QueueGuard<'_> {
+    fn drop(&mut self) {
+        self.queue.finish(&self.cwd);
+    }
+}
+
+/// begin 排队期间的登记；future 在轮到之前被丢弃（请求取消、窗口关闭）时把自己从等待队列里移除，
+/// 否则它会一直占着队首，后面的操作全部卡住
+struct WaitingEntry<'a> {
+    queue: &'a GitQueue,
+    cwd: &'a str,
+    id: u64,
+    started: bool,
+}
+
+impl Drop for WaitingEntry<'_> {
+    fn drop(&mut self) {
+        if self.started {
+            return;
+        }
+        let mut repos = self.queue.repos.lock().unwrap();
+        if let Some(repo) = repos.get_mut(self.cwd) {
+            repo.waiting.retain(|op| op.id != self.id);
+            if repo.running.is_none() && repo.waiting.is_empty() {
+                repos.remove(self.cwd);
+            }
+        }
+        self.queue.emit(self.cwd, &repos);
+        // 队首可能换成了别的操作，让它们重新检查
+        self.queue.changed.notify_waiters();
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl GitQueue {
+    pub fn new() -> Self {
+        Self {
+            repos: Mutex::new(HashMap::new()),
+            changed: Notify::new(),
+            next_id: AtomicU64::new(1),
+            app: Mutex::new(None),
+        }
+    }
+
+    /// 记下 AppHandle，之后队列变化时发送 `git-queue-changed` 事件
+    pub fn attach(&self, app: AppHandle) {
+        *self.app.lock().unwrap() = Some(app);
+    }
+
+    /// 排队直到轮到该操作（先进先出）
+    pub(super) async fn begin(&self, cwd: &str, name: &str) -> QueueGuard<'_> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        {
+            let mut repos = self.repos.lock().unwrap();
+            repos.entry(cwd.to_string()).or_default().waiting.push_back(QueuedOperation {
+                id,
+                name: name.to_string(),
+                queued_at: now_millis(),
+                started_at: None,
+            });
+            self.emit(cwd, &repos);
+        }
+        let mut entry = WaitingEntry { queue: self, cwd, id, started: false };
+
+        loop {
+            // 先登记等待再检查，避免检查之后、等待之前的 finish 通知丢失
+            let notified = self.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            {
+                let mut repos = self.repos.lock().unwrap();
+                let repo = repos.entry(cwd.to_string()).or_default();
+                if repo.running.is_none() && repo.waiting.front().is_some_and(|op| op.id == id) {
+                    let mut op = repo.waiting.pop_front().unwrap();
+                    op.started_at = Some(now_millis());
+                    repo.running = Some(op);
+                    self.emit(cwd, &repos);
+                    entry.started = true;
+                    break;
+                }
+            }
+            notified.await;
+        }
+        QueueGuard { queue: self, cwd: cwd.to_string() }
+    }
+
+    /// 仓库当前是否有操作在执行或排队
+    pub(super) fn is_busy(&self, cwd: &str) -> bool {
+        self.repos.lock().unwrap().contains_key(cwd)
+    }
+
+    fn finish(&self, cwd: &str) {
+        let mut repos = self.repos.lock().unwrap();
+        if let Some(repo) = repos.get_mut(cwd) {
+            repo.running = None;
+            if repo.waiting.is_empty() {
+                repos.remove(cwd);
+            }
+        }
+        self.emit(cwd, &repos);
+        self.changed.notify_waiters();
+    }
+
+    fn emit(&self, cwd: &str, repos: &HashMap<String, RepoQueue>) {
+        if let Some(app) = self.app.lock().unwrap().as_ref() {
+            let _ = app.emit("git-queue-changed", state_json(cwd, repos.get(cwd)));
+        }
+    }
+
+    pub(super) fn snapshot(&self, cwd: &str) -> serde_json::Value {
+        state_json(cwd, self.repos.lock().unwrap().get(cwd))
+    }
+}
+
+impl Default for GitQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn state_json(cwd: &str, repo: Option<&RepoQueue>) -> serde_json::Value {
+    serde_json::json!({
+        "cwd": cwd,
+        "running": repo.and_then(|r| r.running.clone()),
+        "waiting": repo.map(|r| r.waiting.iter().cloned().collect::<Vec<_>>()).unwrap_or_default(),
+    })
+}
+
+/// 另一个 git 进程正持有锁文件时的报错
+pub(super) fn is_lock_error(stderr: &str) -> bool {
+    stderr.contains(".lock': File exists") || stderr.contains("Another git process seems to be running")
+}
+
+/// 运行 git 命令，遇到锁冲突时等待后重试；最后一次的结果原样返回
+pub(super) fn retry_on_lock(mut run: impl FnMut() -> std::io::Result<Output>) -> Result<Output, String> {
+    let mut delay = LOCK_RETRY_DELAY;
+    for _ in 0..LOCK_RETRIES {
+        let output = run().map_err(|e| e.to_string())?;
+        if output.status.success() || !is_lock_error(&String::from_utf8_lossy(&output.stderr)) {
+            return Ok(output);
+        }
+        std::thread::sleep(delay);
+        delay *= 2;
+    }
+    run().map_err(|e| e.to_string())
+}
+
+/// 长时间未更新的 index.lock 及其存在时长
+fn stale_lock(cwd: &str) -> Option<(PathBuf, Duration)> {
+    let path = git_path(cwd, "index.lock").ok()?;
+    let age = path.metadata().ok()?.modified().ok()?.elapsed().ok()?;
+    (age >= STALE_LOCK_AGE).then_some((path, age))
+}
+
+/// 重试后仍被锁住时，若锁文件已是残留则在报错里说明
+pub(super) fn explain_lock_error(cwd: &str, stderr: String) -> String {
+    if !is_lock_error(&stderr) {
+        return stderr;
+    }
+    match stale_lock(cwd) {
+        Some((path, age)) => format!(
+            "{}\n{} 已存在 {} 秒未更新，可能是 git 异常退出后残留的锁文件；确认没有其他 git 进程后可以删除",
+            stderr.trim_end(),
+            path.display(),
+            age.as_secs()
+        ),
+        None => stderr,
+    }
+}
+
+/// 取消或超时杀掉 git 之后 index.lock 仍在时的说明；锁可能属于其他 git 进程，交给用户确认后再删除
+pub(super) fn leftover_lock(cwd: &str) -> Option<String> {
+    let path = git_path(cwd, "index.lock").ok().filter(|p| p.exists())?;
+    Some(format!(
+        "git 已被终止，但 {} 仍然存在，可能是 git 来不及清理的锁文件；确认没有其他 git 进程后可以删除",
+        path.display()
+    ))
+}
+
+fn stale_lock_json(cwd: &str) -> serde_json::Value {
+    match stale_lock(cwd) {
+        Some((path, age)) => serde_json::json!({ "path": path, "ageSecs": age.as_secs() }),
+        None => serde_json::Value::Null,
+    }
+}
+
+/// 当前仓库正在执行和排队中的操作，以及残留的 index.lock（如果有）
+#[tauri::command]
+pub async fn git_queue_state(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let mut snapshot = queue.snapshot(&cwd);
+    snapshot["staleLock"] = stale_lock_json(&cwd);
+    Ok(snapshot)
+}
+
+/// 删除残留的 index.lock；锁文件仍在更新（可能有 git 进程在用）时不删除
+#[tauri::command]
+pub async fn git_remove_stale_lock(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "remove-lock").await;
+    let Some((path, _)) = stale_lock(&cwd) else {
+        return Ok(serde_json::json!({ "ok": true, "removed": false }));
+    };
+    std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+    Ok(serde_json::json!({ "ok": true, "removed": true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::git;
+    use super::super::tests::{commit_file, init_repo};
+    use std::sync::Arc;
+
+    // 默认的单线程运行时：排队若阻塞线程，后面的任务和断言都跑不到
+    #[tokio::test]
+    async fn operations_on_one_repo_run_one_at_a_time_in_order() {
+        let queue = Arc::new(GitQueue::new());
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let first = queue.begin("/repo", "first").await;
+        let mut handles = Vec::new();
+        for i in 0..3 {
+            let (worker_queue, log) = (queue.clone(), log.clone());
+            handles.push(tokio::spawn(async move {
+                let _op = worker_queue.begin("/repo", &format!("op{}", i)).await;
+                log.lock().unwrap().push(format!("start {}", i));
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                log.lock().unwrap().push(format!("end {}", i));
+            }));
+            // 等它进入等待队列，保证入队顺序
+            while queue.snapshot("/repo")["waiting"].as_array().unwrap().len() < i + 1 {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        let state = queue.snapshot("/repo");
+        assert_eq!(state["running"]["name"], "first");
+        assert_eq!(state["waiting"].as_array().unwrap().len(), 3);
+        assert!(queue.is_busy("/repo"));
+        assert!(log.lock().unwrap().is_empty());
+
+        drop(first);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["start 0", "end 0", "start 1", "end 1", "start 2", "end 2"]
+        );
+        assert!(queue.snapshot("/repo")["running"].is_null());
+        assert!(!queue.is_busy("/repo"));
+    }
+
+    #[tokio::test]
+    async fn dropped_waiter_leaves_the_queue() {
+        let queue = Arc::new(GitQueue::new());
+        let first = queue.begin("/repo", "first").await;
+        let abandoned = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                let _op = queue.begin("/repo", "abandoned").await;
+            })
+        };
+        while queue.snapshot("/repo")["waiting"].as_array().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        abandoned.abort();
+        assert!(abandoned.await.unwrap_err().is_cancelled());
+        assert!(queue.snapshot("/repo")["waiting"].as_array().unwrap().is_empty());
+
+        drop(first);
+        assert!(!queue.is_busy("/repo"));
+        let next = tokio::time::timeout(Duration::from_secs(5), queue.begin("/repo", "next")).await;
+        assert!(next.is_ok());
+    }
+
+    #[tokio::test]
+    async fn different_repositories_do_not_wait_for_each_other() {
+        let queue = GitQueue::new();
+        let _a = queue.begin("/a", "commit").await;
+        let _b = queue.begin("/b", "commit").await;
+        assert_eq!(queue.snapshot("/b")["running"]["name"], "commit");
+    }
+
+    #[test]
+    fn retries_until_lock_is_released() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "1", "init");
+        std::fs::write(std::path::Path::new(&cwd).join("a.txt"), "2").unwrap();
+        let lock = git_path(&cwd, "index.lock").unwrap();
+        std::fs::write(&lock, "").unwrap();
+
+        let release = {
+            let lock = lock.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(150));
+                std::fs::remove_file(lock).unwrap();
+            })
+        };
+        let output = retry_on_lock(|| super::super::git_cmd(&["add", "a.txt"], &cwd).output()).unwrap();
+        release.join().unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(git(&["diff", "--cached", "--name-only"], &cwd).unwrap(), "a.txt");
+    }
+
+    #[test]
+    fn old_lock_file_is_reported_as_stale() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "1", "init");
+        let lock = git_path(&cwd, "index.lock").unwrap();
+        std::fs::write(&lock, "").unwrap();
+        assert!(stale_lock(&cwd).is_none());
+
+        let old = SystemTime::now() - STALE_LOCK_AGE - Duration::from_secs(1);
+        std::fs::File::options().write(true).open(&lock).unwrap().set_modified(old).unwrap();
+
+        let message = explain_lock_error(&cwd, format!("fatal: Unable to create '{}': File exists.", lock.display()));
+        assert!(message.contains("残留"));
+        assert_eq!(stale_lock(&cwd).unwrap().0, lock);
+    }
+}
@@ -0,0 +1,328 @@
+use serde::Deserialize;
+use tauri::State;
+use crate::state::AppState;
+use super::conflict::{abort_operation, continue_operation, in_progress_operation};
+use super::log::parse_log_records;
+use super::queue::GitQueue;
+use super::{check_rev, git, git_cmd, git_path, git_raw, run_conflicting};
+
+/// 存放本次 rebase 用到的 todo 和提交信息文件，位于 .git 目录内
+const PLAN_DIR: &str = "openloom-rebase";
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanAction {
+    Pick,
+    Reword,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+/// 编辑后的 rebase 计划中的一步，顺序即最终的提交顺序
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlanStep {
+    pub action: PlanAction,
+    pub hash: String,
+    /// reword 的新信息；squash 时作为合并后的信息
+    pub message: Option<String>,
+}
+
+/// 给 sh 使用的单引号转义（GIT_*_EDITOR 由 git 通过 sh 执行）
+fn sh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "/").replace('\'', r"'\''"))
+}
+
+/// 把前端传来的 base 解析成提交哈希，之后只把哈希交给 git，避免 `--root` 之类被当成选项
+fn resolve_base(cwd: &str, base: &str) -> Result<String, String> {
+    git(&["rev-parse", "--verify", &format!("{}^{{commit}}", check_rev(base)?)], cwd)
+}
+
+fn todo_list(cwd: &str, base: &str) -> Result<Vec<serde_json::Value>, String> {
+    let range = format!("{}..HEAD", resolve_base(cwd, base)?);
+    let raw = git_raw(
+        &[
+            "log", "-z", "--reverse", "--no-merges",
+            "--format=%H%x00%h%x00%P%x00%an%x00%ae%x00%ci%x00%D%x00%s",
+            &range,
+        ],
+        cwd,
+    )?;
+    Ok(parse_log_records(&raw)
+        .into_iter()
+        .map(|mut entry| {
+            entry["action"] = "pick".into();
+            entry
+        })
+        .collect())
+}
+
+/// 把计划写成 git-rebase-todo 的格式，需要改提交信息的步骤后面追加 exec amend
+fn render_todo(plan: &[PlanStep], plan_dir: &std::path::Path) -> Result<String, String> {
+    if matches!(plan.first().map(|s| s.action), Some(PlanAction::Squash | PlanAction::Fixup)) {
+        return Err("the first step cannot be squash or fixup".into());
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    for (i, step) in plan.iter().enumerate() {
+        let action = match step.action {
+            PlanAction::Pick | PlanAction::Reword => "pick",
+            PlanAction::Squash => "squash",
+            PlanAction::Fixup => "fixup",
+            PlanAction::Drop => "drop",
+        };
+        lines.push(format!("{} {}", action, step.hash));
+
+        let message = step.message.as_deref().filter(|m| !m.trim().is_empty());
+        let rewrites_message = match step.action {
+            PlanAction::Reword => {
+                if message.is_none() {
+                    return Err(format!("reword of {} needs a message", step.hash));
+                }
+                true
+            }
+            PlanAction::Squash => message.is_some(),
+            _ => false,
+        };
+        if let (true, Some(message)) = (rewrites_message, message) {
+            let msg_path = plan_dir.join(format!("message-{}", i));
+            std::fs::write(&msg_path, message).map_err(|e| e.to_string())?;
+            lines.push(format!(
+                "exec git commit --amend --no-verify --allow-empty -F {}",
+                sh_quote(&msg_path.to_string_lossy())
+            ));
+        }
+    }
+    lines.push(String::new());
+    Ok(lines.join("\n"))
+}
+
+/// rebase 进度：(已完成步数, 总步数)
+fn read_progress(cwd: &str) -> Option<(u32, u32)> {
+    let dir = git_path(cwd, "rebase-merge").ok().filter(|p| p.is_dir())?;
+    let read = |name: &str| -> Option<u32> {
+        std::fs::read_to_string(dir.join(name)).ok()?.trim().parse().ok()
+    };
+    Some((read("msgnum").unwrap_or(0), read("end").unwrap_or(0)))
+}
+
+fn progress_json(cwd: &str) -> serde_json::Value {
+    match read_progress(cwd) {
+        Some((done, total)) => serde_json::json!({ "done": done, "total": total }),
+        None => serde_json::Value::Null,
+    }
+}
+
+/// rebase 结束后清理计划目录
+fn cleanup_if_finished(cwd: &str) {
+    if in_progress_operation(cwd) != Some("rebase") {
+        if let Ok(dir) = git_path(cwd, PLAN_DIR) {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+fn with_progress(cwd: &str, mut result: serde_json::Value) -> serde_json::Value {
+    result["progress"] = progress_json(cwd);
+    cleanup_if_finished(cwd);
+    result
+}
+
+fn execute_plan(cwd: &str, base: &str, plan: &[PlanStep]) -> Result<serde_json::Value, String> {
+    if plan.is_empty() {
+        return Err("rebase plan is empty".into());
+    }
+    let base = resolve_base(cwd, base)?;
+    let plan_dir = git_path(cwd, PLAN_DIR)?;
+    let _ = std::fs::remove_dir_all(&plan_dir);
+    std::fs::create_dir_all(&plan_dir).map_err(|e| e.to_string())?;
+
+    let todo_path = plan_dir.join("todo");
+    std::fs::write(&todo_path, render_todo(plan, &plan_dir)?).map_err(|e| e.to_string())?;
+
+    // git 会把 todo 文件路径追加在编辑器命令后面
+    let mut cmd = git_cmd(&["rebase", "-i", &base], cwd);
+    cmd.env("GIT_SEQUENCE_EDITOR", format!("cp {}", sh_quote(&todo_path.to_string_lossy())));
+    cmd.env("GIT_EDITOR", "true");
+    let result = run_conflicting(cmd, cwd)?;
+    Ok(with_progress(cwd, result))
+}
+
+#[tauri::command]
+pub async fn git_rebase_todo(
+    state: State<'_, AppState>,
+    base: String,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    Ok(serde_json::json!({ "base": base, "steps": todo_list(&cwd, &base)? }))
+}
+
+#[tauri::command]
+pub async fn git_rebase_execute(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    base: String,
+    plan: Vec<PlanStep>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "rebase").await;
+    execute_plan(&cwd, &base, &plan)
+}
+
+#[tauri::command]
+pub async fn git_rebase_progress(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    Ok(serde_json::json!({
+        "inProgress": in_progress_operation(&cwd) == Some("rebase"),
+        "progress": progress_json(&cwd),
+    }))
+}
+
+#[tauri::command]
+pub async fn git_rebase_continue(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "rebase").await;
+    let result = continue_operation(&cwd, "rebase")?;
+    Ok(with_progress(&cwd, result))
+}
+
+#[tauri::command]
+pub async fn git_rebase_abort(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "rebase").await;
+    abort_operation(&cwd, "rebase")?;
+    cleanup_if_finished(&cwd);
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo};
+
+    fn subjects(cwd: &str) -> Vec<String> {
+        git(&["log", "--format=%s"], cwd)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    fn step(action: PlanAction, hash: &serde_json::Value, message: Option<&str>) -> PlanStep {
+        PlanStep {
+            action,
+            hash: hash.as_str().unwrap().to_string(),
+            message: message.map(String::from),
+        }
+    }
+
+    /// base 之后有三个 wip 提交，返回 base 的 hash
+    fn wip_history(cwd: &str) -> String {
+        commit_file(cwd, "a.txt", "base\n", "base");
+        let base = git(&["rev-parse", "HEAD"], cwd).unwrap();
+        commit_file(cwd, "a.txt", "one\n", "wip 1");
+        commit_file(cwd, "b.txt", "two\n", "wip 2");
+        commit_file(cwd, "c.txt", "three\n", "wip 3");
+        base
+    }
+
+    #[test]
+    fn todo_lists_commits_oldest_first() {
+        let (_dir, cwd) = init_repo();
+        let base = wip_history(&cwd);
+
+        let todo = todo_list(&cwd, &base).unwrap();
+
+        let messages: Vec<&str> = todo.iter().map(|s| s["message"].as_str().unwrap()).collect();
+        assert_eq!(messages, vec!["wip 1", "wip 2", "wip 3"]);
+        assert_eq!(todo[0]["action"], "pick");
+    }
+
+    #[test]
+    fn executes_fixup_reword_and_drop() {
+        let (_dir, cwd) = init_repo();
+        let base = wip_history(&cwd);
+        let todo = todo_list(&cwd, &base).unwrap();
+
+        let plan = vec![
+            step(PlanAction::Reword, &todo[0]["hash"], Some("feat: combined work")),
+            step(PlanAction::Fixup, &todo[1]["hash"], None),
+            step(PlanAction::Drop, &todo[2]["hash"], None),
+        ];
+        let result = execute_plan(&cwd, &base, &plan).unwrap();
+
+        assert_eq!(result["ok"], true);
+        assert_eq!(subjects(&cwd), vec!["feat: combined work", "base"]);
+        assert!(std::path::Path::new(&cwd).join("b.txt").exists());
+        assert!(!std::path::Path::new(&cwd).join("c.txt").exists());
+        assert!(!git_path(&cwd, PLAN_DIR).unwrap().exists());
+    }
+
+    #[test]
+    fn squash_with_message_replaces_combined_message() {
+        let (_dir, cwd) = init_repo();
+        let base = wip_history(&cwd);
+        let todo = todo_list(&cwd, &base).unwrap();
+
+        let plan = vec![
+            step(PlanAction::Pick, &todo[0]["hash"], None),
+            step(PlanAction::Squash, &todo[1]["hash"], None),
+            step(PlanAction::Squash, &todo[2]["hash"], Some("feat: everything")),
+        ];
+        execute_plan(&cwd, &base, &plan).unwrap();
+
+        assert_eq!(subjects(&cwd), vec!["feat: everything", "base"]);
+    }
+
+    #[test]
+    fn reorder_conflict_stops_and_can_be_aborted() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "base\n", "base");
+        let base = git(&["rev-parse", "HEAD"], &cwd).unwrap();
+        commit_file(&cwd, "a.txt", "first\n", "first");
+        commit_file(&cwd, "a.txt", "second\n", "second");
+        let head = git(&["rev-parse", "HEAD"], &cwd).unwrap();
+        let todo = todo_list(&cwd, &base).unwrap();
+
+        let plan = vec![
+            step(PlanAction::Pick, &todo[1]["hash"], None),
+            step(PlanAction::Pick, &todo[0]["hash"], None),
+        ];
+        let result = execute_plan(&cwd, &base, &plan).unwrap();
+
+        assert_eq!(result["ok"], false);
+        assert_eq!(result["operation"], "rebase");
+        assert_eq!(result["progress"]["total"], 2);
+
+        abort_operation(&cwd, "rebase").unwrap();
+        assert_eq!(git(&["rev-parse", "HEAD"], &cwd).unwrap(), head);
+    }
+
+    #[test]
+    fn option_like_base_is_rejected() {
+        let (dir, cwd) = init_repo();
+        wip_history(&cwd);
+        let head = git(&["rev-parse", "HEAD"], &cwd).unwrap();
+        let target = dir.path().join("written.txt");
+        let plan = vec![PlanStep { action: PlanAction::Drop, hash: head.clone(), message: None }];
+
+        assert!(todo_list(&cwd, &format!("--output={}", target.display())).is_err());
+        assert!(!target.exists());
+        assert!(execute_plan(&cwd, "--root", &plan).is_err());
+        assert_eq!(git(&["rev-parse", "HEAD"], &cwd).unwrap(), head);
+    }
+
+    #[test]
+    fn leading_squash_is_rejected() {
+        let dir = std::env::temp_dir();
+        let plan = vec![PlanStep { action: PlanAction::Fixup, hash: "abc".into(), message: None }];
+
+        assert!(render_todo(&plan, &dir).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use serde::Deserialize;
+use tauri::{AppHandle, Emitter, State};
+use crate::state::AppState;
+use super::askpass::AskpassBridge;
+use super::queue::GitQueue;
+use super::task::{emit_line, run_streamed, GitTasks, TaskError};
+use super::{conflict, conflicted_files, git, git_cmd};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PullStrategy {
+    Merge,
+    Rebase,
+    FfOnly,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushOptions {
+    pub remote: Option<String>,
+    pub branch: Option<String>,
+    /// 新分支首次推送时设置上游（-u）
+    #[serde(default)]
+    pub set_upstream: bool,
+    #[serde(default)]
+    pub force_with_lease: bool,
+    pub task_id: Option<String>,
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullOptions {
+    pub remote: Option<String>,
+    pub branch: Option<String>,
+    /// 不填时按仓库的 pull.rebase / pull.ff 配置
+    pub strategy: Option<PullStrategy>,
+    pub task_id: Option<String>,
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchOptions {
+    /// 不填时抓取所有远程
+    pub remote: Option<String>,
+    #[serde(default)]
+    pub prune: bool,
+    pub task_id: Option<String>,
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitProgressPayload {
+    pub task_id: String,
+    pub phase: String,
+    pub percent: Option<u32>,
+}
+
+/// 解析 `--progress` 输出，如 "remote: Counting objects:  45% (9/20)" → ("Counting objects", 45)
+pub(super) fn parse_progress(line: &str) -> Option<(String, Option<u32>)> {
+    let line = line.strip_prefix("remote: ").unwrap_or(line);
+    let (phase, rest) = line.split_once(": ")?;
+    if phase.is_empty() || phase.contains('/') || phase.starts_with(|c: char| !c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let percent = rest
+        .split_whitespace()
+        .find_map(|token| token.strip_suffix('%').and_then(|n| n.parse::<u32>().ok()));
+    if percent.is_none() && !rest.contains("done") {
+        return None;
+    }
+    Some((phase.trim().to_string(), percent))
+}
+
+/// 把推送/拉取失败归类为可以由界面处理的状态，无法归类的返回 None
+fn classify_failure(message: &str) -> Option<&'static str> {
+    let lower = message.to_lowercase();
+    if lower.contains("stale info") {
+        Some("staleLease")
+    } else if lower.contains("[rejected]") && (lower.contains("non-fast-forward") || lower.contains("fetch first")) {
+        Some("rejected")
+    } else if lower.contains("divergent branches") || lower.contains("not possible to fast-forward") {
+        Some("diverged")
+    } else {
+        None
+    }
+}
+
+/// 运行网络相关命令：逐行回调输出，失败时按冲突 / 分叉 / 被拒绝等返回结构化状态
+fn run_remote(
+    cwd: &str,
+    cmd: Command,
+    timeout: Option<Duration>,
+    cancel: &AtomicBool,
+    on_line: &mut dyn FnMut(&'static str, &str),
+) -> Result<serde_json::Value, String> {
+    let mut output: Vec<String> = Vec::new();
+    let mut collect = |stream: &'static str, line: &str| {
+        output.push(line.to_string());
+        on_line(stream, line);
+    };
+    let result = match run_streamed(cmd, cancel, timeout, &mut collect) {
+        Ok(result) => result,
+        Err(TaskError::Cancelled) => return Ok(serde_json::json!({ "ok": false, "status": "cancelled" })),
+        Err(TaskError::TimedOut) => return Ok(serde_json::json!({ "ok": false, "status": "timedOut" })),
+        Err(TaskError::Spawn(e)) => return Err(e),
+    };
+
+    if result.success {
+        return Ok(serde_json::json!({ "ok": true, "status": "ok", "result": result.stdout, "output": output }));
+    }
+
+    let message = [result.stdout, result.stderr]
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let conflicts = conflicted_files(cwd);
+    if !conflicts.is_empty() {
+        return Ok(serde_json::json!({
+            "ok": false,
+            "status": "conflicts",
+            "conflicts": conflicts,
+            "operation": conflict::in_progress_operation(cwd),
+            "message": message,
+        }));
+    }
+    match classify_failure(&message) {
+        Some(status) => Ok(serde_json::json!({ "ok": false, "status": status, "message": message })),
+        None => Err(message),
+    }
+}
+
+fn push_args(cwd: &str, options: &PushOptions) -> Result<Vec<String>, String> {
+    let mut args: Vec<String> = vec!["push".into(), "--progress".into()];
+    if options.force_with_lease {
+        args.push("--force-with-lease".into());
+    }
+    let mut remote = options.remote.clone().filter(|r| !r.is_empty());
+    let mut branch = options.branch.clone().filter(|b| !b.is_empty());
+    if options.set_upstream {
+        args.push("--set-upstream".into());
+        remote = remote.or_else(|| Some("origin".into()));
+        if branch.is_none() {
+            let current = git(&["branch", "--show-current"], cwd)?;
+            if current.is_empty() {
+                return Err("cannot set upstream from a detached HEAD".into());
+            }
+            branch = Some(current);
+        }
+    }
+    if let Some(remote) = remote {
+        args.push(remote);
+        args.extend(branch);
+    }
+    Ok(args)
+}
+
+fn pull_args(options: &PullOptions) -> Vec<String> {
+    let mut args: Vec<String> = vec!["pull".into(), "--progress".into()];
+    if let Some(strategy) = options.strategy {
+        args.push(
+            match strategy {
+                PullStrategy::Merge => "--no-rebase",
+                PullStrategy::Rebase => "--rebase",
+                PullStrategy::FfOnly => "--ff-only",
+            }
+            .into(),
+        );
+    }
+    if let Some(remote) = options.remote.clone().filter(|r| !r.is_empty()) {
+        args.push(remote);
+        args.extend(options.branch.clone().filter(|b| !b.is_empty()));
+    }
+    args
+}
+
+fn fetch_args(options: &FetchOptions) -> Vec<String> {
+    let mut args: Vec<String> = vec!["fetch".into(), "--progress".into()];
+    if options.prune {
+        args.push("--prune".into());
+    }
+    match options.remote.clone().filter(|r| !r.is_empty()) {
+        Some(remote) => args.push(remote),
+        None => args.push("--all".into()),
+    }
+    args
+}
+
+/// 执行远程命令并把输出和解析后的进度作为事件推送给前端
+pub(super) fn run_task(
+    app: &AppHandle,
+    tasks: &GitTasks,
+    askpass: &AskpassBridge,
+    cwd: &str,
+    args: &[String],
+    task_id: Option<String>,
+    timeout_secs: Option<u64>,
+) -> Result<serde_json::Value, String> {
+    let arg_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let mut cmd = git_cmd(&arg_refs, cwd);
+    askpass.configure(app, cwd, &mut cmd)?;
+    let (task_id, cancel) = tasks.register(task_id);
+    let result = run_remote(cwd, cmd, timeout_secs.map(Duration::from_secs), &cancel, &mut |stream, line| {
+        emit_line(app, &task_id, stream, line);
+        if let Some((phase, percent)) = parse_progress(line) {
+            let _ = app.emit(
+                "git-task-progress",
+                GitProgressPayload { task_id: task_id.clone(), phase, percent },
+            );
+        }
+    });
+    tasks.finish(&task_id);
+    result.map(|mut value| {
+        value["taskId"] = task_id.into();
+        value
+    })
+}
+
+/// 推送只更新远程跟踪分支，不进仓库队列，等待网络和凭据时不挡暂存、提交等操作
+#[tauri::command]
+pub async fn git_push(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    tasks: State<'_, GitTasks>,
+    askpass: State<'_, AskpassBridge>,
+    options: Option<PushOptions>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let options = options.unwrap_or_default();
+    let args = push_args(&cwd, &options)?;
+    run_task(&app, &tasks, &askpass, &cwd, &args, options.task_id, options.timeout_secs)
+}
+
+#[tauri::command]
+pub async fn git_pull(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    tasks: State<'_, GitTasks>,
+    askpass: State<'_, AskpassBridge>,
+    options: Option<PullOptions>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "pull").await;
+    let options = options.unwrap_or_default();
+    let args = pull_args(&options);
+    run_task(&app, &tasks, &askpass, &cwd, &args, options.task_id, options.timeout_secs)
+}
+
+/// 获取同样只写远程跟踪分支和 FETCH_HEAD，不进仓库队列；拉取要合并到工作区，仍然排队
+#[tauri::command]
+pub async fn git_fetch(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    tasks: State<'_, GitTasks>,
+    askpass: State<'_, AskpassBridge>,
+    options: Option<FetchOptions>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let options = options.unwrap_or_default();
+    let args = fetch_args(&options);
+    run_task(&app, &tasks, &askpass, &cwd, &args, options.task_id, options.timeout_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo, run_git};
+    use tempfile::tempdir;
+
+    fn run(cwd: &str, args: &[String]) -> Result<serde_json::Value, String> {
+        let arg_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        run_remote(cwd, git_cmd(&arg_refs, cwd), None, &AtomicBool::new(false), &mut |_, _| {})
+    }
+
+    /// 一个本地仓库 + 作为 origin 的裸仓库 + 另一个克隆，用来制造分叉
+    fn with_remote() -> (tempfile::TempDir, tempfile::TempDir, String, String, String) {
+        let (dir, cwd) = init_repo();
+        let remote_dir = tempdir().unwrap();
+        let bare = remote_dir.path().join("origin.git").to_string_lossy().to_string();
+        run_git(&["init", "--bare", &bare], &cwd);
+        run_git(&["remote", "add", "origin", &bare], &cwd);
+        commit_file(&cwd, "a.txt", "base\n", "init");
+        let branch = git(&["branch", "--show-current"], &cwd).unwrap();
+        run(&cwd, &push_args(&cwd, &PushOptions { set_upstream: true, ..Default::default() }).unwrap()).unwrap();
+
+        let other = remote_dir.path().join("other").to_string_lossy().to_string();
+        run_git(&["clone", &bare, &other], &cwd);
+        run_git(&["config", "user.email", "other@example.com"], &other);
+        run_git(&["config", "user.name", "Other"], &other);
+        (dir, remote_dir, cwd, other, branch)
+    }
+
+    #[test]
+    fn parses_progress_phase_and_percent() {
+        assert_eq!(
+            parse_progress("remote: Counting objects:  45% (9/20)"),
+            Some(("Counting objects".into(), Some(45)))
+        );
+        assert_eq!(
+            parse_progress("Writing objects: 100% (3/3), 240 bytes | 240.00 KiB/s, done."),
+            Some(("Writing objects".into(), Some(100)))
+        );
+        assert_eq!(parse_progress("To /tmp/origin.git"), None);
+        assert_eq!(parse_progress("hint: Updates were rejected"), None);
+    }
+
+    #[test]
+    fn set_upstream_push_tracks_remote_branch() {
+        let (_dir, _remote, cwd, _other, branch) = with_remote();
+
+        let upstream = git(&["rev-parse", "--abbrev-ref", "@{u}"], &cwd).unwrap();
+
+        assert_eq!(upstream, format!("origin/{}", branch));
+    }
+
+    #[test]
+    fn diverged_push_is_rejected_and_force_with_lease_overrides() {
+        let (_dir, _remote, cwd, other, _branch) = with_remote();
+        commit_file(&other, "a.txt", "theirs\n", "theirs");
+        run_git(&["push"], &other);
+        commit_file(&cwd, "a.txt", "ours\n", "ours");
+
+        let rejected = run(&cwd, &push_args(&cwd, &PushOptions::default()).unwrap()).unwrap();
+        assert_eq!(rejected["ok"], false);
+        assert_eq!(rejected["status"], "rejected");
+
+        run(&cwd, &fetch_args(&FetchOptions::default())).unwrap();
+        let forced = run(&cwd, &push_args(&cwd, &PushOptions { force_with_lease: true, ..Default::default() }).unwrap()).unwrap();
+        assert_eq!(forced["ok"], true);
+    }
+
+    #[test]
+    fn ff_only_pull_reports_divergence_and_rebase_pull_resolves_it() {
+        let (_dir, _remote, cwd, other, _branch) = with_remote();
+        commit_file(&other, "b.txt", "theirs\n", "theirs");
+        run_git(&["push"], &other);
+        commit_file(&cwd, "c.txt", "ours\n", "ours");
+
+        let diverged = run(&cwd, &pull_args(&PullOptions { strategy: Some(PullStrategy::FfOnly), ..Default::default() })).unwrap();
+        assert_eq!(diverged["status"], "diverged");
+
+        let rebased = run(&cwd, &pull_args(&PullOptions { strategy: Some(PullStrategy::Rebase), ..Default::default() })).unwrap();
+        assert_eq!(rebased["ok"], true);
+        assert_eq!(git(&["rev-list", "--count", "@{u}..HEAD"], &cwd).unwrap(), "1");
+    }
+
+    #[test]
+    fn pull_without_strategy_follows_repository_config() {
+        let (_dir, _remote, cwd, other, _branch) = with_remote();
+        commit_file(&other, "b.txt", "theirs\n", "theirs");
+        run_git(&["push"], &other);
+        commit_file(&cwd, "c.txt", "ours\n", "ours");
+        run_git(&["config", "pull.rebase", "true"], &cwd);
+
+        let pulled = run(&cwd, &pull_args(&PullOptions::default())).unwrap();
+        assert_eq!(pulled["ok"], true);
+        // 按配置 rebase，没有产生合并提交
+        assert_eq!(git(&["rev-list", "--count", "--merges", "HEAD"], &cwd).unwrap(), "0");
+        assert_eq!(git(&["rev-list", "--count", "@{u}..HEAD"], &cwd).unwrap(), "1");
+    }
+
+    #[test]
+    fn conflicting_pull_returns_conflicts() {
+        let (_dir, _remote, cwd, other, _branch) = with_remote();
+        commit_file(&other, "a.txt", "theirs\n", "theirs");
+        run_git(&["push"], &other);
+        commit_file(&cwd, "a.txt", "ours\n", "ours");
+
+        let result = run(&cwd, &pull_args(&PullOptions { strategy: Some(PullStrategy::Merge), ..Default::default() })).unwrap();
+
+        assert_eq!(result["status"], "conflicts");
+        assert_eq!(result["operation"], "merge");
+        assert_eq!(result["conflicts"][0]["path"], "a.txt");
+    }
+
+    #[test]
+    fn fetch_with_prune_removes_deleted_remote_branches() {
+        let (_dir, _remote, cwd, other, _branch) = with_remote();
+        run_git(&["push", "origin", "HEAD:refs/heads/gone"], &other);
+        run(&cwd, &fetch_args(&FetchOptions::default())).unwrap();
+        assert!(git(&["rev-parse", "--verify", "origin/gone"], &cwd).is_ok());
+        run_git(&["push", "origin", "--delete", "gone"], &other);
+
+        run(&cwd, &fetch_args(&FetchOptions { remote: Some("origin".into()), prune: true, ..Default::default() })).unwrap();
+
+        assert!(git(&["rev-parse", "--verify", "origin/gone"], &cwd).is_err());
+    }
+}
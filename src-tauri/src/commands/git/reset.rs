@@ -0,0 +1,299 @@
+use serde::Deserialize;
+use tauri::State;
+use crate::state::AppState;
+use super::conflict::in_progress_operation;
+use super::queue::GitQueue;
+use super::{check_rev, git, git_conflicting, git_path, git_raw};
+
+/// 记录硬重置前自动保存的 stash 提交，每行 "<hash>\t<目标>"
+const BACKUP_LOG: &str = "openloom-reset-backups";
+const DEFAULT_REFLOG_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResetMode {
+    Soft,
+    #[default]
+    Mixed,
+    Hard,
+}
+
+/// 把工作区和暂存区的改动保存为不挂在 refs/stash 上的 stash 提交；没有改动时返回 None
+fn backup_changes(cwd: &str, target: &str) -> Result<Option<String>, String> {
+    let message = format!("openloom: before hard reset to {}", target);
+    let hash = git(&["stash", "create", &message], cwd)?;
+    if hash.is_empty() {
+        return Ok(None);
+    }
+    let log = git_path(cwd, BACKUP_LOG)?;
+    let mut content = std::fs::read_to_string(&log).unwrap_or_default();
+    content.push_str(&format!("{}\t{}\n", hash, target));
+    std::fs::write(&log, content).map_err(|e| e.to_string())?;
+    Ok(Some(hash))
+}
+
+fn reset(cwd: &str, target: &str, mode: ResetMode) -> Result<serde_json::Value, String> {
+    let target = if target.is_empty() { "HEAD" } else { check_rev(target)? };
+    git(&["rev-parse", "--verify", &format!("{}^{{commit}}", target)], cwd)?;
+    let backup = match mode {
+        ResetMode::Hard => backup_changes(cwd, target)?,
+        _ => None,
+    };
+    let flag = match mode {
+        ResetMode::Soft => "--soft",
+        ResetMode::Mixed => "--mixed",
+        ResetMode::Hard => "--hard",
+    };
+    git(&["reset", "--quiet", flag, target], cwd)?;
+    Ok(serde_json::json!({ "ok": true, "head": git(&["rev-parse", "HEAD"], cwd)?, "backup": backup }))
+}
+
+/// 最近的备份在前；已被 gc 清理的备份会被跳过
+fn list_backups(cwd: &str) -> Result<Vec<serde_json::Value>, String> {
+    let content = std::fs::read_to_string(git_path(cwd, BACKUP_LOG)?).unwrap_or_default();
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| {
+            let (hash, target) = line.split_once('\t')?;
+            let info = git(&["show", "-s", "--format=%ci", hash], cwd).ok()?;
+            Some(serde_json::json!({ "hash": hash, "target": target, "date": info }))
+        })
+        .collect())
+}
+
+/// 解析 `%gs`，如 "commit (amend): fix typo" → ("commit (amend)", "fix typo")
+fn split_reflog_subject(subject: &str) -> (&str, &str) {
+    match subject.split_once(": ") {
+        Some((action, message)) => (action, message),
+        None => (subject, ""),
+    }
+}
+
+fn read_reflog(cwd: &str, reference: &str, limit: usize) -> Result<Vec<serde_json::Value>, String> {
+    check_rev(reference)?;
+    let count = format!("-n{}", limit);
+    let raw = git_raw(
+        &["reflog", "show", "--date=iso-strict", "--format=%H%x00%h%x00%gd%x00%gs", &count, reference, "--"],
+        cwd,
+    )?;
+    Ok(raw
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let parts: Vec<&str> = line.split('\0').collect();
+            if parts.len() < 4 {
+                return None;
+            }
+            // --date 使 %gd 形如 HEAD@{2024-01-01T00:00:00+08:00}
+            let date = parts[2].split_once("@{").map(|(_, d)| d.trim_end_matches('}')).unwrap_or("");
+            let (action, message) = split_reflog_subject(parts[3]);
+            Some(serde_json::json!({
+                "hash": parts[0],
+                "shortHash": parts[1],
+                "selector": format!("{}@{{{}}}", reference, index),
+                "date": date,
+                "action": action,
+                "message": message,
+            }))
+        })
+        .collect())
+}
+
+/// 撤销上一次移动 HEAD 或分支的操作。
+/// 切换分支时切回原分支；否则把当前分支（分离 HEAD 时为 HEAD）移回它在 reflog 中的上一个位置，
+/// 因此 rebase 等产生多条 HEAD 记录的操作也会整体撤销。再次撤销相当于重做。
+fn undo_last(cwd: &str) -> Result<serde_json::Value, String> {
+    if let Some(op) = in_progress_operation(cwd) {
+        return Err(format!("a {} is in progress; finish or abort it first", op));
+    }
+    let last = git(&["reflog", "show", "-n1", "--format=%gs", "HEAD", "--"], cwd)?;
+    let (action, message) = split_reflog_subject(&last);
+
+    if action == "checkout" {
+        if let Some(previous) = message.strip_prefix("moving from ").and_then(|m| m.split(" to ").next()) {
+            git(&["checkout", "--quiet", previous], cwd)?;
+            return Ok(serde_json::json!({ "ok": true, "undone": last, "head": git(&["rev-parse", "HEAD"], cwd)? }));
+        }
+    }
+
+    let branch = git(&["symbolic-ref", "--quiet", "--short", "HEAD"], cwd).ok();
+    let reference = match &branch {
+        Some(branch) => format!("refs/heads/{}", branch),
+        None => "HEAD".to_string(),
+    };
+    let branch_last = git(&["reflog", "show", "-n1", "--format=%gs", &reference, "--"], cwd)?;
+    let previous = format!("{}@{{1}}", reference);
+    git(&["rev-parse", "--verify", "--quiet", &previous], cwd)
+        .map_err(|_| "nothing to undo".to_string())?;
+
+    // 撤销提交时保留改动在暂存区，其余情况 --keep 会拒绝覆盖本地未提交的改动
+    let flag = if split_reflog_subject(&branch_last).0.starts_with("commit") { "--soft" } else { "--keep" };
+    git(&["reset", "--quiet", flag, &previous], cwd)?;
+    Ok(serde_json::json!({ "ok": true, "undone": branch_last, "head": git(&["rev-parse", "HEAD"], cwd)? }))
+}
+
+#[tauri::command]
+pub async fn git_reset(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    target: String,
+    mode: Option<ResetMode>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "reset").await;
+    reset(&cwd, &target, mode.unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn git_reset_backups(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    Ok(serde_json::json!(list_backups(&cwd)?))
+}
+
+/// 把硬重置前的备份重新应用到工作区
+#[tauri::command]
+pub async fn git_reset_restore_backup(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    hash: String,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "reset").await;
+    git_conflicting(&["stash", "apply", check_rev(&hash)?], &cwd)
+}
+
+/// 列出 HEAD（默认）或某个分支的 reflog
+#[tauri::command]
+pub async fn git_reflog(
+    state: State<'_, AppState>,
+    reference: Option<String>,
+    limit: Option<usize>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let reference = reference.filter(|r| !r.is_empty()).unwrap_or_else(|| "HEAD".into());
+    Ok(serde_json::json!(read_reflog(&cwd, &reference, limit.unwrap_or(DEFAULT_REFLOG_LIMIT))?))
+}
+
+#[tauri::command]
+pub async fn git_undo(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "undo").await;
+    undo_last(&cwd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo, run_git};
+    use std::path::Path;
+
+    fn head(cwd: &str) -> String {
+        git(&["rev-parse", "HEAD"], cwd).unwrap()
+    }
+
+    fn read(cwd: &str, name: &str) -> String {
+        std::fs::read_to_string(Path::new(cwd).join(name)).unwrap()
+    }
+
+    #[test]
+    fn hard_reset_backs_up_working_changes() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "one\n", "first");
+        let first = head(&cwd);
+        commit_file(&cwd, "a.txt", "two\n", "second");
+        std::fs::write(Path::new(&cwd).join("a.txt"), "dirty\n").unwrap();
+
+        let result = reset(&cwd, &first, ResetMode::Hard).unwrap();
+
+        assert_eq!(head(&cwd), first);
+        assert_eq!(read(&cwd, "a.txt"), "one\n");
+        let backup = result["backup"].as_str().unwrap();
+        assert_eq!(git(&["show", &format!("{}:a.txt", backup)], &cwd).unwrap(), "dirty");
+        assert_eq!(list_backups(&cwd).unwrap()[0]["hash"], backup);
+        assert!(git(&["rev-parse", "--verify", "--quiet", "refs/stash"], &cwd).is_err());
+    }
+
+    #[test]
+    fn soft_and_mixed_reset_keep_changes() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "one\n", "first");
+        let first = head(&cwd);
+        commit_file(&cwd, "a.txt", "two\n", "second");
+
+        reset(&cwd, &first, ResetMode::Soft).unwrap();
+        assert_eq!(git(&["diff", "--cached", "--name-only"], &cwd).unwrap(), "a.txt");
+
+        run_git(&["commit", "-q", "-m", "again"], &cwd);
+        let result = reset(&cwd, &first, ResetMode::Mixed).unwrap();
+        assert_eq!(git(&["diff", "--cached", "--name-only"], &cwd).unwrap(), "");
+        assert_eq!(read(&cwd, "a.txt"), "two\n");
+        assert!(result["backup"].is_null());
+    }
+
+    #[test]
+    fn reflog_lists_actions_with_dates() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "one\n", "first");
+        commit_file(&cwd, "a.txt", "two\n", "second");
+
+        let entries = read_reflog(&cwd, "HEAD", 10).unwrap();
+
+        assert_eq!(entries[0]["action"], "commit");
+        assert_eq!(entries[0]["message"], "second");
+        assert_eq!(entries[0]["selector"], "HEAD@{0}");
+        assert_eq!(entries[1]["action"], "commit (initial)");
+        assert!(!entries[0]["date"].as_str().unwrap().is_empty());
+    }
+
+    #[test]
+    fn undo_commit_keeps_changes_staged() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "one\n", "first");
+        let first = head(&cwd);
+        commit_file(&cwd, "a.txt", "two\n", "second");
+
+        undo_last(&cwd).unwrap();
+
+        assert_eq!(head(&cwd), first);
+        assert_eq!(git(&["diff", "--cached", "--name-only"], &cwd).unwrap(), "a.txt");
+    }
+
+    #[test]
+    fn undo_reverts_hard_reset_and_rebase() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "one\n", "first");
+        let first = head(&cwd);
+        commit_file(&cwd, "b.txt", "b\n", "second");
+        let second = head(&cwd);
+
+        reset(&cwd, &first, ResetMode::Hard).unwrap();
+        undo_last(&cwd).unwrap();
+        assert_eq!(head(&cwd), second);
+
+        let main = git(&["branch", "--show-current"], &cwd).unwrap();
+        run_git(&["switch", "-q", "-c", "other", &first], &cwd);
+        commit_file(&cwd, "c.txt", "c\n", "other work");
+        run_git(&["switch", "-q", &main], &cwd);
+        run_git(&["rebase", "-q", "other"], &cwd);
+        assert_ne!(head(&cwd), second);
+
+        undo_last(&cwd).unwrap();
+        assert_eq!(head(&cwd), second);
+    }
+
+    #[test]
+    fn undo_checkout_switches_back() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "one\n", "first");
+        let main = git(&["branch", "--show-current"], &cwd).unwrap();
+        run_git(&["switch", "-q", "-c", "feature"], &cwd);
+
+        undo_last(&cwd).unwrap();
+
+        assert_eq!(git(&["branch", "--show-current"], &cwd).unwrap(), main);
+    }
+}
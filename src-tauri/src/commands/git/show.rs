@@ -0,0 +1,262 @@
+use super::backend::{blob_text, GitBackend};
+use super::compare::{parse_name_status, parse_numstat};
+use super::{git, git_raw, lfs, submodule};
+
+/// 提交中的一个改动文件
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct ChangedFile {
+    pub(super) status: String,
+    pub(super) path: String,
+    pub(super) old_path: String,
+    pub(super) additions: Option<u64>,
+    pub(super) deletions: Option<u64>,
+    pub(super) binary: bool,
+}
+
+impl ChangedFile {
+    pub(super) fn to_json(&self) -> serde_json::Value {
+        // stats 保留旧的展示字段，供只显示一段文字的地方使用
+        let stats = if self.binary {
+            "Bin".to_string()
+        } else {
+            format!("+{} -{}", self.additions.unwrap_or(0), self.deletions.unwrap_or(0))
+        };
+        serde_json::json!({
+            "file": self.path,
+            "oldFile": self.old_path,
+            "status": self.status,
+            "additions": self.additions,
+            "deletions": self.deletions,
+            "binary": self.binary,
+            "stats": stats,
+        })
+    }
+}
+
+/// 要比较的父提交：根提交为 None；合并提交按 parent（从 1 开始，默认第一个父提交）选择
+pub(super) fn select_parent<T: Clone>(hash: &str, parents: &[T], parent: Option<usize>) -> Result<Option<T>, String> {
+    if parents.is_empty() {
+        return Ok(None);
+    }
+    let index = parent.unwrap_or(1);
+    if index == 0 || index > parents.len() {
+        return Err(format!("提交 {} 只有 {} 个父提交", hash, parents.len()));
+    }
+    Ok(Some(parents[index - 1].clone()))
+}
+
+fn diff_tree(cwd: &str, parent: Option<&str>, hash: &str, format: &str) -> Result<String, String> {
+    let mut args = vec!["diff-tree", "-r", "-z", "-M", "--no-commit-id", format];
+    match parent {
+        Some(parent) => args.extend([parent, hash]),
+        None => args.extend(["--root", hash]),
+    }
+    git_raw(&args, cwd)
+}
+
+/// 提交相对所选父提交的改动，带重命名检测和 numstat
+fn changed_files(cwd: &str, parent: Option<&str>, hash: &str) -> Result<Vec<ChangedFile>, String> {
+    let stats = parse_numstat(&diff_tree(cwd, parent, hash, "--numstat")?);
+    let changes = parse_name_status(&diff_tree(cwd, parent, hash, "--name-status")?);
+    Ok(changes
+        .into_iter()
+        .map(|(status, old_path, path)| {
+            let (additions, deletions) = stats.get(&path).copied().unwrap_or((None, None));
+            let binary = stats.contains_key(&path) && additions.is_none() && deletions.is_none();
+            ChangedFile { status, path, old_path, additions, deletions, binary }
+        })
+        .collect())
+}
+
+pub(super) fn read_show(cwd: &str, hash: &str, parent: Option<usize>) -> Result<serde_json::Value, String> {
+    let header = git_raw(&["show", "-s", "--format=%H%x00%h%x00%s%x00%an%x00%ae%x00%ci%x00%P%x00%b", hash], cwd)?;
+    let fields: Vec<&str> = header.splitn(8, '\0').collect();
+    if fields.len() < 8 {
+        return Err("empty output".into());
+    }
+    let full_hash = fields[0];
+    let parents: Vec<&str> = fields[6].split_whitespace().collect();
+    let selected = select_parent(full_hash, &parents, parent)?;
+    let files: Vec<serde_json::Value> = changed_files(cwd, selected, full_hash)?
+        .iter()
+        .map(ChangedFile::to_json)
+        .collect();
+
+    Ok(serde_json::json!({
+        "hash": full_hash, "shortHash": fields[1],
+        "subject": fields[2], "author": fields[3],
+        "email": fields[4], "date": fields[5], "body": fields[7].trim(),
+        "parents": parents,
+        "parent": selected,
+        "files": files,
+    }))
+}
+
+fn blob_size(cwd: &str, rev: &str, path: &str) -> Option<u64> {
+    git(&["cat-file", "-s", &format!("{}:{}", rev, path)], cwd).ok()?.parse().ok()
+}
+
+pub(super) fn file_diff(
+    backend: &dyn GitBackend,
+    cwd: &str,
+    hash: &str,
+    file: &str,
+    parent: Option<usize>,
+) -> Result<serde_json::Value, String> {
+    let show = backend.show(cwd, hash, parent)?;
+    let hash = show["hash"].as_str().unwrap_or(hash);
+    let selected = show["parent"].as_str().map(String::from);
+    let change = show["files"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|c| c["file"] == file || c["oldFile"] == file);
+    // 通过重命名检测找到文件在父提交中的路径
+    let old_file = change
+        .and_then(|c| c["oldFile"].as_str())
+        .unwrap_or(file)
+        .to_string();
+
+    let new_link = submodule::gitlink_at(cwd, Some(hash), file);
+    let old_link = selected.as_deref().and_then(|p| submodule::gitlink_at(cwd, Some(p), &old_file));
+    if new_link.is_some() || old_link.is_some() {
+        return Ok(submodule::submodule_diff(cwd, file, old_link.as_deref(), new_link.as_deref()));
+    }
+
+    if change.is_some_and(|c| c["binary"] == true) {
+        return Ok(serde_json::json!({
+            "file": file,
+            "oldFile": old_file,
+            "binary": true,
+            "oldSize": selected.as_deref().and_then(|p| blob_size(cwd, p, &old_file)),
+            "newSize": blob_size(cwd, hash, file),
+            "oldContent": "",
+            "newContent": "",
+        }));
+    }
+
+    let new_content = blob_text(backend, cwd, Some(hash), file);
+    let old_content = match &selected {
+        Some(parent) => blob_text(backend, cwd, Some(parent), &old_file),
+        None => String::new(),
+    };
+
+    if let Some(diff) = lfs::lfs_diff(cwd, file, &old_content, &new_content, false) {
+        return Ok(diff);
+    }
+
+    Ok(serde_json::json!({
+        "oldContent": old_content,
+        "newContent": new_content,
+        "file": file,
+        "oldFile": old_file,
+        "binary": false,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{backends, commit_file, init_repo, run_git};
+    use std::path::Path;
+
+    #[test]
+    fn root_commit_diffs_against_empty_tree() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "hello\n", "root");
+
+        for backend in backends() {
+            let show = backend.show(&cwd, "HEAD", None).unwrap();
+            assert_eq!(show["files"][0]["file"], "a.txt");
+            assert_eq!(show["files"][0]["status"], "A");
+            assert_eq!(show["files"][0]["additions"], 1);
+            assert!(show["parent"].is_null());
+
+            let diff = file_diff(backend, &cwd, "HEAD", "a.txt", None).unwrap();
+            assert_eq!(diff["oldContent"], "");
+            assert_eq!(diff["newContent"], "hello\n");
+        }
+    }
+
+    #[test]
+    fn renamed_file_reads_old_content_from_old_path() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "old.txt", "one\ntwo\nthree\nfour\n", "create");
+        run_git(&["mv", "old.txt", "new.txt"], &cwd);
+        std::fs::write(Path::new(&cwd).join("new.txt"), "one\ntwo\nthree\nfour\nfive\n").unwrap();
+        run_git(&["commit", "-q", "-am", "rename"], &cwd);
+
+        for backend in backends() {
+            let show = backend.show(&cwd, "HEAD", None).unwrap();
+            assert_eq!(show["files"].as_array().unwrap().len(), 1);
+            assert_eq!(show["files"][0]["status"], "R");
+            assert_eq!(show["files"][0]["oldFile"], "old.txt");
+
+            let diff = file_diff(backend, &cwd, "HEAD", "new.txt", None).unwrap();
+            assert_eq!(diff["oldFile"], "old.txt");
+            assert_eq!(diff["oldContent"], "one\ntwo\nthree\nfour\n");
+        }
+    }
+
+    #[test]
+    fn merge_commit_can_diff_against_either_parent() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "base\n", "base");
+        run_git(&["checkout", "-q", "-b", "feature"], &cwd);
+        commit_file(&cwd, "feature.txt", "feature\n", "feature");
+        run_git(&["checkout", "-q", "-"], &cwd);
+        commit_file(&cwd, "main.txt", "main\n", "main");
+        run_git(&["merge", "-q", "--no-edit", "feature"], &cwd);
+
+        for backend in backends() {
+            let first = backend.show(&cwd, "HEAD", None).unwrap();
+            assert_eq!(first["parents"].as_array().unwrap().len(), 2);
+            assert_eq!(first["files"][0]["file"], "feature.txt");
+
+            let second = backend.show(&cwd, "HEAD", Some(2)).unwrap();
+            assert_eq!(second["files"][0]["file"], "main.txt");
+            let diff = file_diff(backend, &cwd, "HEAD", "main.txt", Some(2)).unwrap();
+            assert_eq!(diff["oldContent"], "");
+            assert_eq!(diff["newContent"], "main\n");
+
+            assert!(backend.show(&cwd, "HEAD", Some(3)).is_err());
+        }
+    }
+
+    #[test]
+    fn binary_files_report_sizes_instead_of_content() {
+        let (_dir, cwd) = init_repo();
+        std::fs::write(Path::new(&cwd).join("img.bin"), [0u8, 1, 2]).unwrap();
+        run_git(&["add", "img.bin"], &cwd);
+        run_git(&["commit", "-q", "-m", "add"], &cwd);
+        std::fs::write(Path::new(&cwd).join("img.bin"), [0u8, 1, 2, 3, 4]).unwrap();
+        run_git(&["commit", "-q", "-am", "grow"], &cwd);
+
+        for backend in backends() {
+            let show = backend.show(&cwd, "HEAD", None).unwrap();
+            assert_eq!(show["files"][0]["binary"], true);
+            assert_eq!(show["files"][0]["stats"], "Bin");
+
+            let diff = file_diff(backend, &cwd, "HEAD", "img.bin", None).unwrap();
+            assert_eq!(diff["binary"], true);
+            assert_eq!(diff["oldSize"], 3);
+            assert_eq!(diff["newSize"], 5);
+            assert_eq!(diff["newContent"], "");
+        }
+    }
+
+    #[test]
+    fn backends_report_the_same_commit() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "1\n2\n3\n", "first");
+        std::fs::write(Path::new(&cwd).join("a.txt"), "1\n3\n4\n").unwrap();
+        std::fs::write(Path::new(&cwd).join("b.txt"), "b\n").unwrap();
+        run_git(&["add", "."], &cwd);
+        run_git(&["commit", "-q", "-m", "subject", "-m", "body line"], &cwd);
+
+        let [cli, lib] = backends();
+        let expected = cli.show(&cwd, "HEAD", None).unwrap();
+        assert_eq!(expected["body"], "body line");
+        assert_eq!(lib.show(&cwd, "HEAD", None).unwrap(), expected);
+    }
+}
@@ -0,0 +1,357 @@
+use tauri::State;
+use crate::state::AppState;
+use super::queue::GitQueue;
+use super::{git, git_conflicting, git_raw};
+
+fn stash_ref(index: usize) -> String {
+    format!("stash@{{{}}}", index)
+}
+
+/// 从 reflog 主题中取出分支名："WIP on main: ..." / "On main: msg"
+fn parse_stash_subject(subject: &str) -> (String, String) {
+    let rest = subject
+        .strip_prefix("WIP on ")
+        .or_else(|| subject.strip_prefix("On "))
+        .unwrap_or(subject);
+    match rest.split_once(": ") {
+        Some((branch, message)) => (branch.to_string(), message.to_string()),
+        None => (String::new(), rest.to_string()),
+    }
+}
+
+fn list_stashes(cwd: &str) -> Result<serde_json::Value, String> {
+    let raw = git_raw(&["stash", "list", "--format=%gd%x00%H%x00%ct%x00%ci%x00%gs"], cwd)?;
+    let entries: Vec<serde_json::Value> = raw
+        .lines()
+        .filter(|l| !l.is_empty())
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.splitn(5, '\0').collect();
+            if parts.len() < 5 {
+                return None;
+            }
+            let index: usize = parts[0]
+                .trim_start_matches("stash@{")
+                .trim_end_matches('}')
+                .parse()
+                .ok()?;
+            let (branch, message) = parse_stash_subject(parts[4]);
+            Some(serde_json::json!({
+                "index": index,
+                "ref": parts[0],
+                "hash": parts[1],
+                "timestamp": parts[2].parse::<i64>().unwrap_or(0),
+                "date": parts[3],
+                "branch": branch,
+                "message": message,
+            }))
+        })
+        .collect();
+    Ok(serde_json::json!(entries))
+}
+
+fn save_stash(
+    cwd: &str,
+    message: Option<&str>,
+    include_untracked: bool,
+    staged_only: bool,
+) -> Result<serde_json::Value, String> {
+    let before = git(&["rev-parse", "--verify", "-q", "refs/stash"], cwd).ok();
+
+    let mut args = vec!["stash", "push"];
+    if let Some(msg) = message.filter(|m| !m.is_empty()) {
+        args.push("-m");
+        args.push(msg);
+    }
+    if staged_only {
+        args.push("--staged");
+    } else if include_untracked {
+        args.push("--include-untracked");
+    }
+    git(&args, cwd)?;
+
+    // 没有可保存的改动时 git 仍返回成功，通过 refs/stash 是否变化判断
+    let after = git(&["rev-parse", "--verify", "-q", "refs/stash"], cwd).ok();
+    let created = after.is_some() && after != before;
+    Ok(serde_json::json!({ "ok": true, "created": created, "hash": if created { after } else { None } }))
+}
+
+/// 把 `git diff` 的补丁按文件拆分
+fn split_patch(raw: &str) -> Vec<serde_json::Value> {
+    let mut files: Vec<serde_json::Value> = Vec::new();
+    let mut chunks: Vec<Vec<&str>> = Vec::new();
+    for line in raw.lines() {
+        if line.starts_with("diff --git ") || chunks.is_empty() {
+            chunks.push(Vec::new());
+        }
+        if let Some(chunk) = chunks.last_mut() {
+            chunk.push(line);
+        }
+    }
+
+    for chunk in chunks {
+        let Some(header) = chunk.first().filter(|h| h.starts_with("diff --git ")) else {
+            continue;
+        };
+        let mut old_path: Option<String> = None;
+        let mut new_path: Option<String> = None;
+        let mut status = "modified";
+        let mut additions = 0;
+        let mut deletions = 0;
+        let mut binary = false;
+        let mut in_hunks = false;
+
+        for line in &chunk[1..] {
+            if in_hunks {
+                if line.starts_with('+') {
+                    additions += 1;
+                } else if line.starts_with('-') {
+                    deletions += 1;
+                }
+                continue;
+            }
+            if let Some(p) = line.strip_prefix("--- ") {
+                if p != "/dev/null" {
+                    old_path = Some(p.trim_start_matches("a/").to_string());
+                }
+            } else if let Some(p) = line.strip_prefix("+++ ") {
+                if p != "/dev/null" {
+                    new_path = Some(p.trim_start_matches("b/").to_string());
+                }
+            } else if let Some(p) = line.strip_prefix("rename from ") {
+                old_path = Some(p.to_string());
+                status = "renamed";
+            } else if let Some(p) = line.strip_prefix("rename to ") {
+                new_path = Some(p.to_string());
+            } else if line.starts_with("new file mode") {
+                status = "added";
+            } else if line.starts_with("deleted file mode") {
+                status = "deleted";
+            } else if line.starts_with("Binary files ") {
+                binary = true;
+            } else if line.starts_with("@@") {
+                in_hunks = true;
+            }
+        }
+
+        // 二进制文件没有 ---/+++ 行，只能从 diff --git 头里取路径
+        if old_path.is_none() && new_path.is_none() {
+            if let Some((a, b)) = header["diff --git ".len()..].split_once(" b/") {
+                old_path = Some(a.trim_start_matches("a/").to_string());
+                new_path = Some(b.to_string());
+            }
+        }
+
+        let path = new_path.clone().or_else(|| old_path.clone()).unwrap_or_default();
+        let old_path = if status == "renamed" { old_path } else { None };
+        files.push(serde_json::json!({
+            "path": path,
+            "oldPath": old_path,
+            "status": status,
+            "additions": additions,
+            "deletions": deletions,
+            "binary": binary,
+            "patch": chunk.join("\n"),
+        }));
+    }
+    files
+}
+
+fn show_stash(cwd: &str, index: usize) -> Result<serde_json::Value, String> {
+    let reference = stash_ref(index);
+    let raw = git_raw(
+        &["-c", "core.quotePath=false", "stash", "show", "-p", "--include-untracked", "--no-color", &reference],
+        cwd,
+    )?;
+    Ok(serde_json::json!({ "ref": reference, "files": split_patch(&raw) }))
+}
+
+#[tauri::command]
+pub async fn git_stash_save(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    message: Option<String>,
+    include_untracked: Option<bool>,
+    staged_only: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "stash").await;
+    save_stash(
+        &cwd,
+        message.as_deref(),
+        include_untracked.unwrap_or(false),
+        staged_only.unwrap_or(false),
+    )
+}
+
+#[tauri::command]
+pub async fn git_stash_list(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    list_stashes(&cwd)
+}
+
+#[tauri::command]
+pub async fn git_stash_show(state: State<'_, AppState>, index: usize) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    show_stash(&cwd, index)
+}
+
+#[tauri::command]
+pub async fn git_stash_apply(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    index: usize,
+    restore_index: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "stash-apply").await;
+    let reference = stash_ref(index);
+    let mut args = vec!["stash", "apply"];
+    if restore_index.unwrap_or(false) {
+        args.push("--index");
+    }
+    args.push(&reference);
+    git_conflicting(&args, &cwd)
+}
+
+#[tauri::command]
+pub async fn git_stash_pop(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    index: usize,
+    restore_index: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "stash-pop").await;
+    let reference = stash_ref(index);
+    let mut args = vec!["stash", "pop"];
+    if restore_index.unwrap_or(false) {
+        args.push("--index");
+    }
+    args.push(&reference);
+    // 冲突时 git 不会删除该 stash，结果里的 ok=false 即表示 stash 仍保留
+    git_conflicting(&args, &cwd)
+}
+
+#[tauri::command]
+pub async fn git_stash_drop(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    index: usize,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "stash-drop").await;
+    git(&["stash", "drop", &stash_ref(index)], &cwd)?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+#[tauri::command]
+pub async fn git_stash_branch(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    index: usize,
+    branch: String,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "stash-branch").await;
+    git_conflicting(&["stash", "branch", &branch, &stash_ref(index)], &cwd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo};
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn save_with_message_is_listed_with_branch() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        let branch = git(&["branch", "--show-current"], &cwd).unwrap();
+        fs::write(Path::new(&cwd).join("a.txt"), "v2").unwrap();
+
+        let saved = save_stash(&cwd, Some("half done"), false, false).unwrap();
+        assert_eq!(saved["created"], true);
+
+        let list = list_stashes(&cwd).unwrap();
+        assert_eq!(list[0]["index"], 0);
+        assert_eq!(list[0]["branch"], branch);
+        assert_eq!(list[0]["message"], "half done");
+        assert!(list[0]["timestamp"].as_i64().unwrap() > 0);
+    }
+
+    #[test]
+    fn save_without_changes_reports_not_created() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+
+        let saved = save_stash(&cwd, None, false, false).unwrap();
+
+        assert_eq!(saved["created"], false);
+        assert_eq!(list_stashes(&cwd).unwrap().as_array().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn staged_only_leaves_unstaged_changes_in_worktree() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a1", "init");
+        commit_file(&cwd, "b.txt", "b1", "init b");
+        fs::write(Path::new(&cwd).join("a.txt"), "a2").unwrap();
+        git(&["add", "a.txt"], &cwd).unwrap();
+        fs::write(Path::new(&cwd).join("b.txt"), "b2").unwrap();
+
+        save_stash(&cwd, None, false, true).unwrap();
+
+        assert_eq!(fs::read_to_string(Path::new(&cwd).join("a.txt")).unwrap(), "a1");
+        assert_eq!(fs::read_to_string(Path::new(&cwd).join("b.txt")).unwrap(), "b2");
+    }
+
+    #[test]
+    fn show_splits_diff_per_file_including_untracked() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "one\n", "init");
+        fs::write(Path::new(&cwd).join("a.txt"), "two\n").unwrap();
+        fs::write(Path::new(&cwd).join("new.txt"), "fresh\n").unwrap();
+        save_stash(&cwd, None, true, false).unwrap();
+
+        let shown = show_stash(&cwd, 0).unwrap();
+        let files = shown["files"].as_array().unwrap();
+
+        let a = files.iter().find(|f| f["path"] == "a.txt").unwrap();
+        assert_eq!(a["status"], "modified");
+        assert_eq!(a["additions"], 1);
+        assert_eq!(a["deletions"], 1);
+        let new = files.iter().find(|f| f["path"] == "new.txt").unwrap();
+        assert_eq!(new["status"], "added");
+    }
+
+    #[test]
+    fn apply_with_conflict_returns_structured_result() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "base\n", "init");
+        fs::write(Path::new(&cwd).join("a.txt"), "stashed\n").unwrap();
+        save_stash(&cwd, None, false, false).unwrap();
+        commit_file(&cwd, "a.txt", "committed\n", "diverge");
+
+        let result = git_conflicting(&["stash", "pop", &stash_ref(0)], &cwd).unwrap();
+
+        assert_eq!(result["ok"], false);
+        assert_eq!(result["conflicts"][0]["path"], "a.txt");
+        // pop 冲突时 stash 仍保留
+        assert_eq!(list_stashes(&cwd).unwrap().as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn branch_from_stash_checks_out_new_branch_with_changes() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        fs::write(Path::new(&cwd).join("a.txt"), "v2").unwrap();
+        save_stash(&cwd, None, false, false).unwrap();
+
+        let result = git_conflicting(&["stash", "branch", "from-stash", &stash_ref(0)], &cwd).unwrap();
+
+        assert_eq!(result["ok"], true);
+        assert_eq!(git(&["branch", "--show-current"], &cwd).unwrap(), "from-stash");
+        assert_eq!(fs::read_to_string(Path::new(&cwd).join("a.txt")).unwrap(), "v2");
+    }
+}
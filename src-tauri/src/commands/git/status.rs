@@ -0,0 +1,304 @@
+use tauri::State;
+use crate::state::AppState;
+use super::backend;
+use super::git_raw;
+
+/// porcelain v2 的单个状态字符转成前端使用的状态名，'.' 表示未改动
+pub(super) fn status_name(code: char) -> Option<&'static str> {
+    match code {
+        'M' | 'T' => Some("modified"),
+        'A' => Some("added"),
+        'D' => Some("deleted"),
+        'R' => Some("renamed"),
+        'C' => Some("copied"),
+        _ => None,
+    }
+}
+
+/// 未合并条目的 XY 对应的冲突类型
+pub(super) fn conflict_type(xy: &str) -> &'static str {
+    match xy {
+        "DD" => "both-deleted",
+        "AU" => "added-by-us",
+        "UD" => "deleted-by-them",
+        "UA" => "added-by-them",
+        "DU" => "deleted-by-us",
+        "AA" => "both-added",
+        _ => "both-modified",
+    }
+}
+
+/// `<sub>` 字段：非子模块为 "N..."，子模块为 "S<c><m><u>"
+fn parse_submodule(sub: &str) -> serde_json::Value {
+    let flags: Vec<char> = sub.chars().collect();
+    if flags.first() != Some(&'S') || flags.len() < 4 {
+        return serde_json::Value::Null;
+    }
+    serde_json::json!({
+        "commitChanged": flags[1] == 'C',
+        "trackedChanges": flags[2] == 'M',
+        "untrackedChanges": flags[3] == 'U',
+    })
+}
+
+pub(super) fn file_entry(
+    path: &str,
+    orig_path: Option<&str>,
+    xy: &str,
+    sub: &str,
+    status: &str,
+    staged: bool,
+) -> serde_json::Value {
+    let mut codes = xy.chars();
+    let index = codes.next().filter(|c| *c != '.').map(|c| c.to_string());
+    let worktree = codes.next().filter(|c| *c != '.').map(|c| c.to_string());
+    serde_json::json!({
+        "path": path,
+        "origPath": orig_path,
+        "status": status,
+        "staged": staged,
+        "index": index,
+        "worktree": worktree,
+        "conflict": serde_json::Value::Null,
+        "submodule": parse_submodule(sub),
+    })
+}
+
+/// 解析 `git status --porcelain=v2 -z --branch --show-stash` 的输出。
+/// 同时有暂存和未暂存改动的文件会拆成两条记录，分别对应暂存区和工作区。
+pub(super) fn parse_status_v2(raw: &str) -> serde_json::Value {
+    let mut branch = serde_json::json!({
+        "oid": null, "head": null, "upstream": null,
+        "ahead": 0, "behind": 0, "detached": false, "stashCount": 0,
+    });
+    let mut files: Vec<serde_json::Value> = Vec::new();
+
+    let mut records = raw.split('\0').filter(|r| !r.is_empty());
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => branch["oid"] = value.into(),
+                "branch.head" if value == "(detached)" => branch["detached"] = true.into(),
+                "branch.head" => branch["head"] = value.into(),
+                "branch.upstream" => branch["upstream"] = value.into(),
+                "branch.ab" => {
+                    for part in value.split_whitespace() {
+                        if let Some(n) = part.strip_prefix('+') {
+                            branch["ahead"] = n.parse::<i64>().unwrap_or(0).into();
+                        } else if let Some(n) = part.strip_prefix('-') {
+                            branch["behind"] = n.parse::<i64>().unwrap_or(0).into();
+                        }
+                    }
+                }
+                "stash" => branch["stashCount"] = value.parse::<i64>().unwrap_or(0).into(),
+                _ => {}
+            }
+            continue;
+        }
+
+        let (kind, rest) = record.split_at(1);
+        let rest = rest.trim_start();
+        match kind {
+            "1" | "2" => {
+                // 1 XY sub mH mI mW hH hI path
+                // 2 XY sub mH mI mW hH hI Xscore path \0 origPath
+                let field_count = if kind == "1" { 8 } else { 9 };
+                let fields: Vec<&str> = rest.splitn(field_count, ' ').collect();
+                if fields.len() < field_count {
+                    continue;
+                }
+                let xy = fields[0];
+                let sub = fields[1];
+                let path = fields[field_count - 1];
+                let orig_path = if kind == "2" { records.next() } else { None };
+
+                let mut codes = xy.chars();
+                let x = codes.next().unwrap_or('.');
+                let y = codes.next().unwrap_or('.');
+                if let Some(status) = status_name(x) {
+                    files.push(file_entry(path, orig_path, xy, sub, status, true));
+                }
+                if let Some(status) = status_name(y) {
+                    files.push(file_entry(path, orig_path, xy, sub, status, false));
+                }
+            }
+            "u" => {
+                // u XY sub m1 m2 m3 mW h1 h2 h3 path
+                let fields: Vec<&str> = rest.splitn(10, ' ').collect();
+                if fields.len() < 10 {
+                    continue;
+                }
+                let mut entry = file_entry(fields[9], None, fields[0], fields[1], "conflicted", false);
+                entry["conflict"] = conflict_type(fields[0]).into();
+                files.push(entry);
+            }
+            "?" => files.push(file_entry(rest, None, "..", "N...", "untracked", false)),
+            _ => {}
+        }
+    }
+
+    serde_json::json!({ "branch": branch, "files": files })
+}
+
+pub(super) fn read_status(cwd: &str) -> Result<serde_json::Value, String> {
+    let raw = git_raw(&["status", "--porcelain=v2", "-z", "--branch", "--show-stash"], cwd)?;
+    Ok(parse_status_v2(&raw))
+}
+
+#[tauri::command]
+pub async fn git_status(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let root = state.get_root();
+    let cwd = root.to_string_lossy().to_string();
+    backend::for_root(&root).status(&cwd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::git_conflicting;
+    use super::super::tests::{backends, commit_file, init_repo, run_git};
+    use std::fs;
+    use std::path::Path;
+
+    fn entries<'a>(status: &'a serde_json::Value, path: &str) -> Vec<&'a serde_json::Value> {
+        status["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|f| f["path"] == path)
+            .collect()
+    }
+
+    #[test]
+    fn paths_with_spaces_and_non_ascii_are_kept_verbatim() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "base.txt", "x", "init");
+        fs::write(Path::new(&cwd).join("my file 文件.txt"), "x").unwrap();
+
+        for backend in backends() {
+            let status = backend.status(&cwd).unwrap();
+            let found = entries(&status, "my file 文件.txt");
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0]["status"], "untracked");
+        }
+    }
+
+    #[test]
+    fn rename_keeps_original_path() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "old.txt", "content\n", "init");
+        run_git(&["mv", "old.txt", "new.txt"], &cwd);
+
+        for backend in backends() {
+            let status = backend.status(&cwd).unwrap();
+            let found = entries(&status, "new.txt");
+            assert_eq!(found[0]["status"], "renamed");
+            assert_eq!(found[0]["origPath"], "old.txt");
+            assert_eq!(found[0]["staged"], true);
+        }
+    }
+
+    #[test]
+    fn staged_and_modified_file_yields_index_and_worktree_entries() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "v1", "init");
+        fs::write(Path::new(&cwd).join("a.txt"), "v2").unwrap();
+        run_git(&["add", "a.txt"], &cwd);
+        fs::write(Path::new(&cwd).join("a.txt"), "v3").unwrap();
+
+        for backend in backends() {
+            let status = backend.status(&cwd).unwrap();
+            let found = entries(&status, "a.txt");
+            assert_eq!(found.len(), 2);
+            assert!(found.iter().any(|f| f["staged"] == true));
+            assert!(found.iter().any(|f| f["staged"] == false));
+            assert_eq!(found[0]["index"], "M");
+            assert_eq!(found[0]["worktree"], "M");
+        }
+    }
+
+    #[test]
+    fn conflicted_file_reports_conflict_type() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "base\n", "init");
+        run_git(&["switch", "-c", "other"], &cwd);
+        commit_file(&cwd, "a.txt", "theirs\n", "theirs");
+        run_git(&["switch", "-"], &cwd);
+        commit_file(&cwd, "a.txt", "ours\n", "ours");
+        let merged = git_conflicting(&["merge", "other"], &cwd).unwrap();
+        assert_eq!(merged["ok"], false);
+
+        for backend in backends() {
+            let status = backend.status(&cwd).unwrap();
+            let found = entries(&status, "a.txt");
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0]["status"], "conflicted");
+            assert_eq!(found[0]["conflict"], "both-modified");
+        }
+    }
+
+    #[test]
+    fn branch_header_reports_upstream_and_ahead_behind() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "1", "init");
+        let main = super::super::git(&["branch", "--show-current"], &cwd).unwrap();
+        run_git(&["switch", "-c", "topic", "--track", &main], &cwd);
+        commit_file(&cwd, "a.txt", "2", "ahead");
+
+        for backend in backends() {
+            let status = backend.status(&cwd).unwrap();
+            assert_eq!(status["branch"]["head"], "topic");
+            assert_eq!(status["branch"]["upstream"], main);
+            assert_eq!(status["branch"]["ahead"], 1);
+            assert_eq!(status["branch"]["behind"], 0);
+        }
+    }
+
+    #[test]
+    fn reading_status_does_not_rewrite_the_index() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a", "init");
+        // 只改 mtime，普通的 git status 会为此刷新并写回索引
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        fs::File::options().write(true).open(Path::new(&cwd).join("a.txt")).unwrap().set_modified(old).unwrap();
+        let index = Path::new(&cwd).join(".git").join("index");
+        let before = fs::read(&index).unwrap();
+
+        read_status(&cwd).unwrap();
+
+        assert_eq!(fs::read(&index).unwrap(), before);
+    }
+
+    #[test]
+    fn backends_report_the_same_status() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "1", "init");
+        commit_file(&cwd, "dir/b.txt", "1", "nested");
+        fs::write(Path::new(&cwd).join("a.txt"), "2").unwrap();
+        run_git(&["stash"], &cwd);
+        fs::write(Path::new(&cwd).join("a.txt"), "3").unwrap();
+        fs::remove_file(Path::new(&cwd).join("dir/b.txt")).unwrap();
+        fs::write(Path::new(&cwd).join("new.txt"), "n").unwrap();
+        run_git(&["add", "new.txt"], &cwd);
+        fs::create_dir_all(Path::new(&cwd).join("untracked/deep")).unwrap();
+        fs::write(Path::new(&cwd).join("untracked/deep/x.txt"), "x").unwrap();
+
+        let [cli, lib] = backends();
+        let expected = cli.status(&cwd).unwrap();
+        assert_eq!(expected["branch"]["stashCount"], 1);
+        assert_eq!(lib.status(&cwd).unwrap(), expected);
+    }
+
+    #[test]
+    fn parses_submodule_state_flags() {
+        let raw = "1 .M SC.U 160000 160000 160000 abc abc libs/sub\0";
+
+        let status = parse_status_v2(raw);
+
+        let sub = &status["files"][0]["submodule"];
+        assert_eq!(sub["commitChanged"], true);
+        assert_eq!(sub["trackedChanges"], false);
+        assert_eq!(sub["untrackedChanges"], true);
+    }
+}
@@ -0,0 +1,310 @@
+use std::path::Path;
+use tauri::{AppHandle, State};
+use crate::state::AppState;
+use super::askpass::AskpassBridge;
+use super::queue::GitQueue;
+use super::remote::run_task;
+use super::task::GitTasks;
+use super::{git, git_raw};
+
+/// .gitmodules 中的一个子模块
+#[derive(Debug, Default, Clone, PartialEq)]
+struct SubmoduleConfig {
+    name: String,
+    path: String,
+    url: String,
+    branch: Option<String>,
+}
+
+/// 解析 `git config -f .gitmodules --list` 的 "submodule.<name>.<key>=<value>"
+fn parse_gitmodules(raw: &str) -> Vec<SubmoduleConfig> {
+    let mut modules: Vec<SubmoduleConfig> = Vec::new();
+    for line in raw.lines() {
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let Some(rest) = key.strip_prefix("submodule.") else { continue };
+        // name 本身可能包含点，key 总是最后一段
+        let Some((name, field)) = rest.rsplit_once('.') else { continue };
+        let index = match modules.iter().position(|m| m.name == name) {
+            Some(i) => i,
+            None => {
+                modules.push(SubmoduleConfig { name: name.to_string(), ..Default::default() });
+                modules.len() - 1
+            }
+        };
+        let module = &mut modules[index];
+        match field {
+            "path" => module.path = value.to_string(),
+            "url" => module.url = value.to_string(),
+            "branch" => module.branch = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    modules.retain(|m| !m.path.is_empty());
+    modules
+}
+
+/// 树或索引中记录的子模块提交（gitlink，模式 160000）
+pub(super) fn gitlink_at(cwd: &str, treeish: Option<&str>, path: &str) -> Option<String> {
+    let raw = match treeish {
+        Some(treeish) => git(&["ls-tree", treeish, "--", path], cwd).ok()?,
+        None => git(&["ls-files", "-s", "--", path], cwd).ok()?,
+    };
+    let fields: Vec<&str> = raw.lines().next()?.split_whitespace().collect();
+    if fields.first() != Some(&"160000") {
+        return None;
+    }
+    // ls-tree: "160000 commit <sha>\t<path>"；ls-files -s: "160000 <sha> 0\t<path>"
+    let sha = if treeish.is_some() { fields.get(2) } else { fields.get(1) };
+    sha.map(|s| s.to_string())
+}
+
+/// 子模块工作目录当前检出的提交，未初始化时为 None
+pub(super) fn checked_out_commit(cwd: &str, path: &str) -> Option<String> {
+    let dir = Path::new(cwd).join(path);
+    if !dir.join(".git").exists() {
+        return None;
+    }
+    git(&["rev-parse", "HEAD"], &dir.to_string_lossy()).ok()
+}
+
+fn list_submodules(cwd: &str) -> Result<Vec<serde_json::Value>, String> {
+    if !Path::new(cwd).join(".gitmodules").exists() {
+        return Ok(Vec::new());
+    }
+    let raw = git_raw(&["config", "-f", ".gitmodules", "--list"], cwd)?;
+    Ok(parse_gitmodules(&raw)
+        .into_iter()
+        .map(|module| {
+            let dir = Path::new(cwd).join(&module.path);
+            let recorded = gitlink_at(cwd, None, &module.path);
+            let checked_out = checked_out_commit(cwd, &module.path);
+            let initialized = git(&["config", "--get", &format!("submodule.{}.url", module.name)], cwd).is_ok();
+            let dirty = checked_out.is_some()
+                && git(&["status", "--porcelain"], &dir.to_string_lossy()).is_ok_and(|s| !s.is_empty());
+            serde_json::json!({
+                "name": module.name,
+                "path": module.path,
+                "absolutePath": dir.to_string_lossy(),
+                "url": module.url,
+                "branch": module.branch,
+                "recorded": recorded,
+                "checkedOut": checked_out,
+                "initialized": initialized,
+                "populated": checked_out.is_some(),
+                "outOfDate": checked_out.is_some() && recorded != checked_out,
+                "dirty": dirty,
+            })
+        })
+        .collect())
+}
+
+fn commits_between(sub_dir: &str, from: &str, to: &str) -> Vec<serde_json::Value> {
+    let range = format!("{}..{}", from, to);
+    git(&["log", "--format=%H%x00%h%x00%s", &range], sub_dir)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.splitn(3, '\0').collect();
+            (parts.len() == 3).then(|| serde_json::json!({ "hash": parts[0], "shortHash": parts[1], "subject": parts[2] }))
+        })
+        .collect()
+}
+
+/// 子模块指针变化的差异：列出两次记录之间子模块中新增（和回退掉）的提交，而不是文本差异
+pub(super) fn submodule_diff(cwd: &str, path: &str, old: Option<&str>, new: Option<&str>) -> serde_json::Value {
+    let sub_dir = Path::new(cwd).join(path).to_string_lossy().to_string();
+    let (added, removed) = match (old, new) {
+        (Some(old), Some(new)) if checked_out_commit(cwd, path).is_some() => {
+            (commits_between(&sub_dir, old, new), commits_between(&sub_dir, new, old))
+        }
+        _ => (Vec::new(), Vec::new()),
+    };
+    let line = |commit: Option<&str>| commit.map(|c| format!("Subproject commit {}\n", c)).unwrap_or_default();
+    serde_json::json!({
+        "file": path,
+        "submodule": {
+            "oldCommit": old,
+            "newCommit": new,
+            "added": added,
+            "removed": removed,
+        },
+        "oldContent": line(old),
+        "newContent": line(new),
+    })
+}
+
+fn path_args<'a>(mut args: Vec<&'a str>, paths: &'a [String]) -> Vec<&'a str> {
+    if !paths.is_empty() {
+        args.push("--");
+        args.extend(paths.iter().map(|p| p.as_str()));
+    }
+    args
+}
+
+fn update_args(paths: &[String], init: bool, recursive: bool, remote: bool) -> Vec<String> {
+    let mut args: Vec<String> = vec!["submodule".into(), "update".into(), "--progress".into()];
+    if init {
+        args.push("--init".into());
+    }
+    if recursive {
+        args.push("--recursive".into());
+    }
+    // 跟随 .gitmodules 中配置的分支，而不是父仓库记录的提交
+    if remote {
+        args.push("--remote".into());
+    }
+    if !paths.is_empty() {
+        args.push("--".into());
+        args.extend(paths.iter().cloned());
+    }
+    args
+}
+
+#[tauri::command]
+pub async fn git_submodules(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    Ok(serde_json::json!(list_submodules(&cwd)?))
+}
+
+#[tauri::command]
+pub async fn git_submodule_init(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    paths: Option<Vec<String>>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "submodule-init").await;
+    let paths = paths.unwrap_or_default();
+    git(&path_args(vec!["submodule", "init"], &paths), &cwd)?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+/// 把 .gitmodules 中的 URL 同步到各子模块的配置
+#[tauri::command]
+pub async fn git_submodule_sync(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    paths: Option<Vec<String>>,
+    recursive: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "submodule-sync").await;
+    let paths = paths.unwrap_or_default();
+    let mut args = vec!["submodule", "sync"];
+    if recursive.unwrap_or(true) {
+        args.push("--recursive");
+    }
+    git(&path_args(args, &paths), &cwd)?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+/// 可能需要克隆或抓取，按远程任务运行以便显示进度和询问凭据
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn git_submodule_update(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    tasks: State<'_, GitTasks>,
+    askpass: State<'_, AskpassBridge>,
+    paths: Option<Vec<String>>,
+    init: Option<bool>,
+    recursive: Option<bool>,
+    remote: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "submodule-update").await;
+    let args = update_args(
+        &paths.unwrap_or_default(),
+        init.unwrap_or(true),
+        recursive.unwrap_or(true),
+        remote.unwrap_or(false),
+    );
+    run_task(&app, &tasks, &askpass, &cwd, &args, None, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo, run_git};
+
+    /// 父仓库中添加一个子模块，返回 (子模块源仓库的临时目录, 源仓库路径)
+    fn add_submodule(cwd: &str) -> (tempfile::TempDir, String) {
+        let (lib_dir, lib) = init_repo();
+        commit_file(&lib, "lib.txt", "v1\n", "lib v1");
+        commit_file(cwd, "a.txt", "a\n", "init");
+        run_git(&["-c", "protocol.file.allow=always", "submodule", "add", "-q", &lib, "libs/lib"], cwd);
+        run_git(&["commit", "-q", "-m", "add lib"], cwd);
+        (lib_dir, lib)
+    }
+
+    #[test]
+    fn parses_gitmodules_with_dotted_names() {
+        let raw = "submodule.libs/a.b.path=libs/a.b\nsubmodule.libs/a.b.url=https://example.com/a.git\nsubmodule.libs/a.b.branch=main\n";
+
+        assert_eq!(
+            parse_gitmodules(raw),
+            vec![SubmoduleConfig {
+                name: "libs/a.b".into(),
+                path: "libs/a.b".into(),
+                url: "https://example.com/a.git".into(),
+                branch: Some("main".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn lists_recorded_and_checked_out_commits() {
+        let (_dir, cwd) = init_repo();
+        let (_lib_dir, _lib) = add_submodule(&cwd);
+        let sub = Path::new(&cwd).join("libs/lib").to_string_lossy().to_string();
+        run_git(&["config", "user.email", "test@example.com"], &sub);
+        run_git(&["config", "user.name", "Test"], &sub);
+        commit_file(&sub, "lib.txt", "local\n", "local change");
+        std::fs::write(Path::new(&sub).join("lib.txt"), "dirty\n").unwrap();
+
+        let modules = list_submodules(&cwd).unwrap();
+
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0]["path"], "libs/lib");
+        assert_eq!(modules[0]["initialized"], true);
+        assert_eq!(modules[0]["outOfDate"], true);
+        assert_eq!(modules[0]["dirty"], true);
+        assert_eq!(modules[0]["checkedOut"], git(&["rev-parse", "HEAD"], &sub).unwrap().as_str());
+    }
+
+    #[test]
+    fn pointer_diff_lists_commit_range() {
+        let (_dir, cwd) = init_repo();
+        let (_lib_dir, lib) = add_submodule(&cwd);
+        let old = gitlink_at(&cwd, Some("HEAD"), "libs/lib").unwrap();
+        commit_file(&lib, "lib.txt", "v2\n", "lib v2");
+        let sub = Path::new(&cwd).join("libs/lib").to_string_lossy().to_string();
+        run_git(&["pull", "-q", "origin", "HEAD"], &sub);
+        let new = checked_out_commit(&cwd, "libs/lib").unwrap();
+
+        let diff = submodule_diff(&cwd, "libs/lib", Some(&old), Some(&new));
+
+        assert_eq!(diff["submodule"]["added"][0]["subject"], "lib v2");
+        assert_eq!(diff["submodule"]["removed"].as_array().unwrap().len(), 0);
+        assert_eq!(diff["newContent"], format!("Subproject commit {}\n", new));
+    }
+
+    #[test]
+    fn update_initializes_fresh_clone() {
+        let (_dir, cwd) = init_repo();
+        let (_lib_dir, _lib) = add_submodule(&cwd);
+        let (_clone_dir, clone) = init_repo();
+        run_git(&["-c", "protocol.file.allow=always", "pull", "-q", &cwd, "HEAD"], &clone);
+        assert!(list_submodules(&clone).unwrap()[0]["populated"] == false);
+
+        let args = update_args(&[], true, true, false);
+        let mut full: Vec<&str> = vec!["-c", "protocol.file.allow=always"];
+        full.extend(args.iter().map(|a| a.as_str()));
+        run_git(&full, &clone);
+
+        let modules = list_submodules(&clone).unwrap();
+        assert_eq!(modules[0]["populated"], true);
+        assert_eq!(modules[0]["outOfDate"], false);
+    }
+}
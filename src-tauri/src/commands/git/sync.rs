@@ -0,0 +1,225 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::commands::config::read_config;
+use crate::state::AppState;
+use super::queue::GitQueue;
+use super::task::{run_streamed, TaskError};
+use super::{git, git_cmd};
+
+/// 后台线程检查是否该 fetch 的间隔
+const TICK: Duration = Duration::from_secs(5);
+/// 单次自动 fetch 的超时
+const FETCH_TIMEOUT: Duration = Duration::from_secs(120);
+/// 离线退避的上限
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// 自动 fetch 的运行状态；窗口失去焦点时暂停
+pub struct AutoFetch {
+    focused: AtomicBool,
+}
+
+impl AutoFetch {
+    pub fn new() -> Self {
+        Self { focused: AtomicBool::new(true) }
+    }
+
+    pub fn set_focused(&self, focused: bool) {
+        self.focused.store(focused, Ordering::SeqCst);
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused.load(Ordering::SeqCst)
+    }
+}
+
+impl Default for AutoFetch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 当前分支相对上游的 ahead/behind；没有上游时回退到 origin/<branch>
+pub(crate) fn sync_status(cwd: &str) -> serde_json::Value {
+    let no_remote = serde_json::json!({ "ahead": 0, "behind": 0, "hasRemote": false });
+    let upstream = if git(&["rev-parse", "--abbrev-ref", "@{u}"], cwd).is_ok() {
+        "@{u}".to_string()
+    } else {
+        let branch = git(&["branch", "--show-current"], cwd).unwrap_or_default();
+        if branch.is_empty() {
+            return no_remote;
+        }
+        let remote_ref = format!("origin/{}", branch);
+        if git(&["rev-parse", &remote_ref], cwd).is_err() {
+            return no_remote;
+        }
+        remote_ref
+    };
+
+    let count = |range: String| {
+        git(&["rev-list", "--count", &range], cwd)
+            .and_then(|s| s.trim().parse::<i64>().map_err(|e| e.to_string()))
+            .unwrap_or(0)
+    };
+    let ahead = count(format!("{}..HEAD", upstream));
+    let behind = count(format!("HEAD..{}", upstream));
+    serde_json::json!({ "ahead": ahead, "behind": behind, "hasRemote": true })
+}
+
+/// 通知前端 ahead/behind 可能变化
+pub(crate) fn emit_sync_changed(app: &AppHandle, cwd: &str) {
+    let _ = app.emit("git-sync-changed", sync_status(cwd));
+}
+
+/// 连续失败时按 2 的幂退避，最多一小时（且不短于设定的间隔）
+fn backoff_delay(interval: Duration, failures: u32) -> Duration {
+    let delay = interval.saturating_mul(1 << failures.min(6));
+    delay.min(MAX_BACKOFF.max(interval))
+}
+
+/// 后台 fetch 用的 ssh 命令：在用户配置的命令后加上 BatchMode，遇到密码、口令或未知主机时直接失败，
+/// 不会卡住或弹出 askpass。只配置了 GIT_SSH（plink 等）时无法追加 ssh 选项，返回 None
+fn batch_ssh_command(cwd: &str) -> Option<String> {
+    let configured = std::env::var("GIT_SSH_COMMAND")
+        .ok()
+        .filter(|c| !c.trim().is_empty())
+        .or_else(|| git(&["config", "core.sshCommand"], cwd).ok().filter(|c| !c.is_empty()));
+    let base = match configured {
+        Some(command) => command,
+        None if std::env::var_os("GIT_SSH").is_some() => return None,
+        None => "ssh".into(),
+    };
+    Some(format!("{} -o BatchMode=yes", base))
+}
+
+/// 拉取所有远程；没有远程时返回 Ok(false)
+fn fetch_remotes(cwd: &str, cancel: &AtomicBool) -> Result<bool, String> {
+    if git(&["remote"], cwd)?.is_empty() {
+        return Ok(false);
+    }
+    let mut cmd = git_cmd(&["fetch", "--all", "--quiet"], cwd);
+    // 后台任务不能弹出凭据输入
+    cmd.env("GIT_TERMINAL_PROMPT", "0");
+    if let Some(ssh) = batch_ssh_command(cwd) {
+        cmd.env("GIT_SSH_COMMAND", ssh);
+    }
+    match run_streamed(cmd, cancel, Some(FETCH_TIMEOUT), &mut |_, _| {}) {
+        Ok(output) if output.success => Ok(true),
+        Ok(output) => Err(output.stderr),
+        Err(TaskError::TimedOut) => Err("fetch timed out".into()),
+        Err(TaskError::Cancelled) => Err("fetch cancelled".into()),
+        Err(TaskError::Spawn(e)) => Err(e),
+    }
+}
+
+/// 启动后台 fetch 线程，间隔取自当前工作区的 `git.autoFetchInterval`
+pub fn start_auto_fetch(app: AppHandle) {
+    std::thread::spawn(move || {
+        let never_cancel = AtomicBool::new(false);
+        let mut root = PathBuf::new();
+        let mut last_attempt: Option<Instant> = None;
+        let mut failures = 0u32;
+
+        loop {
+            std::thread::sleep(TICK);
+
+            let current = app.state::<AppState>().get_root();
+            if current != root {
+                root = current;
+                last_attempt = None;
+                failures = 0;
+            }
+            let interval = read_config(&root).git.auto_fetch_interval;
+            if interval == 0 || !app.state::<AutoFetch>().is_focused() {
+                continue;
+            }
+            let delay = backoff_delay(Duration::from_secs(interval), failures);
+            if last_attempt.is_some_and(|t| t.elapsed() < delay) {
+                continue;
+            }
+            let cwd = root.to_string_lossy().to_string();
+            let queue = app.state::<GitQueue>();
+            // 有写操作在执行或排队时跳过这一轮，下个 tick 再试
+            if queue.is_busy(&cwd) {
+                continue;
+            }
+            last_attempt = Some(Instant::now());
+
+            match fetch_remotes(&cwd, &never_cancel) {
+                Ok(true) => {
+                    failures = 0;
+                    emit_sync_changed(&app, &cwd);
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    failures = failures.saturating_add(1);
+                    eprintln!("[auto-fetch] failed ({} in a row): {}", failures, e.trim());
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo, run_git};
+    use tempfile::tempdir;
+
+    #[test]
+    fn backoff_doubles_and_is_capped() {
+        let minute = Duration::from_secs(60);
+
+        assert_eq!(backoff_delay(minute, 0), minute);
+        assert_eq!(backoff_delay(minute, 2), minute * 4);
+        assert_eq!(backoff_delay(minute, 30), MAX_BACKOFF);
+        assert_eq!(backoff_delay(MAX_BACKOFF * 2, 3), MAX_BACKOFF * 2);
+    }
+
+    #[test]
+    fn background_ssh_keeps_configured_command_and_never_prompts() {
+        let (_dir, cwd) = init_repo();
+        if std::env::var_os("GIT_SSH_COMMAND").is_some() {
+            return;
+        }
+        if std::env::var_os("GIT_SSH").is_none() {
+            assert_eq!(batch_ssh_command(&cwd).as_deref(), Some("ssh -o BatchMode=yes"));
+        }
+
+        run_git(&["config", "core.sshCommand", "ssh -i ~/.ssh/work"], &cwd);
+        assert_eq!(batch_ssh_command(&cwd).as_deref(), Some("ssh -i ~/.ssh/work -o BatchMode=yes"));
+    }
+
+    #[test]
+    fn fetch_without_remote_is_skipped() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "init");
+
+        assert_eq!(fetch_remotes(&cwd, &AtomicBool::new(false)), Ok(false));
+    }
+
+    #[test]
+    fn unreachable_remote_fails_and_status_reflects_fetched_commits() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "init");
+        run_git(&["remote", "add", "origin", "/nonexistent/openloom-remote.git"], &cwd);
+        assert!(fetch_remotes(&cwd, &AtomicBool::new(false)).is_err());
+
+        let remote_dir = tempdir().unwrap();
+        let bare = remote_dir.path().join("origin.git").to_string_lossy().to_string();
+        run_git(&["init", "--bare", &bare], &cwd);
+        run_git(&["remote", "set-url", "origin", &bare], &cwd);
+        run_git(&["push", "-u", "origin", "HEAD"], &cwd);
+        let other = remote_dir.path().join("other").to_string_lossy().to_string();
+        run_git(&["clone", &bare, &other], &cwd);
+        run_git(&["config", "user.email", "other@example.com"], &other);
+        run_git(&["config", "user.name", "Other"], &other);
+        commit_file(&other, "b.txt", "b\n", "theirs");
+        run_git(&["push"], &other);
+
+        assert_eq!(sync_status(&cwd)["behind"], 0);
+        assert_eq!(fetch_remotes(&cwd, &AtomicBool::new(false)), Ok(true));
+        assert_eq!(sync_status(&cwd)["behind"], 1);
+    }
+}
@@ -0,0 +1,358 @@
+use tauri::{AppHandle, State};
+use crate::state::AppState;
+use super::askpass::AskpassBridge;
+use super::queue::GitQueue;
+use super::remote::run_task;
+use super::task::GitTasks;
+use super::{check_rev, git, git_raw};
+
+const TAG_FORMAT: &str = "--format=%(refname:short)%00%(objecttype)%00%(objectname)%00%(*objectname)%00%(creatordate:iso-strict)%00%(taggername)%00%(taggeremail)%00%(contents:subject)";
+
+/// 变更日志中的分组顺序：(类型, 标题)
+const CHANGELOG_GROUPS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance Improvements"),
+    ("refactor", "Code Refactoring"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Build System"),
+    ("ci", "Continuous Integration"),
+    ("style", "Styles"),
+    ("chore", "Chores"),
+    ("revert", "Reverts"),
+];
+
+fn list_tags(cwd: &str) -> Result<Vec<serde_json::Value>, String> {
+    let raw = git_raw(&["for-each-ref", "--sort=-creatordate", TAG_FORMAT, "refs/tags"], cwd)?;
+    Ok(raw
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split('\0').collect();
+            if parts.len() < 8 {
+                return None;
+            }
+            // 附注标签指向 tag 对象，%(*objectname) 是它最终指向的提交
+            let annotated = parts[1] == "tag";
+            let target = if annotated { parts[3] } else { parts[2] };
+            Some(serde_json::json!({
+                "name": parts[0],
+                "annotated": annotated,
+                "target": target,
+                "date": parts[4],
+                "tagger": if annotated { Some(parts[5]) } else { None },
+                "taggerEmail": if annotated { Some(parts[6].trim_matches(|c| c == '<' || c == '>')) } else { None },
+                "message": if annotated { Some(parts[7]) } else { None },
+            }))
+        })
+        .collect())
+}
+
+/// 有 message 时创建附注标签，否则创建轻量标签
+fn create_tag(cwd: &str, name: &str, target: Option<&str>, message: Option<&str>) -> Result<(), String> {
+    check_rev(name)?;
+    let mut args = vec!["tag"];
+    if let Some(message) = message.filter(|m| !m.trim().is_empty()) {
+        args.extend(["-a", name, "-m", message]);
+    } else {
+        args.push(name);
+    }
+    if let Some(target) = target.filter(|t| !t.is_empty()) {
+        args.push(check_rev(target)?);
+    }
+    git(&args, cwd).map(|_| ())
+}
+
+/// 删除标签，指定 remote 时先删远程再删本地：远程删除失败时本地标签还在，界面可以重试。
+/// 只有删除本地标签时才进仓库队列，推送等待网络和凭据期间不挡其他操作
+async fn delete_tag(
+    queue: &GitQueue,
+    cwd: &str,
+    name: &str,
+    remote: Option<&str>,
+    push: &mut dyn FnMut(&[String]) -> Result<serde_json::Value, String>,
+) -> Result<serde_json::Value, String> {
+    check_rev(name)?;
+    let mut result = serde_json::json!({ "ok": true });
+    if let Some(remote) = remote.filter(|r| !r.is_empty()) {
+        let args = vec!["push".into(), "--progress".into(), remote.to_string(), "--delete".into(), format!("refs/tags/{}", name)];
+        result = match push(&args) {
+            Ok(pushed) if pushed["ok"] != true => return Ok(pushed),
+            Ok(pushed) => pushed,
+            // 远程上本来就没有这个标签
+            Err(e) if e.contains("remote ref does not exist") => result,
+            Err(e) => return Err(e),
+        };
+    }
+    let _op = queue.begin(cwd, "tag-delete").await;
+    git(&["tag", "-d", name], cwd)?;
+    Ok(result)
+}
+
+/// 一条 Conventional Commit 提交信息的组成部分
+#[derive(Debug, PartialEq)]
+struct ConventionalCommit<'a> {
+    kind: &'a str,
+    scope: Option<&'a str>,
+    breaking: bool,
+    description: &'a str,
+}
+
+/// 解析 "type(scope)!: description"，不符合格式返回 None
+fn parse_conventional<'a>(subject: &'a str, body: &str) -> Option<ConventionalCommit<'a>> {
+    let (head, description) = subject.split_once(": ")?;
+    let (head, bang) = match head.strip_suffix('!') {
+        Some(h) => (h, true),
+        None => (head, false),
+    };
+    let (kind, scope) = match head.split_once('(') {
+        Some((kind, rest)) => (kind, Some(rest.strip_suffix(')')?)),
+        None => (head, None),
+    };
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let breaking = bang || body.contains("BREAKING CHANGE:") || body.contains("BREAKING-CHANGE:");
+    Some(ConventionalCommit { kind, scope: scope.filter(|s| !s.is_empty()), breaking, description: description.trim() })
+}
+
+/// 没有指定起点时使用 to 之前最近的标签，没有更早的标签则包含全部历史
+fn previous_tag(cwd: &str, to: &str) -> Option<String> {
+    git(&["describe", "--tags", "--abbrev=0", &format!("{}^", to)], cwd).ok()
+}
+
+fn build_changelog(cwd: &str, from: Option<&str>, to: &str) -> Result<serde_json::Value, String> {
+    check_rev(to)?;
+    let from = match from.filter(|f| !f.is_empty()) {
+        Some(from) => Some(check_rev(from)?.to_string()),
+        None => previous_tag(cwd, to),
+    };
+    let range = match &from {
+        Some(from) => format!("{}..{}", from, to),
+        None => to.to_string(),
+    };
+    let raw = git_raw(&["log", "--no-merges", "--format=%H%x00%h%x00%s%x00%b%x1e", &range], cwd)?;
+
+    let mut groups: Vec<(String, String, Vec<serde_json::Value>)> = CHANGELOG_GROUPS
+        .iter()
+        .map(|(kind, title)| (kind.to_string(), title.to_string(), Vec::new()))
+        .collect();
+    let mut breaking: Vec<serde_json::Value> = Vec::new();
+    let mut other: Vec<serde_json::Value> = Vec::new();
+
+    for record in raw.split('\x1e').map(|r| r.trim_start_matches('\n')).filter(|r| !r.is_empty()) {
+        let parts: Vec<&str> = record.splitn(4, '\0').collect();
+        if parts.len() < 4 {
+            continue;
+        }
+        let (hash, short_hash, subject, body) = (parts[0], parts[1], parts[2], parts[3]);
+        let parsed = parse_conventional(subject, body);
+        let entry = serde_json::json!({
+            "hash": hash,
+            "shortHash": short_hash,
+            "type": parsed.as_ref().map(|c| c.kind),
+            "scope": parsed.as_ref().and_then(|c| c.scope),
+            "description": parsed.as_ref().map(|c| c.description).unwrap_or(subject),
+            "breaking": parsed.as_ref().is_some_and(|c| c.breaking),
+        });
+        if entry["breaking"] == true {
+            breaking.push(entry.clone());
+        }
+        match parsed.and_then(|c| groups.iter_mut().find(|g| g.0 == c.kind)) {
+            Some(group) => group.2.push(entry),
+            None => other.push(entry),
+        }
+    }
+
+    let mut sections: Vec<(String, String, Vec<serde_json::Value>)> = Vec::new();
+    if !breaking.is_empty() {
+        sections.push(("breaking".into(), "BREAKING CHANGES".into(), breaking));
+    }
+    sections.extend(groups.into_iter().filter(|g| !g.2.is_empty()));
+    if !other.is_empty() {
+        sections.push(("other".into(), "Other Changes".into(), other));
+    }
+
+    let mut markdown = match &from {
+        Some(from) => format!("## {} ({}...{})\n", to, from, to),
+        None => format!("## {}\n", to),
+    };
+    for (_, title, commits) in &sections {
+        markdown.push_str(&format!("\n### {}\n\n", title));
+        for commit in commits {
+            let scope = commit["scope"].as_str().map(|s| format!("**{}:** ", s)).unwrap_or_default();
+            markdown.push_str(&format!(
+                "- {}{} ({})\n",
+                scope,
+                commit["description"].as_str().unwrap_or(""),
+                commit["shortHash"].as_str().unwrap_or("")
+            ));
+        }
+    }
+
+    let groups: Vec<serde_json::Value> = sections
+        .into_iter()
+        .map(|(kind, title, commits)| serde_json::json!({ "type": kind, "title": title, "commits": commits }))
+        .collect();
+    Ok(serde_json::json!({ "from": from, "to": to, "groups": groups, "markdown": markdown }))
+}
+
+#[tauri::command]
+pub async fn git_tags(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    Ok(serde_json::json!(list_tags(&cwd)?))
+}
+
+#[tauri::command]
+pub async fn git_tag_create(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    name: String,
+    target: Option<String>,
+    message: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "tag-create").await;
+    create_tag(&cwd, &name, target.as_deref(), message.as_deref())?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+/// 删除本地标签；指定 remote 时先删除远程上的同名标签
+#[tauri::command]
+pub async fn git_tag_delete(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    tasks: State<'_, GitTasks>,
+    askpass: State<'_, AskpassBridge>,
+    name: String,
+    remote: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    delete_tag(&queue, &cwd, &name, remote.as_deref(), &mut |args| run_task(&app, &tasks, &askpass, &cwd, args, None, None)).await
+}
+
+/// 推送单个标签；不指定 name 时推送全部标签。推送不动索引和本地引用，不进仓库队列
+#[tauri::command]
+pub async fn git_tag_push(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    tasks: State<'_, GitTasks>,
+    askpass: State<'_, AskpassBridge>,
+    name: Option<String>,
+    remote: Option<String>,
+    task_id: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let remote = remote.filter(|r| !r.is_empty()).unwrap_or_else(|| "origin".into());
+    let mut args: Vec<String> = vec!["push".into(), "--progress".into(), remote];
+    match name.filter(|n| !n.is_empty()) {
+        Some(name) => args.push(format!("refs/tags/{}", name)),
+        None => args.push("--tags".into()),
+    }
+    run_task(&app, &tasks, &askpass, &cwd, &args, task_id, None)
+}
+
+/// 生成 from..to 之间按 Conventional Commit 类型分组的 Markdown 变更日志草稿
+#[tauri::command]
+pub async fn git_changelog(
+    state: State<'_, AppState>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let to = to.filter(|t| !t.is_empty()).unwrap_or_else(|| "HEAD".into());
+    build_changelog(&cwd, from.as_deref(), &to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo, run_git};
+
+    #[test]
+    fn lists_lightweight_and_annotated_tags() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "init");
+        let head = git(&["rev-parse", "HEAD"], &cwd).unwrap();
+        create_tag(&cwd, "light", None, None).unwrap();
+        create_tag(&cwd, "v1.0.0", Some(&head), Some("Release 1.0.0")).unwrap();
+
+        let tags = list_tags(&cwd).unwrap();
+
+        let light = tags.iter().find(|t| t["name"] == "light").unwrap();
+        let annotated = tags.iter().find(|t| t["name"] == "v1.0.0").unwrap();
+        assert_eq!(light["annotated"], false);
+        assert_eq!(light["target"], head.as_str());
+        assert_eq!(annotated["annotated"], true);
+        assert_eq!(annotated["target"], head.as_str());
+        assert_eq!(annotated["message"], "Release 1.0.0");
+        assert_eq!(annotated["taggerEmail"], "test@example.com");
+    }
+
+    #[tokio::test]
+    async fn remote_delete_failure_keeps_the_local_tag() {
+        let queue = GitQueue::new();
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "init");
+        let remote_dir = tempfile::tempdir().unwrap();
+        let bare = remote_dir.path().join("origin.git").to_string_lossy().to_string();
+        run_git(&["init", "--bare", &bare], &cwd);
+        run_git(&["remote", "add", "origin", &bare], &cwd);
+        create_tag(&cwd, "v1", None, None).unwrap();
+        run_git(&["push", "origin", "v1"], &cwd);
+        let mut push = |args: &[String]| {
+            let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+            git(&args, &cwd).map(|_| serde_json::json!({ "ok": true }))
+        };
+
+        run_git(&["remote", "set-url", "origin", "/nonexistent/openloom-remote.git"], &cwd);
+        assert!(delete_tag(&queue, &cwd, "v1", Some("origin"), &mut push).await.is_err());
+        assert!(git(&["rev-parse", "--verify", "refs/tags/v1"], &cwd).is_ok());
+
+        run_git(&["remote", "set-url", "origin", &bare], &cwd);
+        delete_tag(&queue, &cwd, "v1", Some("origin"), &mut push).await.unwrap();
+        assert!(git(&["rev-parse", "--verify", "refs/tags/v1"], &cwd).is_err());
+        assert!(git(&["ls-remote", "--tags", "origin"], &cwd).unwrap().is_empty());
+
+        // 远程已经没有这个标签时照样删除本地的
+        create_tag(&cwd, "v1", None, None).unwrap();
+        delete_tag(&queue, &cwd, "v1", Some("origin"), &mut push).await.unwrap();
+        assert!(git(&["rev-parse", "--verify", "refs/tags/v1"], &cwd).is_err());
+    }
+
+    #[test]
+    fn parses_conventional_subjects() {
+        assert_eq!(
+            parse_conventional("feat(api)!: drop v1 endpoints", ""),
+            Some(ConventionalCommit { kind: "feat", scope: Some("api"), breaking: true, description: "drop v1 endpoints" })
+        );
+        assert_eq!(
+            parse_conventional("fix: handle empty input", "BREAKING CHANGE: input is required"),
+            Some(ConventionalCommit { kind: "fix", scope: None, breaking: true, description: "handle empty input" })
+        );
+        assert_eq!(parse_conventional("Update README", ""), None);
+        assert_eq!(parse_conventional("Merge branch 'x': y", ""), None);
+    }
+
+    #[test]
+    fn changelog_groups_commits_between_tags() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "chore: init");
+        create_tag(&cwd, "v1.0.0", None, Some("v1")).unwrap();
+        commit_file(&cwd, "b.txt", "b\n", "feat(ui): add dark mode");
+        commit_file(&cwd, "c.txt", "c\n", "fix: crash on start");
+        commit_file(&cwd, "d.txt", "d\n", "tweak things");
+        create_tag(&cwd, "v1.1.0", None, None).unwrap();
+
+        let changelog = build_changelog(&cwd, None, "v1.1.0").unwrap();
+
+        assert_eq!(changelog["from"], "v1.0.0");
+        let titles: Vec<&str> = changelog["groups"].as_array().unwrap().iter().map(|g| g["title"].as_str().unwrap()).collect();
+        assert_eq!(titles, vec!["Features", "Bug Fixes", "Other Changes"]);
+        let markdown = changelog["markdown"].as_str().unwrap();
+        assert!(markdown.starts_with("## v1.1.0 (v1.0.0...v1.1.0)\n"));
+        assert!(markdown.contains("### Features\n\n- **ui:** add dark mode ("));
+        assert!(!markdown.contains("init"));
+    }
+}
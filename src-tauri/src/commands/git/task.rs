@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+/// 取消时先发 SIGTERM 让 git 清理锁文件，超过该时间仍未退出再强杀
+const KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// 正在运行、可被取消的长时间 git 任务（commit 钩子、push、pull、fetch 等）
+pub struct GitTasks {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    next_id: AtomicU64,
+}
+
+impl GitTasks {
+    pub fn new() -> Self {
+        Self {
+            running: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// 登记任务并返回它的取消标记；未指定 id 时自动生成
+    pub(super) fn register(&self, task_id: Option<String>) -> (String, Arc<AtomicBool>) {
+        let id = task_id
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| format!("git-task-{}", self.next_id.fetch_add(1, Ordering::SeqCst)));
+        let flag = Arc::new(AtomicBool::new(false));
+        self.running.lock().unwrap().insert(id.clone(), flag.clone());
+        (id, flag)
+    }
+
+    pub(super) fn finish(&self, task_id: &str) {
+        self.running.lock().unwrap().remove(task_id);
+    }
+
+    pub(super) fn cancel(&self, task_id: &str) -> bool {
+        match self.running.lock().unwrap().get(task_id) {
+            Some(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Default for GitTasks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitTaskOutputPayload {
+    pub task_id: String,
+    pub stream: &'static str,
+    pub line: String,
+}
+
+/// 把任务输出的每一行作为 `git-task-output` 事件发给前端
+pub(super) fn emit_line(app: &AppHandle, task_id: &str, stream: &'static str, line: &str) {
+    let _ = app.emit(
+        "git-task-output",
+        GitTaskOutputPayload { task_id: task_id.to_string(), stream, line: line.to_string() },
+    );
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum TaskError {
+    Cancelled,
+    TimedOut,
+    Spawn(String),
+}
+
+pub(super) struct TaskOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// 按 \n 或 \r 切行转发（--progress 用 \r 刷新同一行）
+fn forward_lines(mut reader: impl Read, stream: &'static str, tx: mpsc::Sender<(&'static str, String)>) {
+    let mut buf = [0u8; 4096];
+    let mut pending: Vec<u8> = Vec::new();
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        for &b in &buf[..n] {
+            if b == b'\n' || b == b'\r' {
+                if !pending.is_empty() {
+                    let line = String::from_utf8_lossy(&pending).to_string();
+                    pending.clear();
+                    if tx.send((stream, line)).is_err() {
+                        return;
+                    }
+                }
+            } else {
+                pending.push(b);
+            }
+        }
+    }
+    if !pending.is_empty() {
+        let _ = tx.send((stream, String::from_utf8_lossy(&pending).to_string()));
+    }
+}
+
+/// 杀掉 git 以及它启动的钩子、凭据助手等子进程。
+/// unix 上 git 在独立的进程组里，整组先 SIGTERM（git 收到后会删除自己的 index.lock），再 SIGKILL 兜底
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        let group = child.id() as libc::pid_t;
+        unsafe { libc::killpg(group, libc::SIGTERM) };
+        let deadline = Instant::now() + KILL_GRACE;
+        while Instant::now() < deadline && matches!(child.try_wait(), Ok(None)) {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        unsafe { libc::killpg(group, libc::SIGKILL) };
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &child.id().to_string()])
+            .creation_flags(super::CREATE_NO_WINDOW)
+            .status();
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// 运行命令并逐行回调输出，支持超时和取消；取消或超时时会杀掉整个进程树
+pub(super) fn run_streamed(
+    mut cmd: Command,
+    cancel: &AtomicBool,
+    timeout: Option<Duration>,
+    on_line: &mut dyn FnMut(&'static str, &str),
+) -> Result<TaskOutput, TaskError> {
+    cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    let mut child = cmd.spawn().map_err(|e| TaskError::Spawn(e.to_string()))?;
+
+    let (tx, rx) = mpsc::channel();
+    if let Some(out) = child.stdout.take() {
+        let tx = tx.clone();
+        std::thread::spawn(move || forward_lines(out, "stdout", tx));
+    }
+    if let Some(err) = child.stderr.take() {
+        let tx = tx.clone();
+        std::thread::spawn(move || forward_lines(err, "stderr", tx));
+    }
+    drop(tx);
+
+    let deadline = timeout.map(|t| Instant::now() + t);
+    let mut stdout: Vec<String> = Vec::new();
+    let mut stderr: Vec<String> = Vec::new();
+    loop {
+        match rx.recv_timeout(Duration::from_millis(50)) {
+            Ok((stream, line)) => {
+                on_line(stream, &line);
+                if stream == "stdout" {
+                    stdout.push(line);
+                } else {
+                    stderr.push(line);
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+        let error = if cancel.load(Ordering::SeqCst) {
+            Some(TaskError::Cancelled)
+        } else if deadline.is_some_and(|d| Instant::now() >= d) {
+            Some(TaskError::TimedOut)
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            kill_tree(&mut child);
+            return Err(error);
+        }
+    }
+
+    let status = child.wait().map_err(|e| TaskError::Spawn(e.to_string()))?;
+    Ok(TaskOutput {
+        success: status.success(),
+        stdout: stdout.join("\n"),
+        stderr: stderr.join("\n"),
+    })
+}
+
+#[tauri::command]
+pub async fn git_cancel_task(
+    tasks: State<'_, GitTasks>,
+    task_id: String,
+) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({ "ok": tasks.cancel(&task_id) }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn carriage_returns_split_progress_lines() {
+        let (tx, rx) = mpsc::channel();
+        forward_lines(&b"Counting: 10%\rCounting: 100%\r\ndone\n"[..], "stderr", tx);
+
+        let lines: Vec<String> = rx.iter().map(|(_, l)| l).collect();
+
+        assert_eq!(lines, vec!["Counting: 10%", "Counting: 100%", "done"]);
+    }
+
+    #[test]
+    fn cancelled_task_is_removed_from_registry_after_finish() {
+        let tasks = GitTasks::new();
+        let (id, flag) = tasks.register(None);
+
+        assert!(tasks.cancel(&id));
+        assert!(flag.load(Ordering::SeqCst));
+        tasks.finish(&id);
+        assert!(!tasks.cancel(&id));
+    }
+}
@@ -0,0 +1,339 @@
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use tauri::{AppHandle, State};
+use crate::pty::{PtyManager, ShellType};
+use crate::state::AppState;
+use super::queue::GitQueue;
+use super::status::read_status;
+use super::{git, git_raw};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddWorktreeOptions {
+    /// 不填时放在主工作树旁边：<仓库名>-<分支名>
+    pub path: Option<String>,
+    /// 检出已有分支
+    pub branch: Option<String>,
+    /// 创建新分支并检出（-b）
+    pub new_branch: Option<String>,
+    /// 新分支的起点，默认 HEAD
+    pub start_point: Option<String>,
+    /// 不关联分支，直接分离 HEAD 检出 start_point
+    #[serde(default)]
+    pub detach: bool,
+}
+
+/// 解析 `git worktree list --porcelain -z`：每个工作树是一组以 NUL 结尾的字段，组之间用空字段分隔
+fn parse_worktrees(raw: &str) -> Vec<serde_json::Value> {
+    let mut worktrees: Vec<serde_json::Value> = Vec::new();
+    let mut current: Option<serde_json::Value> = None;
+    for field in raw.split('\0') {
+        if field.is_empty() {
+            worktrees.extend(current.take());
+            continue;
+        }
+        let (key, value) = field.split_once(' ').unwrap_or((field, ""));
+        if key == "worktree" {
+            worktrees.extend(current.take());
+            current = Some(serde_json::json!({
+                "path": value,
+                "head": null,
+                "branch": null,
+                "detached": false,
+                "bare": false,
+                "locked": false,
+                "lockReason": null,
+                "prunable": false,
+                "main": worktrees.is_empty(),
+            }));
+            continue;
+        }
+        let Some(entry) = current.as_mut() else { continue };
+        match key {
+            "HEAD" => entry["head"] = value.into(),
+            "branch" => entry["branch"] = value.strip_prefix("refs/heads/").unwrap_or(value).into(),
+            "detached" => entry["detached"] = true.into(),
+            "bare" => entry["bare"] = true.into(),
+            "locked" => {
+                entry["locked"] = true.into();
+                if !value.is_empty() {
+                    entry["lockReason"] = value.into();
+                }
+            }
+            "prunable" => entry["prunable"] = true.into(),
+            _ => {}
+        }
+    }
+    worktrees.extend(current);
+    worktrees
+}
+
+fn list_worktrees(cwd: &str) -> Result<Vec<serde_json::Value>, String> {
+    let raw = git_raw(&["worktree", "list", "--porcelain", "-z"], cwd)?;
+    Ok(parse_worktrees(&raw))
+}
+
+/// 分支名中的 / 等字符不适合做目录名
+fn default_worktree_path(cwd: &str, name: &str) -> Result<PathBuf, String> {
+    let worktrees = list_worktrees(cwd)?;
+    let main = worktrees
+        .first()
+        .and_then(|w| w["path"].as_str())
+        .map(PathBuf::from)
+        .ok_or("no main worktree")?;
+    let repo_name = main.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let dir_name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '-' })
+        .collect();
+    let parent = main.parent().unwrap_or(Path::new("."));
+    Ok(parent.join(format!("{}-{}", repo_name, dir_name)))
+}
+
+fn add_worktree(cwd: &str, options: &AddWorktreeOptions) -> Result<serde_json::Value, String> {
+    let new_branch = options.new_branch.as_deref().filter(|b| !b.is_empty());
+    let branch = options.branch.as_deref().filter(|b| !b.is_empty());
+    let start_point = options.start_point.as_deref().filter(|s| !s.is_empty());
+    let name = new_branch.or(branch).or(start_point).unwrap_or("worktree");
+
+    let path = match options.path.as_deref().filter(|p| !p.is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => default_worktree_path(cwd, name)?,
+    };
+    let path = path.to_string_lossy().to_string();
+
+    let mut args = vec!["worktree", "add"];
+    if let Some(new_branch) = new_branch {
+        args.extend(["-b", new_branch, &path]);
+        args.extend(start_point);
+    } else if options.detach || branch.is_none() {
+        args.extend(["--detach", &path]);
+        args.extend(start_point);
+    } else {
+        args.push(&path);
+        args.extend(branch);
+    }
+    git(&args, cwd)?;
+
+    let added = list_worktrees(cwd)?
+        .into_iter()
+        .find(|w| w["path"].as_str().is_some_and(|p| same_path(p, &path)));
+    Ok(serde_json::json!({ "ok": true, "path": path, "worktree": added }))
+}
+
+fn same_path(a: &str, b: &str) -> bool {
+    let canonical = |p: &str| Path::new(p).canonicalize().unwrap_or_else(|_| PathBuf::from(p));
+    canonical(a) == canonical(b)
+}
+
+/// 每个工作树的分支、ahead/behind 和改动数量
+fn summarize(cwd: &str) -> Result<Vec<serde_json::Value>, String> {
+    Ok(list_worktrees(cwd)?
+        .into_iter()
+        .map(|mut worktree| {
+            let path = worktree["path"].as_str().unwrap_or("").to_string();
+            let readable = worktree["bare"] == false && worktree["prunable"] == false;
+            let status = if readable { read_status(&path).ok() } else { None };
+            worktree["status"] = match status {
+                Some(status) => {
+                    let files = status["files"].as_array().cloned().unwrap_or_default();
+                    let count = |pred: &dyn Fn(&serde_json::Value) -> bool| files.iter().filter(|f| pred(f)).count();
+                    serde_json::json!({
+                        "ahead": status["branch"]["ahead"],
+                        "behind": status["branch"]["behind"],
+                        "upstream": status["branch"]["upstream"],
+                        "staged": count(&|f| f["staged"] == true),
+                        "unstaged": count(&|f| f["staged"] == false && f["status"] != "untracked" && f["status"] != "conflicted"),
+                        "untracked": count(&|f| f["status"] == "untracked"),
+                        "conflicted": count(&|f| f["status"] == "conflicted"),
+                        "clean": files.is_empty(),
+                    })
+                }
+                None => serde_json::Value::Null,
+            };
+            worktree
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn git_worktrees(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    Ok(serde_json::json!(list_worktrees(&cwd)?))
+}
+
+/// 所有工作树及其状态摘要，适合同时查看多个并行任务
+#[tauri::command]
+pub async fn git_worktree_summary(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    Ok(serde_json::json!(summarize(&cwd)?))
+}
+
+#[tauri::command]
+pub async fn git_worktree_add(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    options: AddWorktreeOptions,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "worktree-add").await;
+    add_worktree(&cwd, &options)
+}
+
+#[tauri::command]
+pub async fn git_worktree_lock(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    path: String,
+    reason: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "worktree-lock").await;
+    let mut args = vec!["worktree", "lock"];
+    if let Some(reason) = reason.as_deref().filter(|r| !r.is_empty()) {
+        args.extend(["--reason", reason]);
+    }
+    args.push(&path);
+    git(&args, &cwd)?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+#[tauri::command]
+pub async fn git_worktree_unlock(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    path: String,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "worktree-unlock").await;
+    git(&["worktree", "unlock", &path], &cwd)?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+/// force 为 true 时即使有未提交的改动也删除
+#[tauri::command]
+pub async fn git_worktree_remove(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+    path: String,
+    force: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "worktree-remove").await;
+    let mut args = vec!["worktree", "remove"];
+    if force.unwrap_or(false) {
+        args.push("--force");
+    }
+    args.push(&path);
+    git(&args, &cwd)?;
+    Ok(serde_json::json!({ "ok": true }))
+}
+
+/// 清理目录已不存在的工作树记录，返回被清理的路径
+#[tauri::command]
+pub async fn git_worktree_prune(
+    state: State<'_, AppState>,
+    queue: State<'_, GitQueue>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let _op = queue.begin(&cwd, "worktree-prune").await;
+    let pruned: Vec<serde_json::Value> = list_worktrees(&cwd)?
+        .into_iter()
+        .filter(|w| w["prunable"] == true)
+        .map(|w| w["path"].clone())
+        .collect();
+    git(&["worktree", "prune"], &cwd)?;
+    Ok(serde_json::json!({ "ok": true, "pruned": pruned }))
+}
+
+/// 把工作区切换到该工作树，并在其中启动一个终端
+#[tauri::command]
+pub async fn git_worktree_open(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    pty: State<'_, PtyManager>,
+    path: String,
+    shell_type: Option<ShellType>,
+) -> Result<serde_json::Value, String> {
+    let cwd = state.get_root().to_string_lossy().to_string();
+    let known = list_worktrees(&cwd)?
+        .iter()
+        .any(|w| w["path"].as_str().is_some_and(|p| same_path(p, &path)));
+    if !known {
+        return Err(format!("{} is not a worktree of this repository", path));
+    }
+    state.set_root(PathBuf::from(&path));
+    let root = state.get_root().to_string_lossy().to_string();
+    let pty_id = pty.spawn(app, root.clone(), shell_type)?;
+    Ok(serde_json::json!({
+        "ok": true,
+        "path": root,
+        "projectName": state.get_project_name(),
+        "ptyId": pty_id,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::{commit_file, init_repo};
+    use tempfile::tempdir;
+
+    #[test]
+    fn parses_porcelain_worktree_list() {
+        let raw = "worktree /repo\0HEAD abc\0branch refs/heads/main\0\0worktree /repo-feat\0HEAD def\0detached\0locked agent running\0\0worktree /gone\0HEAD 123\0branch refs/heads/old\0prunable gitdir file points to non-existent location\0\0";
+
+        let worktrees = parse_worktrees(raw);
+
+        assert_eq!(worktrees.len(), 3);
+        assert_eq!(worktrees[0]["main"], true);
+        assert_eq!(worktrees[0]["branch"], "main");
+        assert_eq!(worktrees[1]["detached"], true);
+        assert_eq!(worktrees[1]["lockReason"], "agent running");
+        assert_eq!(worktrees[2]["prunable"], true);
+    }
+
+    #[test]
+    fn adds_new_and_existing_branch_worktrees_and_summarizes() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "init");
+        git(&["branch", "existing"], &cwd).unwrap();
+        let parent = tempdir().unwrap();
+        let new_path = parent.path().join("agent-1").to_string_lossy().to_string();
+        let existing_path = parent.path().join("agent-2").to_string_lossy().to_string();
+
+        let added = add_worktree(
+            &cwd,
+            &AddWorktreeOptions { path: Some(new_path.clone()), new_branch: Some("agent/one".into()), ..Default::default() },
+        )
+        .unwrap();
+        assert_eq!(added["worktree"]["branch"], "agent/one");
+        add_worktree(
+            &cwd,
+            &AddWorktreeOptions { path: Some(existing_path.clone()), branch: Some("existing".into()), ..Default::default() },
+        )
+        .unwrap();
+        std::fs::write(Path::new(&new_path).join("b.txt"), "b\n").unwrap();
+
+        let summary = summarize(&cwd).unwrap();
+
+        assert_eq!(summary.len(), 3);
+        let agent = summary.iter().find(|w| w["branch"] == "agent/one").unwrap();
+        assert_eq!(agent["status"]["untracked"], 1);
+        assert_eq!(agent["status"]["clean"], false);
+        let existing = summary.iter().find(|w| w["branch"] == "existing").unwrap();
+        assert_eq!(existing["status"]["clean"], true);
+    }
+
+    #[test]
+    fn default_path_sits_next_to_main_worktree() {
+        let (_dir, cwd) = init_repo();
+        commit_file(&cwd, "a.txt", "a\n", "init");
+
+        let path = default_worktree_path(&cwd, "feature/login").unwrap();
+
+        let main = Path::new(&cwd).canonicalize().unwrap();
+        let expected = format!("{}-feature-login", main.file_name().unwrap().to_string_lossy());
+        assert_eq!(path.file_name().unwrap().to_string_lossy(), expected);
+    }
+}
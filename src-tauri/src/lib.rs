@@ -7,6 +7,15 @@ use tauri::Manager;
 use state::AppState;
 use pty::PtyManager;
 use commands::ssh::SSHManager;
+use commands::git::askpass::AskpassBridge;
+use commands::git::queue::GitQueue;
+use commands::git::sync::AutoFetch;
+use commands::git::task::GitTasks;
+
+/// 被 git / ssh 作为 askpass 程序启动时处理凭据提示并返回退出码
+pub fn askpass_main() -> Option<i32> {
+    commands::git::askpass::run_helper()
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -15,6 +24,16 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .manage(PtyManager::new())
         .manage(SSHManager::new())
+        .manage(GitTasks::new())
+        .manage(GitQueue::new())
+        .manage(AutoFetch::new())
+        .manage(AskpassBridge::new())
+        .on_window_event(|window, event| {
+            // 窗口失去焦点时暂停后台 fetch
+            if let tauri::WindowEvent::Focused(focused) = event {
+                window.state::<AutoFetch>().set_focused(*focused);
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             // files
             commands::files::get_file_tree,
@@ -29,20 +48,83 @@ pub fn run() {
             commands::files::read_file_binary,
             commands::files::reveal_in_explorer,
             // git
-            commands::git::git_status,
+            commands::git::status::git_status,
             commands::git::git_stage,
             commands::git::git_unstage,
             commands::git::git_discard_paths,
             commands::git::git_discard_all,
-            commands::git::git_commit,
-            commands::git::git_branches,
-            commands::git::git_log,
+            commands::git::commit::git_commit,
+            commands::git::task::git_cancel_task,
+            commands::git::queue::git_queue_state,
+            commands::git::queue::git_remove_stale_lock,
+            commands::git::askpass::git_credential_respond,
+            commands::git::branch::git_branches,
+            commands::git::branch::git_branch_create,
+            commands::git::branch::git_checkout_branch,
+            commands::git::branch::git_branch_rename,
+            commands::git::branch::git_branch_delete,
+            commands::git::branch::git_branch_set_upstream,
+            commands::git::stash::git_stash_save,
+            commands::git::stash::git_stash_list,
+            commands::git::stash::git_stash_show,
+            commands::git::stash::git_stash_apply,
+            commands::git::stash::git_stash_pop,
+            commands::git::stash::git_stash_drop,
+            commands::git::stash::git_stash_branch,
+            commands::git::conflict::git_conflicts,
+            commands::git::conflict::git_conflict_versions,
+            commands::git::conflict::git_conflict_merge,
+            commands::git::conflict::git_conflict_resolve,
+            commands::git::conflict::git_conflict_take,
+            commands::git::conflict::git_abort_operation,
+            commands::git::conflict::git_continue_operation,
+            commands::git::cherry_pick::git_cherry_pick,
+            commands::git::cherry_pick::git_revert,
+            commands::git::rebase::git_rebase_todo,
+            commands::git::rebase::git_rebase_execute,
+            commands::git::rebase::git_rebase_progress,
+            commands::git::rebase::git_rebase_continue,
+            commands::git::rebase::git_rebase_abort,
+            commands::git::reset::git_reset,
+            commands::git::reset::git_reset_backups,
+            commands::git::reset::git_reset_restore_backup,
+            commands::git::reset::git_reflog,
+            commands::git::reset::git_undo,
+            commands::git::compare::git_compare,
+            commands::git::compare::git_compare_file,
+            commands::git::diff::git_diff_hunks,
+            commands::git::history::git_file_history,
+            commands::git::history::git_file_revision_diff,
+            commands::git::ignore::git_ignore_add,
+            commands::git::ignore::git_check_ignore,
+            commands::git::lfs::git_lfs_status,
+            commands::git::lfs::git_lfs_track,
+            commands::git::submodule::git_submodules,
+            commands::git::submodule::git_submodule_init,
+            commands::git::submodule::git_submodule_sync,
+            commands::git::submodule::git_submodule_update,
+            commands::git::worktree::git_worktrees,
+            commands::git::worktree::git_worktree_summary,
+            commands::git::worktree::git_worktree_add,
+            commands::git::worktree::git_worktree_lock,
+            commands::git::worktree::git_worktree_unlock,
+            commands::git::worktree::git_worktree_remove,
+            commands::git::worktree::git_worktree_prune,
+            commands::git::worktree::git_worktree_open,
+            commands::git::log::git_log,
+            commands::git::graph::git_graph,
             commands::git::git_show,
             commands::git::git_file_diff,
             commands::git::git_staged_diff,
             commands::git::git_sync_status,
-            commands::git::git_push,
-            commands::git::git_pull,
+            commands::git::remote::git_push,
+            commands::git::remote::git_pull,
+            commands::git::remote::git_fetch,
+            commands::git::tag::git_tags,
+            commands::git::tag::git_tag_create,
+            commands::git::tag::git_tag_delete,
+            commands::git::tag::git_tag_push,
+            commands::git::tag::git_changelog,
             commands::git::git_working_diff,
             // workspace
             commands::workspace::get_workspace,
@@ -90,6 +172,8 @@ pub fn run() {
             app.manage(state);
             let state = app.state::<AppState>();
             watcher::start_watcher(app.handle().clone(), &state);
+            commands::git::sync::start_auto_fetch(app.handle().clone());
+            app.state::<GitQueue>().attach(app.handle().clone());
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -2,5 +2,9 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // 作为 GIT_ASKPASS / SSH_ASKPASS 被调用时只转发提示，不启动界面
+    if let Some(code) = openloom_lib::askpass_main() {
+        std::process::exit(code);
+    }
     openloom_lib::run()
 }
@@ -20,6 +20,14 @@ fn is_ignored_path(rel: &str) -> bool {
     rel.split('/').any(|p| IGNORED_DIRS.contains(&p))
 }
 
+/// 分支引用变化（commit、fetch、reset、终端里执行的 git 命令等）会影响 ahead/behind
+fn is_git_ref_path(rel: &str) -> bool {
+    match rel.strip_prefix(".git/") {
+        Some(p) => matches!(p, "HEAD" | "packed-refs" | "FETCH_HEAD") || p.starts_with("refs/"),
+        None => false,
+    }
+}
+
 pub fn start_watcher(app: AppHandle, state: &AppState) {
     let root = state.get_root();
     let root_clone = root.clone();
@@ -48,8 +56,13 @@ pub fn start_watcher(app: AppHandle, state: &AppState) {
         loop {
             match rx.recv() {
                 Ok(Ok(events)) => {
+                    let mut refs_changed = false;
                     for event in events {
-                        handle_event(&app, &root_clone, &event);
+                        refs_changed |= handle_event(&app, &root_clone, &event);
+                    }
+                    if refs_changed {
+                        let cwd = root_clone.to_string_lossy().to_string();
+                        crate::commands::git::sync::emit_sync_changed(&app, &cwd);
                     }
                 }
                 Ok(Err(e)) => {
@@ -61,15 +74,16 @@ pub fn start_watcher(app: AppHandle, state: &AppState) {
     });
 }
 
-fn handle_event(app: &AppHandle, root: &PathBuf, event: &notify_debouncer_mini::DebouncedEvent) {
+/// 返回该事件是否涉及 git 引用
+fn handle_event(app: &AppHandle, root: &PathBuf, event: &notify_debouncer_mini::DebouncedEvent) -> bool {
     let path = &event.path;
     let rel = match path.strip_prefix(root) {
         Ok(r) => r.to_string_lossy().replace('\\', "/"),
-        Err(_) => return,
+        Err(_) => return false,
     };
 
     if is_ignored_path(&rel) {
-        return;
+        return is_git_ref_path(&rel);
     }
 
     if path.exists() && path.is_file() {
@@ -93,4 +107,5 @@ fn handle_event(app: &AppHandle, root: &PathBuf, event: &notify_debouncer_mini::
             }),
         );
     }
+    false
 }